@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+/// Answers file used by `verify` when no other file is given.
+pub fn default_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("answers.txt")
+}
+
+/// Why an answer was rejected when it was submitted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rejection {
+    TooLow,
+    TooHigh,
+    Wrong,
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::TooLow => write!(f, "too low"),
+            Rejection::TooHigh => write!(f, "too high"),
+            Rejection::Wrong => write!(f, "wrong"),
+        }
+    }
+}
+
+/// What is known about the answer of one part.
+#[derive(Debug, Default, PartialEq)]
+pub struct Expected {
+    /// Accepted answer, `None` while it is not known yet.
+    pub answer: Option<String>,
+    /// Answers that were submitted and rejected.
+    pub rejected: Vec<(String, Rejection)>,
+}
+
+/// Outcome of comparing a computed answer to an [`Expected`] one.
+#[derive(Debug, PartialEq)]
+pub enum Verdict<'a> {
+    Correct,
+    /// Answer differs from the accepted one.
+    Regression {
+        expected: &'a str,
+        rejection: Option<Rejection>,
+    },
+    /// No accepted answer is known, but the answer matches a rejected one.
+    Rejected(Rejection),
+    Unchecked,
+}
+
+impl Expected {
+    pub fn check(&self, answer: &str) -> Verdict<'_> {
+        let rejection = self
+            .rejected
+            .iter()
+            .find(|(rejected, _)| rejected == answer)
+            .map(|&(_, rejection)| rejection);
+
+        match (&self.answer, rejection) {
+            (Some(expected), _) if expected == answer => Verdict::Correct,
+            (Some(expected), rejection) => Verdict::Regression {
+                expected,
+                rejection,
+            },
+            (None, Some(rejection)) => Verdict::Rejected(rejection),
+            (None, None) => Verdict::Unchecked,
+        }
+    }
+}
+
+/// Expected answers, keyed by day and part.
+#[derive(Debug, Default)]
+pub struct Answers(HashMap<(u8, u8), Expected>);
+
+impl Answers {
+    pub fn load(path: &Path) -> anyhow::Result<Answers> {
+        let input = std::fs::read_to_string(path)
+            .with_context(|| format!("Error while reading answers {}", path.display()))?;
+        Answers::parse(&input).with_context(|| format!("Bad answers file {}", path.display()))
+    }
+
+    /// Parses lines of `day part answer rejected...`, where the answer is `?` when unknown and
+    /// each rejected answer is prefixed with `<` (too low), `>` (too high) or `!` (wrong).
+    /// Everything after a `#` is a comment.
+    pub fn parse(input: &str) -> anyhow::Result<Answers> {
+        let mut answers = HashMap::new();
+
+        for (ix, line) in input.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, expected) = parse_line(line).with_context(|| format!("Line {}", ix + 1))?;
+            if answers.insert(key, expected).is_some() {
+                bail!(
+                    "Line {}: day {} part {} is listed twice",
+                    ix + 1,
+                    key.0,
+                    key.1
+                );
+            }
+        }
+
+        Ok(Answers(answers))
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&Expected> {
+        self.0.get(&(day, part))
+    }
+
+    /// Day and part of every listed answer.
+    pub fn keys(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.0.keys().copied()
+    }
+}
+
+fn parse_line(line: &str) -> anyhow::Result<((u8, u8), Expected)> {
+    let mut split = line.split_ascii_whitespace();
+
+    let day = split
+        .next()
+        .context("Missing day")?
+        .parse::<u8>()
+        .context("Bad day")?;
+    let part = split
+        .next()
+        .context("Missing part")?
+        .parse::<u8>()
+        .context("Bad part")?;
+    let answer = match split.next().context("Missing answer")? {
+        "?" => None,
+        answer => Some(answer.to_owned()),
+    };
+
+    let rejected = split
+        .map(|s| {
+            let rejection = match s.chars().next() {
+                Some('<') => Rejection::TooLow,
+                Some('>') => Rejection::TooHigh,
+                Some('!') => Rejection::Wrong,
+                _ => bail!("Rejected answer `{}` must start with <, > or !", s),
+            };
+            Ok((s[1..].to_owned(), rejection))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(((day, part), Expected { answer, rejected }))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# day part answer rejected
+            15 2 2876 >2882
+            13 2 ?   # rendered letters
+
+            22 2 1261885414840992 <1261788327439723 >1525886909356651",
+        )
+        .unwrap();
+
+        assert_eq!(
+            answers.get(15, 2),
+            Some(&Expected {
+                answer: Some("2876".to_owned()),
+                rejected: vec![("2882".to_owned(), Rejection::TooHigh)]
+            })
+        );
+        assert_eq!(answers.get(13, 2), Some(&Expected::default()));
+        assert_eq!(answers.get(22, 2).unwrap().rejected.len(), 2);
+        assert_eq!(answers.get(1, 1), None);
+
+        assert!(Answers::parse("15 2").is_err());
+        assert!(Answers::parse("15 2 2876 2882").is_err());
+        assert!(Answers::parse("15 2 2876\n15 2 2876").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let expected = Expected {
+            answer: Some("2876".to_owned()),
+            rejected: vec![("2882".to_owned(), Rejection::TooHigh)],
+        };
+
+        assert_eq!(expected.check("2876"), Verdict::Correct);
+        assert_eq!(
+            expected.check("2882"),
+            Verdict::Regression {
+                expected: "2876",
+                rejection: Some(Rejection::TooHigh)
+            }
+        );
+        assert_eq!(
+            expected.check("1"),
+            Verdict::Regression {
+                expected: "2876",
+                rejection: None
+            }
+        );
+
+        let unknown = Expected {
+            answer: None,
+            rejected: vec![("14429".to_owned(), Rejection::TooHigh)],
+        };
+        assert_eq!(
+            unknown.check("14429"),
+            Verdict::Rejected(Rejection::TooHigh)
+        );
+        assert_eq!(unknown.check("4563"), Verdict::Unchecked);
+    }
+}
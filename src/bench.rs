@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+
+use crate::solution::DynSolution;
+
+/// Measured step of a day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Phase {
+    fn from_str(s: &str) -> anyhow::Result<Phase> {
+        Ok(match s {
+            "parse" => Phase::Parse,
+            "part1" => Phase::Part1,
+            "part2" => Phase::Part2,
+            _ => bail!("Unknown phase `{}`", s),
+        })
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part1 => write!(f, "part1"),
+            Phase::Part2 => write!(f, "part2"),
+        }
+    }
+}
+
+/// Timing statistics over all iterations of one phase.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Stats {
+        assert!(!samples.is_empty(), "Need at least one sample");
+        samples.sort_unstable();
+
+        Stats {
+            min: samples[0],
+            median: percentile(&samples, 50),
+            p95: percentile(&samples, 95),
+        }
+    }
+}
+
+/// Nearest rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    let rank = (p * sorted.len()).div_ceil(100);
+    sorted[rank.max(1) - 1]
+}
+
+/// Runs `f` and returns its result together with the time it took.
+pub fn time<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let res = f();
+    (res, start.elapsed())
+}
+
+/// Times parsing `input` and both parts of `solution` over `iterations` runs, failing on the
+/// first error.
+pub fn day(
+    solution: &dyn DynSolution,
+    input: &str,
+    iterations: usize,
+) -> anyhow::Result<[(Phase, Stats); 3]> {
+    let mut samples = [vec![], vec![], vec![]];
+
+    for _ in 0..iterations {
+        let (parsed, elapsed) = time(|| solution.parse(input));
+        let parsed = parsed?;
+        samples[0].push(elapsed);
+
+        let (answer, elapsed) = time(|| parsed.part_01());
+        answer?;
+        samples[1].push(elapsed);
+
+        let (answer, elapsed) = time(|| parsed.part_02());
+        answer?;
+        samples[2].push(elapsed);
+    }
+
+    let [parse, part1, part2] = samples;
+    Ok([
+        (Phase::Parse, Stats::from_samples(parse)),
+        (Phase::Part1, Stats::from_samples(part1)),
+        (Phase::Part2, Stats::from_samples(part2)),
+    ])
+}
+
+/// Stats of every measured phase, keyed by day and phase.
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline(HashMap<(u8, Phase), Stats>);
+
+impl Baseline {
+    pub fn insert(&mut self, day: u8, phase: Phase, stats: Stats) {
+        self.0.insert((day, phase), stats);
+    }
+
+    pub fn get(&self, day: u8, phase: Phase) -> Option<&Stats> {
+        self.0.get(&(day, phase))
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Baseline> {
+        let input = std::fs::read_to_string(path)
+            .with_context(|| format!("Error while reading baseline {}", path.display()))?;
+        Baseline::parse(&input).with_context(|| format!("Bad baseline file {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_string())
+            .with_context(|| format!("Error while writing baseline {}", path.display()))
+    }
+
+    /// Parses lines of `day phase min median p95`, times in nanoseconds.
+    pub fn parse(input: &str) -> anyhow::Result<Baseline> {
+        let mut baseline = Baseline::default();
+
+        for (ix, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = line.split_ascii_whitespace().collect::<Vec<_>>();
+            if fields.len() != 5 {
+                bail!("Line {}: expected `day phase min median p95`", ix + 1);
+            }
+
+            let nanos = |s: &str| {
+                s.parse::<u64>()
+                    .map(Duration::from_nanos)
+                    .with_context(|| format!("Line {}: bad time `{}`", ix + 1, s))
+            };
+
+            let day = fields[0]
+                .parse::<u8>()
+                .with_context(|| format!("Line {}: bad day", ix + 1))?;
+            let phase = Phase::from_str(fields[1]).with_context(|| format!("Line {}", ix + 1))?;
+            let stats = Stats {
+                min: nanos(fields[2])?,
+                median: nanos(fields[3])?,
+                p95: nanos(fields[4])?,
+            };
+
+            baseline.insert(day, phase, stats);
+        }
+
+        Ok(baseline)
+    }
+}
+
+impl Display for Baseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# day phase min median p95 (nanoseconds)")?;
+
+        let mut keys = self.0.keys().collect::<Vec<_>>();
+        keys.sort_by_key(|&&(day, phase)| (day, phase as u8));
+
+        for &(day, phase) in keys {
+            let stats = &self.0[&(day, phase)];
+            writeln!(
+                f,
+                "{:02} {} {} {} {}",
+                day,
+                phase,
+                stats.min.as_nanos(),
+                stats.median.as_nanos(),
+                stats.p95.as_nanos()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Relative change of the median against a baseline, in percent.
+pub fn change(baseline: &Stats, current: &Stats) -> f64 {
+    let base = baseline.median.as_secs_f64();
+    if base == 0.0 {
+        return 0.0;
+    }
+    (current.median.as_secs_f64() - base) / base * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn ms(millis: &[u64]) -> Vec<Duration> {
+        millis.iter().map(|&m| Duration::from_millis(m)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(ms(&[5, 1, 4, 2, 3]));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.p95, Duration::from_millis(5));
+
+        let stats = Stats::from_samples(ms(&(1..=100).collect::<Vec<_>>()));
+        assert_eq!(stats.median, Duration::from_millis(50));
+        assert_eq!(stats.p95, Duration::from_millis(95));
+
+        let stats = Stats::from_samples(ms(&[7]));
+        assert_eq!(stats.median, Duration::from_millis(7));
+        assert_eq!(stats.p95, Duration::from_millis(7));
+    }
+
+    #[test]
+    fn test_baseline_roundtrip() {
+        let mut baseline = Baseline::default();
+        baseline.insert(19, Phase::Part1, Stats::from_samples(ms(&[300, 320, 310])));
+        baseline.insert(1, Phase::Parse, Stats::from_samples(ms(&[1])));
+
+        let saved = baseline.to_string();
+        assert!(saved.contains("\n01 parse 1000000 1000000 1000000\n"));
+        assert_eq!(Baseline::parse(&saved).unwrap(), baseline);
+
+        assert!(Baseline::parse("01 parse 1 2").is_err());
+        assert!(Baseline::parse("01 solve 1 2 3").is_err());
+    }
+
+    #[test]
+    fn test_change() {
+        let base = Stats::from_samples(ms(&[100]));
+        let slower = Stats::from_samples(ms(&[125]));
+        assert!((change(&base, &slower) - 25.0).abs() < 1e-9);
+        assert!((change(&slower, &base) + 20.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,1042 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+
+use aoc_2021::output::Format;
+
+pub const USAGE: &str = "Usage: aoc-2021 [run] [--all | --day DAYS] [--part 1|2] [--input FILE|-]
+                    [--format text|json|csv] [--jobs N]
+       aoc-2021 verify [--all | --day DAYS] [--answers FILE]
+       aoc-2021 bench [--all | --day DAYS] [--iterations N] [--save FILE] [--compare FILE]
+                      [--threshold PCT]
+       aoc-2021 generate --day DAY [--size N] [--seed N] [--output FILE]
+       aoc-2021 diff [--all | --day DAYS] [--seeds N] [--size N]
+       aoc-2021 render --day DAY [--input FILE] [--output DIR] [--scale N]
+       aoc-2021 step --day DAY [--input FILE]
+       aoc-2021 watch --day DAY [--input FILE] [--interval MS]
+       aoc-2021 sweep [--window K] [--input FILE|-]
+       aoc-2021 stats [--window K] [--step N] [--input FILE|-] [--output FILE]
+       aoc-2021 mission --input FILE [--steering direct|aim]
+
+Runs the selected days (all of them when nothing is selected). `verify` compares the answers
+with the accepted ones and fails on regressions. `bench` times parsing and both parts, and
+fails when a median got slower than the compared baseline by more than the threshold.
+`generate` writes a random input for a day, the same seed always giving the same input.
+`diff` compares naive and optimized implementations on generated inputs of growing sizes and
+reports the smallest one they disagree on. `render` draws a day as PGM/PPM images, numbered
+frames for the animated ones. `step` loads a simulation and steps through it interactively
+(`help` lists its commands). `watch` answers a day again whenever its input or one of its
+examples changes, comparing with the previous answers. `sweep` streams day 1 depth
+measurements of any length and counts how sums of K of them change from window to window.
+`stats` writes the sum, mean, min, max and median of every window of those as CSV.
+`mission` flies a day 2 mission script (with `repeat` blocks, comments and checkpoints) and
+reports where the submarine ends up and where it passed its checkpoints.
+
+Options:
+    --all           run every solved day
+    -d, --day DAYS  day selection: `15`, `10..=18`, `10..18` or a comma separated list of those
+    -p, --part N    run only part 1 or part 2
+    -i, --input F   read the puzzle input from F instead of inputs/NN_input.txt, `-` for stdin
+                    (single day only)
+    -f, --format F  print results as text (default), JSON lines or CSV
+    -j, --jobs N    run days and parts on N worker threads, still printing in day order
+    -a, --answers F read the accepted answers from F instead of answers.txt
+    -n, --iterations N  number of timed runs per day (default 10)
+    --save F        save the measured timings as a baseline to F
+    --compare F     compare the measured timings with the baseline in F
+    --threshold PCT allowed slowdown of the median in percent (default 10)
+    --size N        size of the generated input, in lines, grid rows, ... depending on the day
+                    (default: about the size of the real input), the largest one for `diff`
+    --seed N        seed of the generated input (default 0)
+    --seeds N       number of inputs of each size compared by `diff` (default 20)
+    -o, --output F  write the generated input or the statistics to F instead of stdout, or
+                    the rendered images to the directory F instead of render/
+    --scale N       size in pixels of a rendered cell (default 4)
+    --interval MS   how often `watch` checks the files, in milliseconds (default 500)
+    -w, --window K  number of measurements in a window of `sweep` or `stats` (default 3)
+    --step N        number of measurements between the starts of two windows (default 1)
+    --steering M    steering model of `mission`, `direct` (part 1) or `aim` (part 2), both
+                    when not given
+    -h, --help      print this message";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(RunArgs),
+    Verify(VerifyArgs),
+    Bench(BenchArgs),
+    Generate(GenerateArgs),
+    Diff(DiffArgs),
+    Render(RenderArgs),
+    Step(StepArgs),
+    Watch(WatchArgs),
+    Sweep(SweepArgs),
+    Stats(StatsArgs),
+    Mission(MissionArgs),
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunArgs {
+    /// Selected days, `None` meaning all of them.
+    pub days: Option<Vec<u8>>,
+    pub part: Option<u8>,
+    pub input: Option<PathBuf>,
+    pub format: Format,
+    /// Number of worker threads, 1 running everything on the main thread.
+    pub jobs: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyArgs {
+    /// Selected days, `None` meaning all of them.
+    pub days: Option<Vec<u8>>,
+    pub answers: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BenchArgs {
+    /// Selected days, `None` meaning all of them.
+    pub days: Option<Vec<u8>>,
+    pub iterations: usize,
+    pub save: Option<PathBuf>,
+    pub compare: Option<PathBuf>,
+    /// Allowed slowdown of the median, in percent.
+    pub threshold: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GenerateArgs {
+    pub day: u8,
+    /// Size of the input, `None` meaning the size of the real one.
+    pub size: Option<usize>,
+    pub seed: u64,
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DiffArgs {
+    /// Selected days, `None` meaning all of them.
+    pub days: Option<Vec<u8>>,
+    pub seeds: u64,
+    /// Largest size of the compared inputs, `None` meaning the one of each pair.
+    pub max_size: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RenderArgs {
+    pub day: u8,
+    pub input: Option<PathBuf>,
+    pub output: PathBuf,
+    /// Size in pixels of a cell.
+    pub scale: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StepArgs {
+    pub day: u8,
+    pub input: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WatchArgs {
+    pub day: u8,
+    /// Watched input instead of the default one and the examples.
+    pub input: Option<PathBuf>,
+    /// Time between two checks, in milliseconds.
+    pub interval: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SweepArgs {
+    /// Number of measurements in a window.
+    pub window: usize,
+    pub input: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StatsArgs {
+    /// Number of measurements in a window.
+    pub window: usize,
+    /// Number of measurements between the starts of two windows.
+    pub step: usize,
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+}
+
+/// Steering model of a submarine, see `d02::Steering`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Steering {
+    Direct,
+    Aim,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MissionArgs {
+    pub script: PathBuf,
+    /// Steering model, `None` meaning both.
+    pub steering: Option<Steering>,
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> anyhow::Result<Command> {
+    let mut args = args.into_iter().peekable();
+
+    match args.peek().map(|a| a.as_str()) {
+        Some("verify") => {
+            args.next();
+            parse_verify_args(args)
+        }
+        Some("bench") => {
+            args.next();
+            parse_bench_args(args)
+        }
+        Some("generate") => {
+            args.next();
+            parse_generate_args(args)
+        }
+        Some("diff") => {
+            args.next();
+            parse_diff_args(args)
+        }
+        Some("render") => {
+            args.next();
+            parse_render_args(args)
+        }
+        Some("step") => {
+            args.next();
+            parse_step_args(args)
+        }
+        Some("watch") => {
+            args.next();
+            parse_watch_args(args)
+        }
+        Some("sweep") => {
+            args.next();
+            parse_sweep_args(args)
+        }
+        Some("stats") => {
+            args.next();
+            parse_stats_args(args)
+        }
+        Some("mission") => {
+            args.next();
+            parse_mission_args(args)
+        }
+        Some("run") => {
+            args.next();
+            parse_run_args(args)
+        }
+        _ => parse_run_args(args),
+    }
+}
+
+fn parse_run_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut all = false;
+    let mut days = None;
+    let mut part = None;
+    let mut input = None;
+    let mut format = Format::Text;
+    let mut jobs = 1;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--all" => all = true,
+            "-d" | "--day" => {
+                let value = args.next().context("Missing value for --day")?;
+                days = Some(parse_days(&value)?);
+            }
+            "-p" | "--part" => {
+                let value = args.next().context("Missing value for --part")?;
+                part = Some(parse_part(&value)?);
+            }
+            "-i" | "--input" => {
+                let value = args.next().context("Missing value for --input")?;
+                input = Some(PathBuf::from(value));
+            }
+            "-f" | "--format" => {
+                let value = args.next().context("Missing value for --format")?;
+                format = Format::parse(&value)?;
+            }
+            "-j" | "--jobs" => {
+                let value = args.next().context("Missing value for --jobs")?;
+                jobs = value
+                    .parse::<usize>()
+                    .with_context(|| format!("`{}` is not a valid number of jobs", value))?;
+                if jobs == 0 {
+                    bail!("Need at least one job");
+                }
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    if all && days.is_some() {
+        bail!("--all and --day cannot be used together");
+    }
+
+    if input.is_some() && days.as_ref().map(Vec::len) != Some(1) {
+        bail!("--input needs exactly one day selected with --day");
+    }
+
+    Ok(Command::Run(RunArgs {
+        days,
+        part,
+        input,
+        format,
+        jobs,
+    }))
+}
+
+fn parse_verify_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut all = false;
+    let mut days = None;
+    let mut answers = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--all" => all = true,
+            "-d" | "--day" => {
+                let value = args.next().context("Missing value for --day")?;
+                days = Some(parse_days(&value)?);
+            }
+            "-a" | "--answers" => {
+                let value = args.next().context("Missing value for --answers")?;
+                answers = Some(PathBuf::from(value));
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    if all && days.is_some() {
+        bail!("--all and --day cannot be used together");
+    }
+
+    Ok(Command::Verify(VerifyArgs { days, answers }))
+}
+
+fn parse_bench_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut all = false;
+    let mut days = None;
+    let mut iterations = 10;
+    let mut save = None;
+    let mut compare = None;
+    let mut threshold = 10.0;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--all" => all = true,
+            "-d" | "--day" => {
+                let value = args.next().context("Missing value for --day")?;
+                days = Some(parse_days(&value)?);
+            }
+            "-n" | "--iterations" => {
+                let value = args.next().context("Missing value for --iterations")?;
+                iterations = value
+                    .parse::<usize>()
+                    .with_context(|| format!("`{}` is not a valid number of iterations", value))?;
+                if iterations == 0 {
+                    bail!("Need at least one iteration");
+                }
+            }
+            "--save" => {
+                let value = args.next().context("Missing value for --save")?;
+                save = Some(PathBuf::from(value));
+            }
+            "--compare" => {
+                let value = args.next().context("Missing value for --compare")?;
+                compare = Some(PathBuf::from(value));
+            }
+            "--threshold" => {
+                let value = args.next().context("Missing value for --threshold")?;
+                threshold = value
+                    .parse::<f64>()
+                    .with_context(|| format!("`{}` is not a valid threshold", value))?;
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    if all && days.is_some() {
+        bail!("--all and --day cannot be used together");
+    }
+
+    Ok(Command::Bench(BenchArgs {
+        days,
+        iterations,
+        save,
+        compare,
+        threshold,
+    }))
+}
+
+fn parse_generate_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut day = None;
+    let mut size = None;
+    let mut seed = 0;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-d" | "--day" => {
+                let value = args.next().context("Missing value for --day")?;
+                day = Some(parse_day(&value)?);
+            }
+            "--size" => {
+                let value = args.next().context("Missing value for --size")?;
+                let value = value
+                    .parse::<usize>()
+                    .with_context(|| format!("`{}` is not a valid size", value))?;
+                if value == 0 {
+                    bail!("Size must be at least 1");
+                }
+                size = Some(value);
+            }
+            "--seed" => {
+                let value = args.next().context("Missing value for --seed")?;
+                seed = value
+                    .parse::<u64>()
+                    .with_context(|| format!("`{}` is not a valid seed", value))?;
+            }
+            "-o" | "--output" => {
+                let value = args.next().context("Missing value for --output")?;
+                output = Some(PathBuf::from(value));
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    let day = day.context("generate needs a day selected with --day")?;
+
+    Ok(Command::Generate(GenerateArgs {
+        day,
+        size,
+        seed,
+        output,
+    }))
+}
+
+fn parse_diff_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut all = false;
+    let mut days = None;
+    let mut seeds = 20;
+    let mut max_size = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--all" => all = true,
+            "-d" | "--day" => {
+                let value = args.next().context("Missing value for --day")?;
+                days = Some(parse_days(&value)?);
+            }
+            "--seeds" => {
+                let value = args.next().context("Missing value for --seeds")?;
+                seeds = value
+                    .parse::<u64>()
+                    .with_context(|| format!("`{}` is not a valid number of seeds", value))?;
+                if seeds == 0 {
+                    bail!("Need at least one seed");
+                }
+            }
+            "--size" => {
+                let value = args.next().context("Missing value for --size")?;
+                let value = value
+                    .parse::<usize>()
+                    .with_context(|| format!("`{}` is not a valid size", value))?;
+                if value == 0 {
+                    bail!("Size must be at least 1");
+                }
+                max_size = Some(value);
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    if all && days.is_some() {
+        bail!("--all and --day cannot be used together");
+    }
+
+    Ok(Command::Diff(DiffArgs {
+        days,
+        seeds,
+        max_size,
+    }))
+}
+
+fn parse_render_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut day = None;
+    let mut input = None;
+    let mut output = PathBuf::from("render");
+    let mut scale = 4;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-d" | "--day" => {
+                let value = args.next().context("Missing value for --day")?;
+                day = Some(parse_day(&value)?);
+            }
+            "-i" | "--input" => {
+                let value = args.next().context("Missing value for --input")?;
+                input = Some(PathBuf::from(value));
+            }
+            "-o" | "--output" => {
+                let value = args.next().context("Missing value for --output")?;
+                output = PathBuf::from(value);
+            }
+            "--scale" => {
+                let value = args.next().context("Missing value for --scale")?;
+                scale = value
+                    .parse::<usize>()
+                    .with_context(|| format!("`{}` is not a valid scale", value))?;
+                if scale == 0 {
+                    bail!("Scale must be at least 1");
+                }
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    let day = day.context("render needs a day selected with --day")?;
+
+    Ok(Command::Render(RenderArgs {
+        day,
+        input,
+        output,
+        scale,
+    }))
+}
+
+fn parse_step_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut day = None;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-d" | "--day" => {
+                let value = args.next().context("Missing value for --day")?;
+                day = Some(parse_day(&value)?);
+            }
+            "-i" | "--input" => {
+                let value = args.next().context("Missing value for --input")?;
+                input = Some(PathBuf::from(value));
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    let day = day.context("step needs a day selected with --day")?;
+
+    Ok(Command::Step(StepArgs { day, input }))
+}
+
+fn parse_watch_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut day = None;
+    let mut input = None;
+    let mut interval = 500;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-d" | "--day" => {
+                let value = args.next().context("Missing value for --day")?;
+                day = Some(parse_day(&value)?);
+            }
+            "-i" | "--input" => {
+                let value = args.next().context("Missing value for --input")?;
+                input = Some(PathBuf::from(value));
+            }
+            "--interval" => {
+                let value = args.next().context("Missing value for --interval")?;
+                interval = value
+                    .parse::<u64>()
+                    .with_context(|| format!("`{}` is not a valid interval", value))?;
+                if interval == 0 {
+                    bail!("Interval must be at least 1 millisecond");
+                }
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    let day = day.context("watch needs a day selected with --day")?;
+
+    Ok(Command::Watch(WatchArgs {
+        day,
+        input,
+        interval,
+    }))
+}
+
+fn parse_sweep_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut window = 3;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-w" | "--window" => {
+                let value = args.next().context("Missing value for --window")?;
+                window = value
+                    .parse::<usize>()
+                    .with_context(|| format!("`{}` is not a valid window", value))?;
+                if window == 0 {
+                    bail!("Window must be at least 1 measurement");
+                }
+            }
+            "-i" | "--input" => {
+                let value = args.next().context("Missing value for --input")?;
+                input = Some(PathBuf::from(value));
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    Ok(Command::Sweep(SweepArgs { window, input }))
+}
+
+fn parse_stats_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut window = 3;
+    let mut step = 1;
+    let mut input = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-w" | "--window" => {
+                let value = args.next().context("Missing value for --window")?;
+                window = value
+                    .parse::<usize>()
+                    .with_context(|| format!("`{}` is not a valid window", value))?;
+                if window == 0 {
+                    bail!("Window must be at least 1 measurement");
+                }
+            }
+            "--step" => {
+                let value = args.next().context("Missing value for --step")?;
+                step = value
+                    .parse::<usize>()
+                    .with_context(|| format!("`{}` is not a valid step", value))?;
+                if step == 0 {
+                    bail!("Step must be at least 1 measurement");
+                }
+            }
+            "-i" | "--input" => {
+                let value = args.next().context("Missing value for --input")?;
+                input = Some(PathBuf::from(value));
+            }
+            "-o" | "--output" => {
+                let value = args.next().context("Missing value for --output")?;
+                output = Some(PathBuf::from(value));
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    Ok(Command::Stats(StatsArgs {
+        window,
+        step,
+        input,
+        output,
+    }))
+}
+
+fn parse_mission_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Command> {
+    let mut script = None;
+    let mut steering = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-i" | "--input" => {
+                let value = args.next().context("Missing value for --input")?;
+                script = Some(PathBuf::from(value));
+            }
+            "--steering" => {
+                let value = args.next().context("Missing value for --steering")?;
+                steering = Some(match value.as_str() {
+                    "direct" => Steering::Direct,
+                    "aim" => Steering::Aim,
+                    _ => bail!("Steering must be direct or aim, got `{}`", value),
+                });
+            }
+            _ => bail!("Unknown argument `{}`", arg),
+        }
+    }
+
+    let script = script.context("mission needs a script given with --input")?;
+
+    Ok(Command::Mission(MissionArgs { script, steering }))
+}
+
+fn parse_part(s: &str) -> anyhow::Result<u8> {
+    match s {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => bail!("Part must be 1 or 2, got `{}`", s),
+    }
+}
+
+/// Parses `15`, `10..18`, `10..=18` and comma separated lists of those.
+fn parse_days(s: &str) -> anyhow::Result<Vec<u8>> {
+    let mut days = vec![];
+
+    for spec in s.split(',') {
+        let spec = spec.trim();
+        let (from, to) = if let Some((from, to)) = spec.split_once("..=") {
+            (parse_day(from)?, parse_day(to)?)
+        } else if let Some((from, to)) = spec.split_once("..") {
+            let to = parse_day(to)?;
+            if to <= 1 {
+                bail!("Empty day range `{}`", spec);
+            }
+            (parse_day(from)?, to - 1)
+        } else {
+            let day = parse_day(spec)?;
+            (day, day)
+        };
+
+        if from > to {
+            bail!("Empty day range `{}`", spec);
+        }
+
+        for day in from..=to {
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+    }
+
+    days.sort_unstable();
+    Ok(days)
+}
+
+fn parse_day(s: &str) -> anyhow::Result<u8> {
+    let day = s
+        .trim()
+        .parse::<u8>()
+        .with_context(|| format!("`{}` is not a valid day", s))?;
+
+    if !(1..=25).contains(&day) {
+        bail!("Day must be between 1 and 25, got {}", day);
+    }
+    Ok(day)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|a| a.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("15").unwrap(), vec![15]);
+        assert_eq!(parse_days("10..=13").unwrap(), vec![10, 11, 12, 13]);
+        assert_eq!(parse_days("10..13").unwrap(), vec![10, 11, 12]);
+        assert_eq!(parse_days("5,1..=3,2").unwrap(), vec![1, 2, 3, 5]);
+
+        assert!(parse_days("0").is_err());
+        assert!(parse_days("26").is_err());
+        assert!(parse_days("13..10").is_err());
+        assert!(parse_days("x").is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(args("")).unwrap(),
+            Command::Run(RunArgs {
+                days: None,
+                part: None,
+                input: None,
+                format: Format::Text,
+                jobs: 1
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("run --day 15 --part 2 --input other.txt")).unwrap(),
+            Command::Run(RunArgs {
+                days: Some(vec![15]),
+                part: Some(2),
+                input: Some(PathBuf::from("other.txt")),
+                format: Format::Text,
+                jobs: 1
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("run --all --format csv --jobs 4")).unwrap(),
+            Command::Run(RunArgs {
+                days: None,
+                part: None,
+                input: None,
+                format: Format::Csv,
+                jobs: 4
+            })
+        );
+
+        assert_eq!(parse_args(args("--help")).unwrap(), Command::Help);
+    }
+
+    #[test]
+    fn test_parse_verify_args() {
+        assert_eq!(
+            parse_args(args("verify")).unwrap(),
+            Command::Verify(VerifyArgs {
+                days: None,
+                answers: None
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("verify --day 15,22 --answers other.txt")).unwrap(),
+            Command::Verify(VerifyArgs {
+                days: Some(vec![15, 22]),
+                answers: Some(PathBuf::from("other.txt"))
+            })
+        );
+
+        assert!(parse_args(args("verify --all --day 3")).is_err());
+        assert!(parse_args(args("verify --part 1")).is_err());
+    }
+
+    #[test]
+    fn test_parse_bench_args() {
+        assert_eq!(
+            parse_args(args("bench")).unwrap(),
+            Command::Bench(BenchArgs {
+                days: None,
+                iterations: 10,
+                save: None,
+                compare: None,
+                threshold: 10.0
+            })
+        );
+
+        assert_eq!(
+            parse_args(args(
+                "bench -d 19,23 -n 3 --save new.txt --compare old.txt --threshold 25"
+            ))
+            .unwrap(),
+            Command::Bench(BenchArgs {
+                days: Some(vec![19, 23]),
+                iterations: 3,
+                save: Some(PathBuf::from("new.txt")),
+                compare: Some(PathBuf::from("old.txt")),
+                threshold: 25.0
+            })
+        );
+
+        assert!(parse_args(args("bench -n 0")).is_err());
+        assert!(parse_args(args("bench --threshold x")).is_err());
+    }
+
+    #[test]
+    fn test_parse_generate_args() {
+        assert_eq!(
+            parse_args(args("generate --day 19")).unwrap(),
+            Command::Generate(GenerateArgs {
+                day: 19,
+                size: None,
+                seed: 0,
+                output: None
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("generate -d 15 --size 500 --seed 7 -o big.txt")).unwrap(),
+            Command::Generate(GenerateArgs {
+                day: 15,
+                size: Some(500),
+                seed: 7,
+                output: Some(PathBuf::from("big.txt"))
+            })
+        );
+
+        assert!(parse_args(args("generate")).is_err());
+        assert!(parse_args(args("generate --day 1..=3")).is_err());
+        assert!(parse_args(args("generate --day 6 --seed x")).is_err());
+        assert!(parse_args(args("generate --day 1 --size 0")).is_err());
+    }
+
+    #[test]
+    fn test_parse_diff_args() {
+        assert_eq!(
+            parse_args(args("diff")).unwrap(),
+            Command::Diff(DiffArgs {
+                days: None,
+                seeds: 20,
+                max_size: None
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("diff --day 14,22 --seeds 5 --size 8")).unwrap(),
+            Command::Diff(DiffArgs {
+                days: Some(vec![14, 22]),
+                seeds: 5,
+                max_size: Some(8)
+            })
+        );
+
+        assert!(parse_args(args("diff --seeds 0")).is_err());
+        assert!(parse_args(args("diff --size 0")).is_err());
+        assert!(parse_args(args("diff --all --day 6")).is_err());
+    }
+
+    #[test]
+    fn test_parse_render_args() {
+        assert_eq!(
+            parse_args(args("render --day 9")).unwrap(),
+            Command::Render(RenderArgs {
+                day: 9,
+                input: None,
+                output: PathBuf::from("render"),
+                scale: 4
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("render -d 20 -i big.txt -o frames --scale 2")).unwrap(),
+            Command::Render(RenderArgs {
+                day: 20,
+                input: Some(PathBuf::from("big.txt")),
+                output: PathBuf::from("frames"),
+                scale: 2
+            })
+        );
+
+        assert!(parse_args(args("render")).is_err());
+        assert!(parse_args(args("render --day 9 --scale 0")).is_err());
+    }
+
+    #[test]
+    fn test_parse_step_args() {
+        assert_eq!(
+            parse_args(args("step --day 18 -i other.txt")).unwrap(),
+            Command::Step(StepArgs {
+                day: 18,
+                input: Some(PathBuf::from("other.txt"))
+            })
+        );
+
+        assert!(parse_args(args("step")).is_err());
+        assert!(parse_args(args("step --day 11 --part 1")).is_err());
+    }
+
+    #[test]
+    fn test_parse_watch_args() {
+        assert_eq!(
+            parse_args(args("watch --day 13")).unwrap(),
+            Command::Watch(WatchArgs {
+                day: 13,
+                input: None,
+                interval: 500
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("watch -d 4 -i mine.txt --interval 100")).unwrap(),
+            Command::Watch(WatchArgs {
+                day: 4,
+                input: Some(PathBuf::from("mine.txt")),
+                interval: 100
+            })
+        );
+
+        assert!(parse_args(args("watch")).is_err());
+        assert!(parse_args(args("watch --day 4 --interval 0")).is_err());
+    }
+
+    #[test]
+    fn test_parse_sweep_args() {
+        assert_eq!(
+            parse_args(args("sweep")).unwrap(),
+            Command::Sweep(SweepArgs {
+                window: 3,
+                input: None
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("sweep -w 50 --input -")).unwrap(),
+            Command::Sweep(SweepArgs {
+                window: 50,
+                input: Some(PathBuf::from("-"))
+            })
+        );
+
+        assert!(parse_args(args("sweep --window 0")).is_err());
+        assert!(parse_args(args("sweep --day 1")).is_err());
+    }
+
+    #[test]
+    fn test_parse_stats_args() {
+        assert_eq!(
+            parse_args(args("stats")).unwrap(),
+            Command::Stats(StatsArgs {
+                window: 3,
+                step: 1,
+                input: None,
+                output: None
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("stats -w 100 --step 10 -i - -o stats.csv")).unwrap(),
+            Command::Stats(StatsArgs {
+                window: 100,
+                step: 10,
+                input: Some(PathBuf::from("-")),
+                output: Some(PathBuf::from("stats.csv"))
+            })
+        );
+
+        assert!(parse_args(args("stats --step 0")).is_err());
+        assert!(parse_args(args("stats --window")).is_err());
+    }
+
+    #[test]
+    fn test_parse_mission_args() {
+        assert_eq!(
+            parse_args(args("mission -i dive.txt")).unwrap(),
+            Command::Mission(MissionArgs {
+                script: PathBuf::from("dive.txt"),
+                steering: None
+            })
+        );
+
+        assert_eq!(
+            parse_args(args("mission --steering aim --input dive.txt")).unwrap(),
+            Command::Mission(MissionArgs {
+                script: PathBuf::from("dive.txt"),
+                steering: Some(Steering::Aim)
+            })
+        );
+
+        assert!(parse_args(args("mission")).is_err());
+        assert!(parse_args(args("mission -i dive.txt --steering sideways")).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(parse_args(args("run --all --day 3")).is_err());
+        assert!(parse_args(args("run --day 1..=3 --input x.txt")).is_err());
+        assert!(parse_args(args("run --input x.txt")).is_err());
+        assert!(parse_args(args("run --part 3")).is_err());
+        assert!(parse_args(args("run --day")).is_err());
+        assert!(parse_args(args("run --format xml")).is_err());
+        assert!(parse_args(args("run --jobs 0")).is_err());
+        assert!(parse_args(args("walk")).is_err());
+    }
+}
@@ -1,44 +1,229 @@
-use anyhow::Context;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::BufRead;
+
 use itertools::Itertools;
 
-fn inputs() -> anyhow::Result<Vec<usize>> {
-    let input_string =
-        std::fs::read_to_string("inputs/01_input.txt").context("Error while reading input")?;
+use crate::error::{self, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    const NAME: &'static str = "Sonar Sweep";
+
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        parse(input)
+    }
+
+    fn part_01(depths: &Vec<usize>) -> Result<usize> {
+        solve_part1(depths)
+    }
 
-    Ok(input_string
-        .split('\n')
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect::<Vec<_>>())
+    fn part_02(depths: &Vec<usize>) -> Result<usize> {
+        solve_part2(depths)
+    }
+
+    const INPUT_SIZE: usize = 2000;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
 }
 
-pub fn part_01() -> anyhow::Result<usize> {
-    let depths = inputs()?;
+pub fn parse(input: &str) -> Result<Vec<usize>> {
+    input
+        .lines()
+        .map(|s| error::parse_at(input, s.trim(), "depth"))
+        .collect()
+}
+
+pub fn solve_part1(depths: &[usize]) -> Result<usize> {
+    Ok(solve_01(depths))
+}
+
+pub fn solve_part2(depths: &[usize]) -> Result<usize> {
+    Ok(solve_02(depths))
+}
+
+fn solve_01(depths: &[usize]) -> usize {
+    Sweep::over(1, depths.iter().copied()).increases
+}
+
+fn solve_02(depths: &[usize]) -> usize {
+    Sweep::over(3, depths.iter().copied()).increases
+}
+
+/// How the sums of a sliding window of measurements change from one window to the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+    /// Most increases in a row.
+    pub longest_increasing_run: usize,
+}
+
+impl fmt::Display for Changes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} increases", self.increases)?;
+        writeln!(f, "{} decreases", self.decreases)?;
+        writeln!(f, "{} plateaus", self.plateaus)?;
+        write!(f, "{} increases in a row at most", self.longest_increasing_run)
+    }
+}
 
-    Ok(itertools::fold(
-        depths.iter().tuple_windows(),
-        0,
-        |acc, (d1, d2)| {
-            if d1 < d2 {
-                acc + 1
-            } else {
-                acc
+/// Sliding window over depth measurements fed one at a time, only keeping the last `window`
+/// of them.
+#[derive(Clone, Debug)]
+pub struct Sweep {
+    window: usize,
+    recent: VecDeque<usize>,
+    changes: Changes,
+    run: usize,
+}
+
+impl Sweep {
+    /// Sweep comparing sums of `window` measurements, which must be at least 1.
+    pub fn new(window: usize) -> Sweep {
+        assert!(window > 0, "a window has at least one measurement");
+        Sweep {
+            window,
+            recent: VecDeque::with_capacity(window),
+            changes: Changes::default(),
+            run: 0,
+        }
+    }
+
+    pub fn over(window: usize, depths: impl IntoIterator<Item = usize>) -> Changes {
+        let mut sweep = Sweep::new(window);
+        depths.into_iter().for_each(|depth| sweep.push(depth));
+        sweep.changes()
+    }
+
+    pub fn push(&mut self, depth: usize) {
+        if self.recent.len() < self.window {
+            self.recent.push_back(depth);
+            return;
+        }
+
+        // consecutive windows share all but their first and last measurements
+        let dropped = self.recent.pop_front().unwrap_or_default();
+        self.recent.push_back(depth);
+        match depth.cmp(&dropped) {
+            std::cmp::Ordering::Greater => {
+                self.changes.increases += 1;
+                self.run += 1;
+                self.changes.longest_increasing_run =
+                    self.changes.longest_increasing_run.max(self.run);
             }
-        },
-    ))
+            std::cmp::Ordering::Less => {
+                self.changes.decreases += 1;
+                self.run = 0;
+            }
+            std::cmp::Ordering::Equal => {
+                self.changes.plateaus += 1;
+                self.run = 0;
+            }
+        }
+    }
+
+    pub fn changes(&self) -> Changes {
+        self.changes
+    }
+}
+
+/// Sweeps the measurements read from `reader` one line at a time, so that the memory used does
+/// not depend on the length of the input. The window must be at least 1, as for [`Sweep::new`].
+pub fn sweep<R: BufRead>(reader: R, window: usize) -> Result<Changes> {
+    let mut sweep = Sweep::new(window);
+    for depth in depths(reader) {
+        sweep.push(depth?);
+    }
+    Ok(sweep.changes())
 }
 
-pub fn part_02() -> anyhow::Result<usize> {
-    let depths = inputs()?;
+/// Measurements read from `reader` one line at a time, parse errors being located in the whole
+/// input.
+pub fn depths<R: BufRead>(reader: R) -> impl Iterator<Item = Result<usize>> {
+    reader.lines().enumerate().map(|(ix, line)| {
+        let line = line?;
+        error::parse_at(&line, line.trim(), "depth").map_err(|err| err.at_line(ix + 1))
+    })
+}
+
+/// `size` depth measurements, mostly going deeper.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut depth = rng.between(100, 200);
+    (0..size)
+        .map(|_| {
+            depth = (depth + rng.between(-10, 20)).max(0);
+            depth.to_string()
+        })
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::error::Error;
 
-    Ok(itertools::fold(
-        depths.iter().tuple_windows().tuple_windows(),
-        0,
-        |acc, ((a1, a2, a3), (b1, b2, b3))| {
-            if a1 + a2 + a3 < b1 + b2 + b3 {
-                acc + 1
-            } else {
-                acc
+    const EXAMPLE: &str = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+
+    #[test]
+    fn test_sweep() {
+        let changes = sweep(EXAMPLE.as_bytes(), 1).unwrap();
+        assert_eq!(
+            changes,
+            Changes {
+                increases: 7,
+                decreases: 2,
+                plateaus: 0,
+                longest_increasing_run: 3,
             }
-        },
-    ))
+        );
+
+        let changes = sweep(EXAMPLE.as_bytes(), 3).unwrap();
+        assert_eq!((changes.increases, changes.plateaus), (5, 1));
+        assert_eq!(
+            changes.to_string(),
+            "5 increases\n1 decreases\n1 plateaus\n4 increases in a row at most"
+        );
+
+        let depths = parse(EXAMPLE).unwrap();
+        for window in 1..=12 {
+            let streamed = sweep(EXAMPLE.as_bytes(), window).unwrap();
+            let sums = depths
+                .windows(window)
+                .map(|w| w.iter().sum::<usize>())
+                .collect::<Vec<_>>();
+            let increases = sums.windows(2).filter(|w| w[0] < w[1]).count();
+            assert_eq!(streamed.increases, increases, "window {}", window);
+            assert_eq!(
+                streamed.increases + streamed.decreases + streamed.plateaus,
+                sums.len().saturating_sub(1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_sweep_errors() {
+        match sweep("199\n200\n 2x8\n".as_bytes(), 3) {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (3, 2)),
+            res => panic!("unexpected {:?}", res),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "a window has at least one measurement")]
+    fn test_sweep_empty_window() {
+        let _ = sweep(EXAMPLE.as_bytes(), 0);
+    }
 }
@@ -1,75 +1,767 @@
-use anyhow::{anyhow, bail, Context};
+use std::collections::HashMap;
+use std::fmt;
 
-enum Command {
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    const NAME: &'static str = "Dive!";
+
+    type Input = Vec<Command>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<Command>> {
+        parse(input)
+    }
+
+    fn part_01(cmds: &Vec<Command>) -> Result<u64> {
+        solve_part1(cmds)
+    }
+
+    fn part_02(cmds: &Vec<Command>) -> Result<u64> {
+        solve_part2(cmds)
+    }
+
+    const INPUT_SIZE: usize = 1000;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
     Forward(u64),
     Up(u64),
     Down(u64),
+    /// Undoes a `forward`, only found in mission scripts.
+    Back(u64),
+}
+
+impl Command {
+    /// Command of the puzzle input named `keyword`, `None` when there is no such command.
+    pub fn new(keyword: &str, val: u64) -> Option<Command> {
+        Some(match keyword {
+            "forward" => Command::Forward(val),
+            "up" => Command::Up(val),
+            "down" => Command::Down(val),
+            _ => return None,
+        })
+    }
+
+    /// Command of a mission script named `keyword`, which can also be `back`.
+    pub fn in_mission(keyword: &str, val: u64) -> Option<Command> {
+        match keyword {
+            "back" => Some(Command::Back(val)),
+            _ => Command::new(keyword, val),
+        }
+    }
+}
+
+/// Written as in the input, `forward 5`.
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Forward(val) => write!(f, "forward {}", val),
+            Command::Up(val) => write!(f, "up {}", val),
+            Command::Down(val) => write!(f, "down {}", val),
+            Command::Back(val) => write!(f, "back {}", val),
+        }
+    }
 }
 
-fn inputs() -> anyhow::Result<Vec<Command>> {
-    let input_string =
-        std::fs::read_to_string("inputs/02_input.txt").context("Error while reading input")?;
+pub fn parse(input: &str) -> Result<Vec<Command>> {
+    input
+        .lines()
+        .map(|line| parse_line(input, line))
+        .collect::<Result<Vec<_>>>()
+}
+
+pub fn solve_part1(cmds: &[Command]) -> Result<u64> {
+    Submarine::run(Direct, cmds)?.position().product()
+}
 
-    input_string
-        .split('\n')
-        .map(|s| parse_line(s))
-        .collect::<anyhow::Result<Vec<_>>>()
+pub fn solve_part2(cmds: &[Command]) -> Result<u64> {
+    Submarine::run(Aim, cmds)?.position().product()
 }
 
-fn parse_line(line: &str) -> anyhow::Result<Command> {
+fn parse_line(input: &str, line: &str) -> Result<Command> {
     let mut split = line.split_ascii_whitespace();
 
     let cmd_str = split
         .next()
-        .ok_or(anyhow!("Missing command in line: `{}`", line))?;
+        .ok_or_else(|| Error::parse_at(input, line, "missing command"))?;
     let val = split
         .next()
-        .ok_or(anyhow!("Missing value in line: `{}`", line))?
-        .parse::<u64>()
-        .context("Parsed value is not a number")?;
+        .ok_or_else(|| Error::parse_at(input, &line[line.len()..], "missing value"))?;
+    let val = error::parse_at::<u64>(input, val, "value")?;
+
+    Command::new(cmd_str, val)
+        .ok_or_else(|| Error::parse_at(input, cmd_str, format!("unknown command `{}`", cmd_str)))
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    pub horizontal: u64,
+    pub depth: u64,
+    /// Only used by the [`Aim`] steering.
+    pub aim: u64,
+}
+
+impl Position {
+    /// Horizontal position multiplied by the depth, the answer to both parts.
+    pub fn product(&self) -> Result<u64> {
+        self.horizontal
+            .checked_mul(self.depth)
+            .ok_or_else(|| Error::Overflow("product of the final position".to_owned()))
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "horizontal {}, depth {}, aim {}",
+            self.horizontal, self.depth, self.aim
+        )
+    }
+}
+
+/// How a submarine moves on a command.
+pub trait Steering {
+    /// Position after `cmd`, `None` when it would go above the surface or too far.
+    fn apply(&self, position: Position, cmd: Command) -> Option<Position>;
+}
+
+/// Part 1: `up` and `down` change the depth directly.
+pub struct Direct;
+
+impl Steering for Direct {
+    fn apply(&self, position: Position, cmd: Command) -> Option<Position> {
+        Some(match cmd {
+            Command::Forward(val) => Position {
+                horizontal: position.horizontal.checked_add(val)?,
+                ..position
+            },
+            Command::Up(val) => Position {
+                depth: position.depth.checked_sub(val)?,
+                ..position
+            },
+            Command::Down(val) => Position {
+                depth: position.depth.checked_add(val)?,
+                ..position
+            },
+            Command::Back(val) => Position {
+                horizontal: position.horizontal.checked_sub(val)?,
+                ..position
+            },
+        })
+    }
+}
+
+/// Part 2: `up` and `down` change the aim, and moving forward dives along it.
+pub struct Aim;
+
+impl Steering for Aim {
+    fn apply(&self, position: Position, cmd: Command) -> Option<Position> {
+        Some(match cmd {
+            Command::Forward(val) => Position {
+                horizontal: position.horizontal.checked_add(val)?,
+                depth: position.depth.checked_add(position.aim.checked_mul(val)?)?,
+                ..position
+            },
+            Command::Up(val) => Position {
+                aim: position.aim.checked_sub(val)?,
+                ..position
+            },
+            Command::Down(val) => Position {
+                aim: position.aim.checked_add(val)?,
+                ..position
+            },
+            // back up along the aim, rising as much as the same `forward` dives
+            Command::Back(val) => Position {
+                horizontal: position.horizontal.checked_sub(val)?,
+                depth: position.depth.checked_sub(position.aim.checked_mul(val)?)?,
+                ..position
+            },
+        })
+    }
+}
+
+/// Submarine following commands with a steering model, recording where each one took it.
+pub struct Submarine<S> {
+    steering: S,
+    /// Position after every executed command.
+    trajectory: Vec<Position>,
+}
+
+impl<S: Steering> Submarine<S> {
+    /// Submarine at the surface, not having moved yet.
+    pub fn new(steering: S) -> Submarine<S> {
+        Submarine {
+            steering,
+            trajectory: vec![],
+        }
+    }
+
+    /// Submarine having executed all of `cmds`.
+    pub fn run(steering: S, cmds: &[Command]) -> Result<Submarine<S>> {
+        let mut submarine = Submarine::new(steering);
+        for &cmd in cmds {
+            submarine.execute(cmd)?;
+        }
+        Ok(submarine)
+    }
+
+    /// Moves according to `cmd`, failing without moving when it would go above the surface or
+    /// too far.
+    pub fn execute(&mut self, cmd: Command) -> Result<Position> {
+        let position = self.steering.apply(self.position(), cmd).ok_or_else(|| {
+            Error::Overflow(format!(
+                "command #{} (`{}`) takes the submarine above the surface or too far",
+                self.trajectory.len() + 1,
+                cmd
+            ))
+        })?;
+        self.trajectory.push(position);
+        Ok(position)
+    }
+
+    pub fn position(&self) -> Position {
+        self.trajectory.last().copied().unwrap_or_default()
+    }
+
+    /// Position after every executed command, in order.
+    pub fn trajectory(&self) -> &[Position] {
+        &self.trajectory
+    }
+
+    /// Deepest depth reached and the index of the first command reaching it, `None` before
+    /// any command.
+    pub fn max_depth(&self) -> Option<(usize, u64)> {
+        self.trajectory
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, position)| position.depth)
+            .map(|(ix, position)| (ix, position.depth))
+    }
+}
+
+/// Mission script, a compact way of writing long lists of commands:
+///
+/// ```text
+/// script     = block ;
+/// block      = { statement | separator } ;
+/// statement  = command | repeat | checkpoint ;
+/// command    = ( "forward" | "down" | "up" | "back" ) NUMBER ;
+/// repeat     = "repeat" NUMBER "{" block "}" ;
+/// checkpoint = "checkpoint" LABEL ;
+/// separator  = NEWLINE | ";" ;
+/// ```
+///
+/// Statements end at a separator or a closing brace, and `#` starts a comment running to the
+/// end of the line. Labels are made of letters, digits, `_` and `-`, each used once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mission {
+    statements: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Statement {
+    /// Command together with the script line it is on.
+    Command(Command, usize),
+    Repeat(u64, Vec<Statement>),
+    Checkpoint(String),
+}
+
+/// Position of a submarine when it passed a checkpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub label: String,
+    /// Number of commands executed before reaching it.
+    pub commands: usize,
+    pub position: Position,
+}
+
+/// Submarine after a mission, with every checkpoint it passed in order.
+pub struct Flight<S> {
+    pub submarine: Submarine<S>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl<S: Steering> Flight<S> {
+    /// Every checkpoint, then where the submarine ended up, how deep it went and the product of
+    /// its final position, one per line.
+    pub fn report(&self) -> Result<String> {
+        let mut lines = vec![];
+        for checkpoint in &self.checkpoints {
+            lines.push(format!(
+                "checkpoint {} after {} commands: {}",
+                checkpoint.label, checkpoint.commands, checkpoint.position
+            ));
+        }
+
+        let submarine = &self.submarine;
+        lines.push(format!(
+            "end after {} commands: {}",
+            submarine.trajectory().len(),
+            submarine.position()
+        ));
+        if let Some((ix, depth)) = submarine.max_depth() {
+            lines.push(format!("deepest: {} after command #{}", depth, ix + 1));
+        }
+        lines.push(format!("product: {}", submarine.position().product()?));
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Most statements (commands, checkpoints and iterations of a repeat) a mission may evaluate.
+const MAX_MISSION_STEPS: usize = 10_000_000;
+
+/// Most commands a mission may execute, the position after each of them being recorded.
+const MAX_MISSION_COMMANDS: usize = 1_000_000;
+
+/// Most checkpoints a mission may pass, each of them being recorded with its own label.
+const MAX_MISSION_CHECKPOINTS: usize = 10_000;
+
+/// Most repeat blocks nested in one another, parsing and flying recursing into each of them.
+const MAX_MISSION_NESTING: usize = 100;
+
+impl Mission {
+    pub fn parse(input: &str) -> Result<Mission> {
+        let mut parser = MissionParser {
+            input,
+            tokens: tokenize(input),
+            next: 0,
+            labels: HashMap::new(),
+            nesting: 0,
+        };
+        let statements = parser.block(None)?;
+        Ok(Mission { statements })
+    }
+
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+
+    /// Flies the mission from the surface with the given steering model.
+    pub fn fly<S: Steering>(&self, steering: S) -> Result<Flight<S>> {
+        let mut flight = Flight {
+            submarine: Submarine::new(steering),
+            checkpoints: vec![],
+        };
+        let mut steps = MAX_MISSION_STEPS;
+        fly(&self.statements, &mut flight, &mut steps)?;
+        Ok(flight)
+    }
+}
+
+/// Evaluates `statements`, each of them and each iteration of a repeat taking one of the
+/// `steps` left.
+fn fly<S: Steering>(
+    statements: &[Statement],
+    flight: &mut Flight<S>,
+    steps: &mut usize,
+) -> Result<()> {
+    for statement in statements {
+        take_step(steps)?;
+        match statement {
+            &Statement::Command(cmd, line) => {
+                if flight.submarine.trajectory().len() == MAX_MISSION_COMMANDS {
+                    return Err(Error::Unsolvable(format!(
+                        "mission executes more than {} commands",
+                        MAX_MISSION_COMMANDS
+                    )));
+                }
+                flight.submarine.execute(cmd).map_err(|err| match err {
+                    Error::Overflow(message) => {
+                        Error::Overflow(format!("{}, on line {}", message, line))
+                    }
+                    err => err,
+                })?;
+            }
+            Statement::Repeat(count, body) => {
+                for _ in 0..*count {
+                    take_step(steps)?;
+                    fly(body, flight, steps)?;
+                }
+            }
+            Statement::Checkpoint(label) => {
+                if flight.checkpoints.len() == MAX_MISSION_CHECKPOINTS {
+                    return Err(Error::Unsolvable(format!(
+                        "mission passes more than {} checkpoints",
+                        MAX_MISSION_CHECKPOINTS
+                    )));
+                }
+                flight.checkpoints.push(Checkpoint {
+                    label: label.clone(),
+                    commands: flight.submarine.trajectory().len(),
+                    position: flight.submarine.position(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn take_step(steps: &mut usize) -> Result<()> {
+    *steps = steps.checked_sub(1).ok_or_else(|| {
+        Error::Unsolvable(format!(
+            "mission evaluates more than {} statements",
+            MAX_MISSION_STEPS
+        ))
+    })?;
+    Ok(())
+}
+
+/// Words, braces and semicolons of a script, every line followed by an empty token at its end
+/// standing for the newline. Tokens are slices of the script so that errors can locate them.
+fn tokenize(input: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    for line in input.lines() {
+        let code = line.split('#').next().unwrap_or_default();
+        let mut start = None;
+        for (ix, c) in code.char_indices() {
+            let single = matches!(c, '{' | '}' | ';');
+            if c.is_whitespace() || single {
+                if let Some(from) = start.take() {
+                    tokens.push(&code[from..ix]);
+                }
+                if single {
+                    tokens.push(&code[ix..ix + 1]);
+                }
+            } else if start.is_none() {
+                start = Some(ix);
+            }
+        }
+        if let Some(from) = start {
+            tokens.push(&code[from..]);
+        }
+        tokens.push(&line[line.len()..]);
+    }
+    tokens
+}
 
-    Ok(match cmd_str {
-        "forward" => Command::Forward(val),
-        "up" => Command::Up(val),
-        "down" => Command::Down(val),
-        _ => bail!("Unknown string for command `{}`", cmd_str),
-    })
+struct MissionParser<'a> {
+    input: &'a str,
+    tokens: Vec<&'a str>,
+    next: usize,
+    /// Line of every checkpoint label already used.
+    labels: HashMap<&'a str, usize>,
+    /// Number of repeat blocks the parser is in.
+    nesting: usize,
 }
 
-pub fn part_01() -> anyhow::Result<u64> {
-    let cmds = inputs()?;
+impl<'a> MissionParser<'a> {
+    /// Next token, the end of the script being an empty one.
+    fn peek(&self) -> &'a str {
+        self.tokens
+            .get(self.next)
+            .copied()
+            .unwrap_or(&self.input[self.input.len()..])
+    }
+
+    fn at_end(&self) -> bool {
+        self.next >= self.tokens.len()
+    }
+
+    fn advance(&mut self) -> &'a str {
+        let token = self.peek();
+        self.next += 1;
+        token
+    }
+
+    fn error(&self, token: &str, message: impl Into<String>) -> Error {
+        Error::parse_at(self.input, token, message)
+    }
+
+    /// Statements up to the brace closing `open`, or to the end of the script at the top level.
+    fn block(&mut self, open: Option<&'a str>) -> Result<Vec<Statement>> {
+        let mut statements = vec![];
+        loop {
+            if self.at_end() {
+                return match open {
+                    Some(open) => Err(self.error(open, "`{` is never closed")),
+                    None => Ok(statements),
+                };
+            }
+
+            match self.advance() {
+                "" | ";" => {}
+                "}" if open.is_some() => return Ok(statements),
+                close @ "}" => return Err(self.error(close, "unmatched `}`")),
+                word => {
+                    statements.push(self.statement(word)?);
+                    let end = self.peek();
+                    if !matches!(end, "" | ";" | "}") {
+                        return Err(self.error(
+                            end,
+                            format!("expected the end of the statement, found `{}`", end),
+                        ));
+                    }
+                }
+            }
+        }
+    }
 
-    let mut x = 0;
-    let mut y = 0;
+    fn statement(&mut self, word: &'a str) -> Result<Statement> {
+        match word {
+            "repeat" => {
+                if self.nesting == MAX_MISSION_NESTING {
+                    return Err(self.error(
+                        word,
+                        format!(
+                            "repeat blocks nested more than {} deep",
+                            MAX_MISSION_NESTING
+                        ),
+                    ));
+                }
+                let count = self.number("repeat count")?;
+                let open = self.advance();
+                if open != "{" {
+                    return Err(self.error(open, "expected `{` after the repeat count"));
+                }
+
+                self.nesting += 1;
+                let body = self.block(Some(open))?;
+                self.nesting -= 1;
+                Ok(Statement::Repeat(count, body))
+            }
+            "checkpoint" => {
+                let label = self.advance();
+                if label.is_empty() || matches!(label, "{" | "}" | ";") {
+                    return Err(self.error(label, "missing checkpoint label"));
+                }
+                if let Some(bad) = label
+                    .char_indices()
+                    .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                {
+                    let at = &label[bad.0..];
+                    return Err(self.error(at, format!("bad character `{}` in label", bad.1)));
+                }
 
-    for cmd in cmds {
-        match cmd {
-            Command::Forward(val) => x += val,
-            Command::Up(val) => y -= val,
-            Command::Down(val) => y += val,
+                let (line, _) = error::position(self.input, label);
+                if let Some(first) = self.labels.insert(label, line) {
+                    return Err(self.error(
+                        label,
+                        format!("checkpoint `{}` already defined on line {}", label, first),
+                    ));
+                }
+                Ok(Statement::Checkpoint(label.to_owned()))
+            }
+            _ => {
+                let input = self.input;
+                let unknown =
+                    || Error::parse_at(input, word, format!("unknown command `{}`", word));
+                // unknown commands are reported before their value
+                Command::in_mission(word, 0).ok_or_else(unknown)?;
+                let val = self.number("value")?;
+                let cmd = Command::in_mission(word, val).ok_or_else(unknown)?;
+                Ok(Statement::Command(cmd, error::position(self.input, word).0))
+            }
         }
     }
 
-    Ok(x * y)
+    fn number(&mut self, what: &str) -> Result<u64> {
+        let token = self.advance();
+        if token.is_empty() || matches!(token, "{" | "}" | ";") {
+            return Err(self.error(token, format!("missing {}", what)));
+        }
+        error::parse_at(self.input, token, what)
+    }
 }
 
-pub fn part_02() -> anyhow::Result<u64> {
-    let cmds = inputs()?;
+/// `size` commands, never going above the surface.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut depth = 0;
+    let mut lines = vec![];
+    for _ in 0..size {
+        let value = rng.between(1, 9);
+        let line = match rng.below(3) {
+            0 => format!("forward {}", value),
+            1 if depth >= value => {
+                depth -= value;
+                format!("up {}", value)
+            }
+            _ => {
+                depth += value;
+                format!("down {}", value)
+            }
+        };
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
 
-    let mut x = 0;
-    let mut y = 0;
-    let mut aim = 0;
+    const EXAMPLE: &str = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2";
 
-    for cmd in cmds {
-        match cmd {
-            Command::Forward(val) => {
-                x += val;
-                y += aim * val;
+    #[test]
+    fn test_submarine() {
+        let cmds = parse(EXAMPLE).unwrap();
+
+        let direct = Submarine::run(Direct, &cmds).unwrap();
+        assert_eq!(direct.position().product().unwrap(), 150);
+        assert_eq!(direct.trajectory().len(), 6);
+        assert_eq!(direct.max_depth(), Some((4, 10)));
+
+        let aim = Submarine::run(Aim, &cmds).unwrap();
+        assert_eq!(aim.position().product().unwrap(), 900);
+        assert_eq!(
+            aim.trajectory()[2],
+            Position {
+                horizontal: 13,
+                depth: 40,
+                aim: 5
             }
-            Command::Up(val) => aim -= val,
-            Command::Down(val) => aim += val,
+        );
+        assert_eq!(aim.max_depth(), Some((5, 60)));
+
+        assert_eq!(Submarine::new(Aim).max_depth(), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("forward 3\ndown 2").unwrap(),
+            [Command::Forward(3), Command::Down(2)]
+        );
+        match parse("forward 3\nback 1\ndown 2") {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (2, 1)),
+            res => panic!("unexpected {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_surface() {
+        let mut submarine = Submarine::new(Direct);
+        submarine.execute(Command::Down(2)).unwrap();
+        submarine.execute(Command::Up(2)).unwrap();
+        assert_eq!(submarine.max_depth(), Some((0, 2)));
+
+        match submarine.execute(Command::Up(1)) {
+            Err(Error::Overflow(message)) => assert!(message.contains("#3 (`up 1`)")),
+            res => panic!("unexpected {:?}", res),
         }
+        assert_eq!(submarine.trajectory().len(), 2);
     }
 
-    Ok(x * y)
+    #[test]
+    fn test_mission() {
+        let script = "# the example, twice over
+checkpoint start
+repeat 2 {
+    forward 5; down 5
+    forward 8
+    up 3; down 8; forward 2  # back at the surface?
+}
+checkpoint twice
+repeat 3 { repeat 2 { back 1 } }
+";
+        let mission = Mission::parse(script).unwrap();
+        assert_eq!(mission.statements().len(), 4);
+
+        let cmds = parse(EXAMPLE).unwrap();
+        let twice = [cmds.clone(), cmds].concat();
+        let flight = mission.fly(Direct).unwrap();
+        let expected = Submarine::run(Direct, &twice).unwrap().position();
+        assert_eq!(
+            flight.checkpoints,
+            [
+                Checkpoint {
+                    label: "start".to_owned(),
+                    commands: 0,
+                    position: Position::default(),
+                },
+                Checkpoint {
+                    label: "twice".to_owned(),
+                    commands: 12,
+                    position: expected,
+                }
+            ]
+        );
+        assert_eq!(flight.submarine.trajectory().len(), 18);
+        assert_eq!(flight.submarine.position().horizontal, 24);
+
+        let flight = mission.fly(Aim).unwrap();
+        assert_eq!(flight.checkpoints[1].position.depth, 270);
+        assert_eq!(
+            flight.report().unwrap(),
+            "checkpoint start after 0 commands: horizontal 0, depth 0, aim 0
+checkpoint twice after 12 commands: horizontal 30, depth 270, aim 20
+end after 18 commands: horizontal 24, depth 150, aim 20
+deepest: 270 after command #12
+product: 3600"
+        );
+        assert_eq!(
+            flight.submarine.position(),
+            Position {
+                horizontal: 24,
+                depth: 150,
+                aim: 20
+            }
+        );
+    }
+
+    #[test]
+    fn test_mission_errors() {
+        let located = |script: &str| match Mission::parse(script) {
+            Err(Error::Parse { line, column, .. }) => (line, column),
+            res => panic!("unexpected {:?} for {:?}", res, script),
+        };
+
+        assert_eq!(located("forward 1\nsideways 2"), (2, 1));
+        assert_eq!(located("forward 1\ndown"), (2, 5));
+        assert_eq!(located("up 1 2"), (1, 6));
+        assert_eq!(located("repeat 2\n{ down 1 }"), (1, 9));
+        assert_eq!(located("repeat x { down 1 }"), (1, 8));
+        assert_eq!(located("\nrepeat 2 {\n down 1\n"), (2, 10));
+        assert_eq!(located("down 1 }"), (1, 8));
+        assert_eq!(located("checkpoint a\ncheckpoint a"), (2, 12));
+        assert_eq!(located("checkpoint a.b"), (1, 13));
+        assert_eq!(located("checkpoint # none"), (1, 18));
+
+        let nested = |depth: usize| {
+            format!(
+                "{}forward 1{}",
+                "repeat 1 {\n".repeat(depth),
+                "\n}".repeat(depth)
+            )
+        };
+        assert!(Mission::parse(&nested(MAX_MISSION_NESTING)).is_ok());
+        assert_eq!(located(&nested(MAX_MISSION_NESTING + 1)), (101, 1));
+        assert_eq!(located(&nested(200_000)), (101, 1));
+
+        match Mission::parse("down 1\nrepeat 2 {\n up 1\n}")
+            .unwrap()
+            .fly(Direct)
+        {
+            Err(Error::Overflow(message)) => assert!(message.ends_with("on line 3")),
+            res => panic!("unexpected {:?}", res.map(|flight| flight.checkpoints)),
+        }
+
+        for (endless, limit) in [
+            ("repeat 18446744073709551615 { }", "statements"),
+            ("repeat 4000000000 { checkpoint a }", "checkpoints"),
+            ("repeat 4000000000 { forward 1 }", "commands"),
+        ] {
+            match Mission::parse(endless).unwrap().fly(Direct) {
+                Err(Error::Unsolvable(message)) => assert!(message.ends_with(limit)),
+                res => panic!(
+                    "unexpected {:?}",
+                    res.map(|flight| flight.checkpoints.len())
+                ),
+            }
+        }
+    }
 }
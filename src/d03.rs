@@ -1,106 +1,383 @@
-use anyhow::Context;
+use std::collections::HashMap;
+use std::fmt;
 
-fn inputs() -> anyhow::Result<Vec<BinaryCounter>> {
-    let input_string =
-        std::fs::read_to_string("inputs/03_input.txt").context("Error while reading input")?;
+use crate::error::{Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-    Ok(input_string
-        .split('\n')
-        .map(|s| BinaryCounter::from_str(s))
-        .collect::<Vec<_>>())
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    const NAME: &'static str = "Binary Diagnostic";
+
+    type Input = Report;
+    type Answer1 = Bits;
+    type Answer2 = Bits;
+
+    fn parse(input: &str) -> Result<Report> {
+        parse(input)
+    }
+
+    fn part_01(report: &Report) -> Result<Bits> {
+        solve_part1(report)
+    }
+
+    fn part_02(report: &Report) -> Result<Bits> {
+        solve_part2(report)
+    }
+
+    const INPUT_SIZE: usize = 1000;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+/// Diagnostic report, numbers all having `width` bits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub width: usize,
+    pub numbers: Vec<Bits>,
+}
+
+pub fn parse(input: &str) -> Result<Report> {
+    let mut width = None;
+
+    let numbers = input
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            if line.is_empty() {
+                return Err(Error::parse_at(input, line, "missing binary digits"));
+            }
+            if let Some(ix) = line.find(|c| c != '0' && c != '1') {
+                return Err(Error::parse_at(
+                    input,
+                    &line[ix..],
+                    "expected a binary digit",
+                ));
+            }
+            let expected = *width.get_or_insert(line.len());
+            if expected != line.len() {
+                return Err(Error::parse_at(
+                    input,
+                    line,
+                    format!(
+                        "all numbers must have the same number of digits, {} here instead of {}",
+                        line.len(),
+                        expected
+                    ),
+                ));
+            }
+            Ok(Bits::from_digits(line))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Report {
+        width: width.unwrap_or_default(),
+        numbers,
+    })
 }
 
-#[derive(Clone)]
-struct BinaryCounter {
-    digits_cnt: Vec<u16>,
-    num_cnt: u16,
+pub fn solve_part1(report: &Report) -> Result<Bits> {
+    let (gamma, epsilon) = power_rates(report)?;
+    Ok(gamma.mul(&epsilon))
 }
 
-impl BinaryCounter {
-    fn from_str(s: &str) -> Self {
-        BinaryCounter {
-            digits_cnt: s.chars().map(|c| if c == '1' { 1 } else { 0 }).collect(),
-            num_cnt: 0,
+pub fn solve_part2(report: &Report) -> Result<Bits> {
+    let (oxygen, co2) = life_support_ratings(report)?;
+    Ok(oxygen.mul(&co2))
+}
+
+/// Unsigned number of any width, as many bits as it has digits, stored in 64 bit words.
+/// It displays as a decimal integer, and as its bits with `{:b}`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bits {
+    width: usize,
+    /// Least significant word first, bits above the width always unset.
+    words: Vec<u64>,
+}
+
+impl Bits {
+    pub fn zero(width: usize) -> Bits {
+        Bits {
+            width,
+            words: vec![0; width.div_ceil(64)],
         }
     }
 
-    fn add(&mut self, other: &BinaryCounter) {
-        self.digits_cnt = self
-            .digits_cnt
-            .iter()
-            .zip(other.digits_cnt.iter())
-            .map(|(a, b)| a + b)
-            .collect();
-        self.num_cnt += 1;
+    /// Number written with the binary digits `digits`, which must all be `0` or `1`.
+    pub fn from_digits(digits: &str) -> Bits {
+        let mut bits = Bits::zero(digits.len());
+        for (ix, digit) in digits.bytes().enumerate() {
+            bits.set(ix, digit == b'1');
+        }
+        bits
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Bit `ix`, counting from the most significant one.
+    pub fn get(&self, ix: usize) -> bool {
+        let pos = self.width - 1 - ix;
+        self.words[pos / 64] >> (pos % 64) & 1 == 1
     }
 
-    fn to_number(&self) -> u32 {
-        let str: String = self
-            .digits_cnt
-            .iter()
-            .map(|&c| if c > self.num_cnt / 2 { '1' } else { '0' })
-            .collect();
-        u32::from_str_radix(&str, 2).unwrap()
+    /// Sets bit `ix`, counting from the most significant one.
+    pub fn set(&mut self, ix: usize, bit: bool) {
+        let pos = self.width - 1 - ix;
+        let mask = 1 << (pos % 64);
+        if bit {
+            self.words[pos / 64] |= mask;
+        } else {
+            self.words[pos / 64] &= !mask;
+        }
+    }
+
+    /// Number with every bit of the width flipped.
+    pub fn complement(&self) -> Bits {
+        let mut res = self.clone();
+        for word in &mut res.words {
+            *word = !*word;
+        }
+        if !self.width.is_multiple_of(64) {
+            if let Some(top) = res.words.last_mut() {
+                *top &= (1 << (self.width % 64)) - 1;
+            }
+        }
+        res
+    }
+
+    /// Product of both numbers, as wide as they are together.
+    pub fn mul(&self, other: &Bits) -> Bits {
+        let mut res = Bits::zero(self.width + other.width);
+        for (i, &a) in self.words.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in other.words.iter().enumerate() {
+                let sum = a as u128 * b as u128 + res.words[i + j] as u128 + carry;
+                res.words[i + j] = sum as u64;
+                carry = sum >> 64;
+            }
+            // the product fits the width, so the carry does too
+            if let Some(word) = res.words.get_mut(i + other.words.len()) {
+                *word = carry as u64;
+            }
+        }
+        res
+    }
+
+    /// The number when it fits in 128 bits, whatever its width.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.words.iter().skip(2).any(|&word| word != 0) {
+            return None;
+        }
+        let word = |ix: usize| self.words.get(ix).copied().unwrap_or_default() as u128;
+        Some(word(1) << 64 | word(0))
     }
 }
 
-pub fn part_01() -> anyhow::Result<u32> {
-    let nums = inputs()?;
+impl fmt::Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(n) = self.to_u128() {
+            return write!(f, "{}", n);
+        }
 
-    let counter = calc(&nums);
+        // groups of 19 decimal digits, the most that fits in a word, least significant first
+        const GROUP: u64 = 10_000_000_000_000_000_000;
+        let mut words = self.words.clone();
+        let mut groups = vec![];
+        while words.iter().any(|&word| word != 0) {
+            let mut rem = 0u128;
+            for word in words.iter_mut().rev() {
+                let cur = rem << 64 | *word as u128;
+                *word = (cur / GROUP as u128) as u64;
+                rem = cur % GROUP as u128;
+            }
+            groups.push(rem as u64);
+        }
 
-    let gamma = counter.to_number();
-    let epsilon = !gamma & 0b111111111111;
+        let mut groups = groups.iter().rev();
+        if let Some(first) = groups.next() {
+            write!(f, "{}", first)?;
+        }
+        for group in groups {
+            write!(f, "{:019}", group)?;
+        }
+        Ok(())
+    }
+}
 
-    Ok(gamma * epsilon)
+impl fmt::Binary for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = (0..self.width)
+            .map(|ix| if self.get(ix) { '1' } else { '0' })
+            .collect::<String>();
+        f.pad_integral(true, "0b", &digits)
+    }
 }
 
-fn calc(nums: &[BinaryCounter]) -> BinaryCounter {
-    let mut counter = BinaryCounter::from_str("000000000000");
+/// Number of numbers having a 1 at each bit, most significant first.
+fn count_ones(numbers: &[Bits], width: usize) -> Vec<usize> {
+    (0..width)
+        .map(|ix| numbers.iter().filter(|n| n.get(ix)).count())
+        .collect()
+}
 
-    for num in nums.iter() {
-        counter.add(num);
+/// Gamma rate, the most common bit of every position, and epsilon rate, the least common one.
+pub fn power_rates(report: &Report) -> Result<(Bits, Bits)> {
+    if report.numbers.is_empty() {
+        return Err(Error::Unsolvable("Empty report".to_owned()));
     }
 
-    counter
+    let mut gamma = Bits::zero(report.width);
+    for (ix, ones) in count_ones(&report.numbers, report.width)
+        .into_iter()
+        .enumerate()
+    {
+        gamma.set(ix, ones * 2 > report.numbers.len());
+    }
+    let epsilon = gamma.complement();
+    Ok((gamma, epsilon))
 }
 
-pub fn part_02() -> anyhow::Result<u32> {
-    let nums = inputs()?;
+/// Oxygen generator and CO2 scrubber ratings.
+pub fn life_support_ratings(report: &Report) -> Result<(Bits, Bits)> {
+    Ok((rating(report, true)?, rating(report, false)?))
+}
 
+/// Filters the numbers bit by bit, keeping the most (or least) common value, until one is left.
+fn rating(report: &Report, most_common: bool) -> Result<Bits> {
     let mut ix = 0;
-    let mut n1 = nums.clone();
-    while n1.len() > 1 {
-        let counter = calc(&n1);
-        let most_common = if counter.digits_cnt[ix] >= counter.num_cnt - counter.digits_cnt[ix] {
-            1
-        } else {
-            0
-        };
-        n1 = n1
-            .into_iter()
-            .filter(|n| n.digits_cnt[ix] == most_common)
-            .collect();
+    let mut left = report.numbers.iter().collect::<Vec<_>>();
+    while left.len() > 1 {
+        if ix == report.width {
+            return Err(Error::Unsolvable(
+                "Duplicate numbers in the report".to_owned(),
+            ));
+        }
+
+        let ones = left.iter().filter(|n| n.get(ix)).count();
+        let keep = (ones >= left.len() - ones) == most_common;
+        // a bit all the numbers agree on doesn't filter any of them out
+        if left.iter().any(|n| n.get(ix) == keep) {
+            left.retain(|n| n.get(ix) == keep);
+        }
         ix += 1;
     }
 
-    let mut ix = 0;
-    let mut n2 = nums.clone();
-    while n2.len() > 1 {
-        let counter = calc(&n2);
-        let least_common = if counter.digits_cnt[ix] >= counter.num_cnt - counter.digits_cnt[ix] {
-            0
-        } else {
-            1
+    left.first()
+        .map(|&n| n.clone())
+        .ok_or_else(|| Error::Unsolvable("Empty report".to_owned()))
+}
+
+/// `size` distinct numbers, of 12 bits like the real input or as many as needed to tell that
+/// many numbers apart (at most 64, as `size` is a `usize`).
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let width = 12.max((usize::BITS - (size - 1).leading_zeros()) as usize);
+    let span = 1u128 << width;
+
+    // the first `size` steps of a Fisher-Yates shuffle of `0..span`, only keeping the entries
+    // that were swapped away from their index
+    let mut swapped = HashMap::new();
+    (0..size as u64)
+        .map(|ix| {
+            let other = ix + (rng.next_u64() as u128 % (span - ix as u128)) as u64;
+            let picked = swapped.get(&other).copied().unwrap_or(other);
+            let current = swapped.get(&ix).copied().unwrap_or(ix);
+            swapped.insert(other, current);
+            format!("{:0width$b}", picked, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const EXAMPLE: &str =
+        "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010";
+
+    #[test]
+    fn test_example() {
+        let report = parse(EXAMPLE).unwrap();
+        assert_eq!(report.width, 5);
+
+        let (gamma, epsilon) = power_rates(&report).unwrap();
+        assert_eq!((gamma.to_u128(), epsilon.to_u128()), (Some(22), Some(9)));
+        assert_eq!(format!("{:b} {:b}", gamma, epsilon), "10110 01001");
+        assert_eq!(solve_part1(&report).unwrap().to_string(), "198");
+
+        let (oxygen, co2) = life_support_ratings(&report).unwrap();
+        assert_eq!(
+            format!("{} {:b} {} {:#b}", oxygen, oxygen, co2, co2),
+            "23 10111 10 0b01010"
+        );
+        assert_eq!(solve_part2(&report).unwrap().to_string(), "230");
+    }
+
+    #[test]
+    fn test_wide_report() {
+        // 200 bit numbers, the first and last bits telling them apart
+        let number = |first: char, middle: char, last: char| {
+            format!("{}{}{}", first, middle.to_string().repeat(198), last)
         };
-        n2 = n2
-            .into_iter()
-            .filter(|n| n.digits_cnt[ix] == least_common)
-            .collect();
-        ix += 1;
+        let input = [
+            number('1', '0', '1'),
+            number('1', '1', '0'),
+            number('0', '1', '1'),
+        ]
+        .join("\n");
+        let report = parse(&input).unwrap();
+
+        let (gamma, epsilon) = power_rates(&report).unwrap();
+        assert_eq!(format!("{:b}", gamma), number('1', '1', '1'));
+        assert_eq!(format!("{:b}", epsilon), number('0', '0', '0'));
+        assert_eq!(epsilon.to_u128(), Some(0));
+
+        let (oxygen, co2) = life_support_ratings(&report).unwrap();
+        assert_eq!(format!("{:b}", oxygen), number('1', '1', '0'));
+        assert_eq!(format!("{:b}", co2), number('0', '1', '1'));
+        assert_eq!(oxygen.to_u128(), None);
+        assert_eq!(oxygen.mul(&co2).width(), 400);
+
+        let err = parse(&format!("{}\n0101", input)).unwrap_err();
+        assert!(err.to_string().contains("4 here instead of 200"));
     }
 
-    let ox = n1.into_iter().next().unwrap().to_number();
-    let co = n2.into_iter().next().unwrap().to_number();
-    Ok(ox * co)
+    #[test]
+    fn test_generate() {
+        for size in [1, 3, 4096, 5000] {
+            let input = generate(&mut Rng::new(3), size);
+            let report = parse(&input).unwrap();
+            assert_eq!(report.width, if size > 4096 { 13 } else { 12 });
+            assert_eq!(input.lines().collect::<HashSet<_>>().len(), size);
+        }
+    }
+
+    #[test]
+    fn test_bits() {
+        let two_100 = Bits::from_digits(&format!("1{}", "0".repeat(100)));
+        assert_eq!(two_100.to_string(), "1267650600228229401496703205376");
+        assert_eq!(
+            two_100.mul(&two_100).to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+
+        let ones = Bits::from_digits(&"1".repeat(128));
+        assert_eq!(ones.to_u128(), Some(u128::MAX));
+        assert_eq!(
+            ones.mul(&ones).to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        assert_eq!(ones.complement().to_u128(), Some(0));
+        assert_eq!(Bits::from_digits("0110").complement().to_string(), "9");
+    }
 }
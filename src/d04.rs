@@ -1,9 +1,40 @@
-use anyhow::{anyhow, Context};
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-pub fn part_01() -> anyhow::Result<u32> {
-    let (nums, mut cards) = inputs()?;
+pub struct Day04;
 
-    for num in nums {
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    const NAME: &'static str = "Giant Squid";
+
+    type Input = (Vec<u32>, Vec<BingoCard>);
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<(Vec<u32>, Vec<BingoCard>)> {
+        parse(input)
+    }
+
+    fn part_01(input: &(Vec<u32>, Vec<BingoCard>)) -> Result<u32> {
+        solve_part1(input)
+    }
+
+    fn part_02(input: &(Vec<u32>, Vec<BingoCard>)) -> Result<u32> {
+        solve_part2(input)
+    }
+
+    const INPUT_SIZE: usize = 100;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn solve_part1((nums, cards): &(Vec<u32>, Vec<BingoCard>)) -> Result<u32> {
+    let mut cards = cards.clone();
+
+    for &num in nums {
         for card in cards.iter_mut() {
             card.new_number(num);
             if card.is_bingo() {
@@ -12,17 +43,20 @@ pub fn part_01() -> anyhow::Result<u32> {
         }
     }
 
-    anyhow::bail!("No bingo found!")
+    Err(Error::Unsolvable("No bingo found!".to_owned()))
 }
 
-pub fn part_02() -> anyhow::Result<u32> {
-    let (nums, cards) = inputs()?;
-    let mut cards = cards.into_iter().map(|c| (c, false)).collect::<Vec<_>>();
+pub fn solve_part2((nums, cards): &(Vec<u32>, Vec<BingoCard>)) -> Result<u32> {
+    let mut cards = cards
+        .iter()
+        .cloned()
+        .map(|c| (c, false))
+        .collect::<Vec<_>>();
 
     let mut wins = 0;
     let cards_number = cards.len();
 
-    for num in nums {
+    for &num in nums {
         for (card, won) in cards.iter_mut().filter(|(_, won)| !won) {
             card.new_number(num);
             if card.is_bingo() {
@@ -34,103 +68,93 @@ pub fn part_02() -> anyhow::Result<u32> {
             }
         }
     }
-    anyhow::bail!("No bingo found!")
+    Err(Error::Unsolvable("Not every card gets a bingo".to_owned()))
 }
 
-fn inputs() -> anyhow::Result<(Vec<u32>, Vec<BingoCard>)> {
-    let input_string =
-        std::fs::read_to_string("inputs/04_input.txt").context("Error while reading input")?;
-
-    parse_inputs(&input_string)
-}
-
-fn parse_inputs(input: &str) -> anyhow::Result<(Vec<u32>, Vec<BingoCard>)> {
-    let mut split = input.split('\n');
+pub fn parse(input: &str) -> Result<(Vec<u32>, Vec<BingoCard>)> {
+    let mut split = input.trim_end().split('\n');
 
     let numbers_line = split
         .next()
-        .ok_or(anyhow!("Missing line with input numbers"))?;
+        .ok_or_else(|| Error::parse_at(input, input, "missing line with input numbers"))?;
     let nums = numbers_line
         .split(',')
-        .map(|n| n.parse::<u32>())
-        .collect::<Result<Vec<u32>, _>>()?;
+        .map(|n| error::parse_at(input, n.trim(), "number"))
+        .collect::<Result<Vec<u32>>>()?;
 
     let mut cards = Vec::new();
     while split.next().is_some() {
         let mut lines: [&str; 5] = [""; 5];
-        for ix in 0..5 {
-            lines[ix] = split
-                .next()
-                .ok_or(anyhow!("Bingo card is not formatted well"))?;
+        for line in lines.iter_mut() {
+            *line = split.next().ok_or_else(|| {
+                Error::parse_at(input, &input[input.len()..], "bingo card must have 5 rows")
+            })?;
         }
-        cards.push(BingoCard::parse_lines(lines)?);
+        cards.push(BingoCard::parse_lines(input, lines)?);
     }
 
     Ok((nums, cards))
 }
 
-#[derive(Debug)]
-struct BingoCard(Vec<Vec<(u32, bool)>>);
+#[derive(Clone, Debug)]
+pub struct BingoCard(Vec<Vec<(u32, bool)>>);
 
 impl BingoCard {
-    fn parse_lines(bingo: [&str; 5]) -> anyhow::Result<Self> {
+    /// Parses the 5 rows of a card, `input` being the whole input they come from.
+    fn parse_lines(input: &str, bingo: [&str; 5]) -> Result<Self> {
         let mut rows = Vec::with_capacity(5);
         for line in bingo {
             let numbers = line
-                .trim()
-                .replace("  ", " ")
-                .split(' ')
-                .map(|n| n.trim().parse::<u32>())
-                .collect::<Result<Vec<u32>, _>>()?;
+                .split_whitespace()
+                .map(|n| error::parse_at(input, n, "number"))
+                .collect::<Result<Vec<u32>>>()?;
+            if numbers.len() != 5 {
+                return Err(Error::parse_at(
+                    input,
+                    line,
+                    "bingo row must have 5 numbers",
+                ));
+            }
             rows.push(numbers.into_iter().map(|n| (n, false)).collect());
         }
 
         Ok(BingoCard(rows))
     }
 
-    fn new_number(&mut self, num: u32) {
+    /// Marks `num` if it is on the card.
+    pub fn new_number(&mut self, num: u32) {
         for row in self.0.iter_mut() {
-            let found = row.iter_mut().find(|&&mut (n, _)| n == num);
-            if found.is_some() {
-                found.unwrap().1 = true;
+            if let Some(found) = row.iter_mut().find(|&&mut (n, _)| n == num) {
+                found.1 = true;
                 return;
             }
         }
     }
 
-    fn is_bingo(&self) -> bool {
+    /// Whether a whole row or column is marked.
+    pub fn is_bingo(&self) -> bool {
         // check all rows
         let rows = &self.0;
         for row in rows.iter() {
-            if row.iter().all(|n| n.1 == true) {
+            if row.iter().all(|n| n.1) {
                 return true;
             }
         }
 
+        // check all columns
         let dim = rows.len();
-        for ix in 0..dim {
-            let mut column_bingo = true;
-            for iy in 0..dim {
-                let (_, marked) = rows.get(iy).unwrap().get(ix).unwrap();
-                if !marked {
-                    column_bingo = false;
-                    break;
-                }
-            }
-            if column_bingo {
-                return true;
-            }
-        }
-
-        false
+        (0..dim).any(|ix| {
+            rows.iter()
+                .all(|row| row.get(ix).is_some_and(|&(_, marked)| marked))
+        })
     }
 
-    fn unmarked_numbers_sum(&self) -> u32 {
+    pub fn unmarked_numbers_sum(&self) -> u32 {
         self.0
             .iter()
             .map(|row| {
                 row.iter()
-                    .filter(|(_, marked)| *marked == false)
+                    .filter(|(_, marked)| !*marked)
                     .map(|(n, _)| n)
                     .sum::<u32>()
             })
@@ -138,6 +162,28 @@ impl BingoCard {
     }
 }
 
+/// All numbers from 0 to 99 drawn in a random order and `size` bingo cards.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut numbers = (0..100).collect::<Vec<u32>>();
+    rng.shuffle(&mut numbers);
+    let mut input = numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    for _ in 0..size.max(1) {
+        rng.shuffle(&mut numbers);
+        input.push('\n');
+        for row in numbers[..25].chunks(5) {
+            input.push('\n');
+            let row = row.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>();
+            input.push_str(&row.join(" "));
+        }
+    }
+    input
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -145,33 +191,15 @@ mod tests {
 
     #[test]
     fn test_parse_inputs() {
-        let input_str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
-
-        22 13 17 11  0
-         8  2 23  4 24
-        21  9 14 16  7
-         6 10  3 18  5
-         1 12 20 15 19
-        
-         3 15  0  2 22
-         9 18 13 17  5
-        19  8  7 25 23
-        20 11 10 24  4
-        14 21 16 12  6
-        
-        14 21 17 24  4
-        10 16 15  9 19
-        18  8 23 26 20
-        22 11 13  6  5
-         2  0 12  3  7";
-
-        let result = parse_inputs(input_str);
-
-        assert_eq!(result.is_ok(), true);
+        let input_str = include_str!("../inputs/examples/04_example.txt");
+
+        let result = parse(input_str);
+
+        assert!(result.is_ok());
         let (nums, cards) = result.unwrap();
         assert_eq!(nums.len(), 27);
         assert_eq!(cards.len(), 3);
-        assert_eq!(cards.get(0).unwrap().0.len(), 5);
+        assert_eq!(cards.first().unwrap().0.len(), 5);
     }
 
     #[test]
@@ -184,21 +212,21 @@ mod tests {
             " 1 12 20 15 19",
         ];
 
-        let mut bingo = BingoCard::parse_lines(inputs).expect("bad parse");
+        let mut bingo = BingoCard::parse_lines("", inputs).expect("bad parse");
 
         bingo.new_number(8);
         bingo.new_number(2);
         bingo.new_number(23);
         bingo.new_number(4);
-        assert_eq!(bingo.is_bingo(), false);
+        assert!(!bingo.is_bingo());
 
         bingo.new_number(16);
         bingo.new_number(18);
         bingo.new_number(15);
-        assert_eq!(bingo.is_bingo(), false);
+        assert!(!bingo.is_bingo());
 
         bingo.new_number(24);
-        assert_eq!(bingo.is_bingo(), true);
+        assert!(bingo.is_bingo());
     }
 
     #[test]
@@ -211,15 +239,15 @@ mod tests {
             " 1 12 20 15 19",
         ];
 
-        let mut bingo = BingoCard::parse_lines(inputs).expect("bad parse");
+        let mut bingo = BingoCard::parse_lines("", inputs).expect("bad parse");
 
         bingo.new_number(4);
         bingo.new_number(16);
         bingo.new_number(18);
         bingo.new_number(15);
-        assert_eq!(bingo.is_bingo(), false);
+        assert!(!bingo.is_bingo());
 
         bingo.new_number(11);
-        assert_eq!(bingo.is_bingo(), true);
+        assert!(bingo.is_bingo());
     }
 }
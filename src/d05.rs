@@ -1,16 +1,41 @@
-use std::cmp;
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Context};
+use crate::error::{self, Result};
+use crate::geom::Point2;
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-pub fn part_01() -> anyhow::Result<usize> {
-    let lines = inputs()?;
+pub struct Day05;
 
-    solve_01(lines)
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    const NAME: &'static str = "Hydrothermal Venture";
+
+    type Input = Vec<Line>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Line>> {
+        parse(input)
+    }
+
+    fn part_01(lines: &Vec<Line>) -> Result<usize> {
+        solve_part1(lines)
+    }
+
+    fn part_02(lines: &Vec<Line>) -> Result<usize> {
+        solve_part2(lines)
+    }
+
+    const INPUT_SIZE: usize = 500;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
 }
 
-fn solve_01(lines: Vec<Line>) -> anyhow::Result<usize> {
-    let mut map: HashMap<(i32, i32), i32> = HashMap::new();
+pub fn solve_part1(lines: &[Line]) -> Result<usize> {
+    let mut map: HashMap<Point2, i32> = HashMap::new();
 
     for line in lines {
         for dot in line.dots_on_line() {
@@ -22,14 +47,8 @@ fn solve_01(lines: Vec<Line>) -> anyhow::Result<usize> {
     Ok(map.iter().filter(|(_, &v)| v >= 2).count())
 }
 
-pub fn part_02() -> anyhow::Result<usize> {
-    let lines = inputs()?;
-
-    solve_02(lines)
-}
-
-fn solve_02(lines: Vec<Line>) -> anyhow::Result<usize> {
-    let mut map: HashMap<(i32, i32), i32> = HashMap::new();
+pub fn solve_part2(lines: &[Line]) -> Result<usize> {
+    let mut map: HashMap<Point2, i32> = HashMap::new();
 
     for line in lines {
         for dot in line.dots_on_line_2() {
@@ -41,140 +60,125 @@ fn solve_02(lines: Vec<Line>) -> anyhow::Result<usize> {
     Ok(map.iter().filter(|(_, &v)| v >= 2).count())
 }
 
-fn inputs() -> anyhow::Result<Vec<Line>> {
-    let input_string =
-        std::fs::read_to_string("inputs/05_input.txt").context("Error while reading input")?;
-
-    parse_inputs(&input_string)
-}
-
-fn parse_inputs(input_str: &str) -> anyhow::Result<Vec<Line>> {
+pub fn parse(input_str: &str) -> Result<Vec<Line>> {
     input_str
-        .split('\n')
-        .map(|s| Line::try_from_str(s))
+        .lines()
+        .enumerate()
+        .map(|(ix, line)| Line::try_from_str(line).map_err(|err| err.at_line(ix + 1)))
         .collect()
 }
 
 #[derive(Debug)]
-struct Line {
-    start: (i32, i32),
-    end: (i32, i32),
+pub struct Line {
+    pub start: Point2,
+    pub end: Point2,
 }
 
 impl Line {
-    fn try_from_str(line: &str) -> anyhow::Result<Self> {
-        let mut s1 = line.trim().split(" -> ");
-
-        let start_str = s1
-            .next()
-            .ok_or(anyhow!("Line {} is not in the valid format", line))?;
-        let end_str = s1
-            .next()
-            .ok_or(anyhow!("Line {} is not in the valid format", line))?;
-
-        let n = format!("{},{}", start_str.trim(), end_str.trim())
-            .split(',')
-            .map(|u| u.parse::<i32>())
-            .collect::<Result<Vec<_>, _>>()?;
-
-        anyhow::ensure!(n.len() == 4, "Line not valid");
+    /// Parses `x1,y1 -> x2,y2`, piece by piece so that errors name the expected piece where it
+    /// is missing.
+    pub fn try_from_str(line: &str) -> Result<Self> {
+        let mut rest = line.trim();
+        let mut coords = [0; 4];
+        for (ix, coord) in coords.iter_mut().enumerate() {
+            match ix {
+                1 | 3 => rest = error::strip_prefix(line, rest, ",")?,
+                2 => rest = error::strip_prefix(line, rest, " -> ")?,
+                _ => {}
+            }
+            (*coord, rest) = error::leading_number(line, rest, "coordinate")?;
+        }
+        error::end_of_line(line, rest)?;
+
+        let [x1, y1, x2, y2] = coords;
         Ok(Line {
-            start: (*n.get(0).unwrap(), *n.get(1).unwrap()),
-            end: (*n.get(2).unwrap(), *n.get(3).unwrap()),
+            start: Point2::new(x1, y1),
+            end: Point2::new(x2, y2),
         })
     }
 
-    fn dots_on_line(&self) -> Vec<(i32, i32)> {
-        if self.start.0 == self.end.0 {
-            // vertical line
-            let y1 = cmp::min(self.start.1, self.end.1);
-            let y2 = cmp::max(self.start.1, self.end.1);
-
-            (y1..=y2).map(|y| (self.start.0, y)).collect()
-        } else if self.start.1 == self.end.1 {
-            // horizontal line
-            let x1 = cmp::min(self.start.0, self.end.0);
-            let x2 = cmp::max(self.start.0, self.end.0);
-
-            (x1..=x2).map(|x| (x, self.start.1)).collect()
+    /// Points covered by a horizontal or vertical line, none for a diagonal one.
+    pub fn dots_on_line(&self) -> Vec<Point2> {
+        if self.start.x == self.end.x || self.start.y == self.end.y {
+            // vertical or horizontal line
+            self.walk()
         } else {
             // non-vertical / non-horizontal
             vec![]
         }
     }
 
-    fn dots_on_line_2(&self) -> Vec<(i32, i32)> {
-        if (self.start.0 - self.end.0).abs() == (self.start.1 - self.end.1).abs() {
+    /// Points covered by the line, diagonals included.
+    pub fn dots_on_line_2(&self) -> Vec<Point2> {
+        let d = self.end - self.start;
+        if d.x.abs() == d.y.abs() {
             // diagonal (45 degree angle)
-            let dx = (self.end.0 - self.start.0).signum();
-            let dy = (self.end.1 - self.start.1).signum();
-
-            let mut x = self.start.0;
-            let mut y = self.start.1;
-
-            let mut res = vec![(x, y)];
-            while (x, y) != (self.end.0, self.end.1) {
-                x += dx;
-                y += dy;
-                res.push((x, y));
-            }
-            res
+            self.walk()
         } else {
             self.dots_on_line()
         }
     }
+
+    /// Steps from start to end, one king move at a time.
+    fn walk(&self) -> Vec<Point2> {
+        let step = (self.end - self.start).signum();
+        let len = self.start.chebyshev(self.end) as i32;
+
+        (0..=len).map(|i| self.start + step * i).collect()
+    }
+}
+
+/// `size` horizontal, vertical or diagonal lines on a 1000x1000 floor.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lines = vec![];
+    while lines.len() < size {
+        let (x1, y1) = (rng.between(0, 999), rng.between(0, 999));
+        let length = rng.between(1, 500);
+        let (dx, dy) = *rng.choose(&[(1, 0), (0, 1), (1, 1), (1, -1), (-1, 0), (0, -1)]);
+        let (x2, y2) = (x1 + dx * length, y1 + dy * length);
+        if (0..1000).contains(&x2) && (0..1000).contains(&y2) {
+            lines.push(format!("{},{} -> {},{}", x1, y1, x2, y2));
+        }
+    }
+    lines.join("\n")
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::error::Error;
 
     #[test]
     fn test_parse_inputs() {
         let res = Line::try_from_str("18,0 -> 0,8");
 
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let res = res.unwrap();
-        assert_eq!(res.start, (18, 0));
-        assert_eq!(res.end, (0, 8));
-    }
-
-    #[test]
-    fn test_inputs_p01() {
-        let lines = "0,9 -> 5,9
-        8,0 -> 0,8
-        9,4 -> 3,4
-        2,2 -> 2,1
-        7,0 -> 7,4
-        6,4 -> 2,0
-        0,9 -> 2,9
-        3,4 -> 1,4
-        0,0 -> 8,8
-        5,5 -> 8,2";
-
-        let lines = parse_inputs(lines).expect("Parsing failed");
-        let res = solve_01(lines);
-        assert_eq!(res.is_ok(), true);
-        assert_eq!(res.unwrap(), 5);
+        assert_eq!(res.start, Point2::new(18, 0));
+        assert_eq!(res.end, Point2::new(0, 8));
     }
 
     #[test]
-    fn test_inputs_p02() {
-        let lines = "0,9 -> 5,9
-        8,0 -> 0,8
-        9,4 -> 3,4
-        2,2 -> 2,1
-        7,0 -> 7,4
-        6,4 -> 2,0
-        0,9 -> 2,9
-        3,4 -> 1,4
-        0,0 -> 8,8
-        5,5 -> 8,2";
-
-        let lines = parse_inputs(lines).expect("Parsing failed");
-        let res = solve_02(lines);
-        assert_eq!(res.is_ok(), true);
-        assert_eq!(res.unwrap(), 12);
+    fn test_parse_errors() {
+        let located = |line: &str| match Line::try_from_str(line) {
+            Err(Error::Parse {
+                column, message, ..
+            }) => (column, message),
+            res => panic!("unexpected {:?}", res),
+        };
+        assert_eq!(
+            located("8,0 > 0,8"),
+            (4, "expected ` -> `, found ` `".to_owned())
+        );
+        assert_eq!(
+            located("8,0 -> 0;8"),
+            (9, "expected `,`, found `;`".to_owned())
+        );
+        assert_eq!(
+            located("8,x -> 0,8"),
+            (3, "expected a digit, found `x`".to_owned())
+        );
+        assert_eq!(located("8,0 -> 0,8 -> 1,1").0, 11);
     }
 }
@@ -1,28 +1,67 @@
-use anyhow::Context;
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-pub fn part_01() -> anyhow::Result<u64> {
-    let starting = inputs()?;
+pub struct Day06;
 
-    Ok(solve(80, &starting))
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    const NAME: &'static str = "Lanternfish";
+
+    type Input = Vec<usize>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        parse(input)
+    }
+
+    fn part_01(starting: &Vec<usize>) -> Result<u64> {
+        solve_part1(starting)
+    }
+
+    fn part_02(starting: &Vec<usize>) -> Result<u64> {
+        solve_part2(starting)
+    }
+
+    const INPUT_SIZE: usize = 300;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
 }
 
-pub fn part_02() -> anyhow::Result<u64> {
-    let starting = inputs()?;
+pub fn parse(input: &str) -> Result<Vec<usize>> {
+    input
+        .trim()
+        .split(',')
+        .map(|n| {
+            let timer = error::parse_at(input, n.trim(), "timer")?;
+            if timer > 8 {
+                return Err(Error::parse_at(
+                    input,
+                    n.trim(),
+                    "timer must be between 0 and 8",
+                ));
+            }
+            Ok(timer)
+        })
+        .collect()
+}
 
-    Ok(solve(256, &starting))
+pub fn solve_part1(starting: &[usize]) -> Result<u64> {
+    solve(80, starting).ok_or_else(|| overflow(80))
 }
 
-fn inputs() -> anyhow::Result<Vec<usize>> {
-    let input_string =
-        std::fs::read_to_string("inputs/06_input.txt").context("Error while reading input")?;
+pub fn solve_part2(starting: &[usize]) -> Result<u64> {
+    solve(256, starting).ok_or_else(|| overflow(256))
+}
 
-    Ok(input_string
-        .split(',')
-        .map(|n| n.parse())
-        .collect::<Result<_, _>>()?)
+fn overflow(days: usize) -> Error {
+    Error::Overflow(format!("too many lanternfish after {} days", days))
 }
 
-fn solve(max_days: usize, inputs: &[usize]) -> u64 {
+fn solve(max_days: usize, inputs: &[usize]) -> Option<u64> {
     let mut states_cnt = [0u64; 9];
 
     for state in inputs {
@@ -30,19 +69,43 @@ fn solve(max_days: usize, inputs: &[usize]) -> u64 {
     }
 
     for _day in 0..max_days {
-        iteration(&mut states_cnt);
+        iteration(&mut states_cnt)?;
     }
 
-    states_cnt.iter().sum()
+    states_cnt
+        .iter()
+        .try_fold(0u64, |acc, &cnt| acc.checked_add(cnt))
 }
 
-fn iteration(states_cnt: &mut [u64; 9]) {
+fn iteration(states_cnt: &mut [u64; 9]) -> Option<()> {
     let zeros = states_cnt[0];
-    for i in 0..8 {
-        states_cnt[i] = states_cnt[i + 1];
-    }
-    states_cnt[6] += zeros;
+    states_cnt.copy_within(1.., 0);
+    states_cnt[6] = states_cnt[6].checked_add(zeros)?;
     states_cnt[8] = zeros;
+    Some(())
+}
+
+/// Number of lanternfish after `max_days`, simulating every single fish.
+pub(crate) fn simulate(max_days: usize, inputs: &[usize]) -> usize {
+    let mut timers = inputs.to_vec();
+
+    for _day in 0..max_days {
+        let born = timers.iter().filter(|&&timer| timer == 0).count();
+        for timer in timers.iter_mut() {
+            *timer = if *timer == 0 { 6 } else { *timer - 1 };
+        }
+        timers.extend(std::iter::repeat_n(8, born));
+    }
+
+    timers.len()
+}
+
+/// `size` lanternfish with timers between 1 and 5.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| rng.between(1, 5).to_string())
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 #[cfg(test)]
@@ -55,12 +118,12 @@ mod tests {
         let inputs = vec![3, 4, 3, 1, 2];
 
         let res = solve(18, &inputs);
-        assert_eq!(res, 26);
+        assert_eq!(res, Some(26));
 
         let res = solve(80, &inputs);
-        assert_eq!(res, 5934);
+        assert_eq!(res, Some(5934));
 
         let res = solve(256, &inputs);
-        assert_eq!(res, 26984457539);
+        assert_eq!(res, Some(26984457539));
     }
 }
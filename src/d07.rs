@@ -1,12 +1,56 @@
-use anyhow::Context;
 use std::collections::HashMap;
 
-pub fn part_01() -> anyhow::Result<u64> {
-    let positions = inputs()?;
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-    let res = solve_01(&positions);
+pub struct Day07;
 
-    Ok(res.into_iter().min().unwrap())
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    const NAME: &'static str = "The Treachery of Whales";
+
+    type Input = Vec<u64>;
+    type Answer1 = u64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Vec<u64>> {
+        parse(input)
+    }
+
+    fn part_01(positions: &Vec<u64>) -> Result<u64> {
+        solve_part1(positions)
+    }
+
+    fn part_02(positions: &Vec<u64>) -> Result<i64> {
+        solve_part2(positions)
+    }
+
+    const INPUT_SIZE: usize = 1000;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn solve_part1(positions: &[u64]) -> Result<u64> {
+    check_not_empty(positions)?;
+    let res = solve_01(positions);
+
+    Ok(res.into_iter().min().unwrap_or_default())
+}
+
+pub fn solve_part2(positions: &[u64]) -> Result<i64> {
+    check_not_empty(positions)?;
+
+    Ok(solve_02(positions).into_iter().min().unwrap_or_default())
+}
+
+fn check_not_empty(positions: &[u64]) -> Result<()> {
+    if positions.is_empty() {
+        return Err(Error::Unsolvable("No crab positions".to_owned()));
+    }
+    Ok(())
 }
 
 fn solve_01(positions: &[u64]) -> Vec<u64> {
@@ -25,8 +69,8 @@ fn solve_01(positions: &[u64]) -> Vec<u64> {
 
     let mut sums = vec![];
 
-    let max_pos = positions.iter().max().unwrap();
-    for i in 0..=*max_pos {
+    let max_pos = positions.iter().max().copied().unwrap_or_default();
+    for i in 0..=max_pos {
         sums.push(left_sum + right_sum);
 
         let cur_count = *map.get(&i).unwrap_or(&0);
@@ -40,12 +84,6 @@ fn solve_01(positions: &[u64]) -> Vec<u64> {
     sums
 }
 
-pub fn part_02() -> anyhow::Result<i64> {
-    let positions = inputs()?;
-
-    Ok(*solve_02(&positions).iter().min().unwrap())
-}
-
 fn calc_fuel_02(ix: u64, map: &HashMap<u64, u64>) -> i64 {
     let mut sum = 0;
     for (&k, &v) in map {
@@ -64,23 +102,30 @@ fn solve_02(positions: &[u64]) -> Vec<i64> {
         *val += 1;
     }
 
-    let max_pos = positions.iter().max().unwrap();
+    let max_pos = positions.iter().max().copied().unwrap_or_default();
     let mut sums = vec![];
-    for i in 0..=*max_pos {
+    for i in 0..=max_pos {
         sums.push(calc_fuel_02(i, &map));
     }
 
     sums
 }
 
-fn inputs() -> anyhow::Result<Vec<u64>> {
-    let input_string =
-        std::fs::read_to_string("inputs/07_input.txt").context("Error while reading input")?;
-
-    Ok(input_string
+pub fn parse(input: &str) -> Result<Vec<u64>> {
+    input
+        .trim()
         .split(',')
-        .map(|n| n.parse())
-        .collect::<Result<_, _>>()?)
+        .map(|n| error::parse_at(input, n.trim(), "position"))
+        .collect()
+}
+
+/// `size` crabs, spread over about twice as many positions.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let max = 2 * size.max(1) as i64;
+    (0..size.max(1))
+        .map(|_| rng.between(0, max).to_string())
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 #[cfg(test)]
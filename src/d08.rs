@@ -1,11 +1,49 @@
-use anyhow::Context;
 use itertools::Itertools;
 use std::collections::HashSet;
 
-pub fn part_01() -> anyhow::Result<usize> {
-    let inputs = inputs()?;
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-    Ok(inputs
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    const NAME: &'static str = "Seven Segment Search";
+
+    type Input = Vec<(Vec<String>, Vec<String>)>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<(Vec<String>, Vec<String>)>> {
+        parse(input)
+    }
+
+    fn part_01(inputs: &Vec<(Vec<String>, Vec<String>)>) -> Result<usize> {
+        solve_part1(inputs)
+    }
+
+    fn part_02(inputs: &Vec<(Vec<String>, Vec<String>)>) -> Result<usize> {
+        solve_part2(inputs)
+    }
+
+    const INPUT_SIZE: usize = 200;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn solve_part1(inputs: &[(Vec<String>, Vec<String>)]) -> Result<usize> {
+    Ok(solve_01(inputs))
+}
+
+pub fn solve_part2(inputs: &[(Vec<String>, Vec<String>)]) -> Result<usize> {
+    solve_02(inputs)
+}
+
+fn solve_01(inputs: &[(Vec<String>, Vec<String>)]) -> usize {
+    inputs
         .iter()
         .map(|input| {
             input
@@ -14,7 +52,7 @@ pub fn part_01() -> anyhow::Result<usize> {
                 .filter(|d| d.len() == 2 || d.len() == 4 || d.len() == 3 || d.len() == 7)
                 .count()
         })
-        .sum())
+        .sum()
 }
 
 /*
@@ -56,95 +94,37 @@ last 6 seg is a 0 (we can know d after that)
 
 */
 
-pub fn part_02() -> anyhow::Result<usize> {
-    let inputs = inputs()?;
-
+fn solve_02(inputs: &[(Vec<String>, Vec<String>)]) -> Result<usize> {
     let mut digits: [HashSet<char>; 10] = Default::default();
     let mut segments: [HashSet<char>; 7] = Default::default();
 
     let mut sum = 0;
 
     for input in inputs {
-        let (mut signal_patterns, mut result_digits) = input;
-
-        digits[1] = conv_to_set(
-            signal_patterns.remove(
-                signal_patterns
-                    .iter()
-                    .find_position(|s| s.len() == 2)
-                    .unwrap()
-                    .0,
-            ),
-        );
-        digits[4] = conv_to_set(
-            signal_patterns.remove(
-                signal_patterns
-                    .iter()
-                    .find_position(|s| s.len() == 4)
-                    .unwrap()
-                    .0,
-            ),
-        );
-        digits[7] = conv_to_set(
-            signal_patterns.remove(
-                signal_patterns
-                    .iter()
-                    .find_position(|s| s.len() == 3)
-                    .unwrap()
-                    .0,
-            ),
-        );
-        digits[8] = conv_to_set(
-            signal_patterns.remove(
-                signal_patterns
-                    .iter()
-                    .find_position(|s| s.len() == 7)
-                    .unwrap()
-                    .0,
-            ),
-        );
+        let (mut signal_patterns, mut result_digits) = input.clone();
+
+        digits[1] = take_pattern(&mut signal_patterns, 1, |s| s.len() == 2)?;
+        digits[4] = take_pattern(&mut signal_patterns, 4, |s| s.len() == 4)?;
+        digits[7] = take_pattern(&mut signal_patterns, 7, |s| s.len() == 3)?;
+        digits[8] = take_pattern(&mut signal_patterns, 8, |s| s.len() == 7)?;
 
         // 7 - 1 -> segment on top (a)
         segments[0] = &digits[7] - &digits[1];
 
         // find a 6 seg that contains 7 + 4 => that's 9, so we get (g)
-        digits[9] = conv_to_set(
-            signal_patterns.remove(
-                signal_patterns
-                    .iter()
-                    .find_position(|&s| {
-                        s.len() == 6
-                            && conv_to_set(s.clone()).is_superset(&digits[7])
-                            && conv_to_set(s.clone()).is_superset(&digits[4])
-                    })
-                    .unwrap()
-                    .0,
-            ),
-        );
+        digits[9] = take_pattern(&mut signal_patterns, 9, |s| {
+            s.len() == 6
+                && conv_to_set(s.clone()).is_superset(&digits[7])
+                && conv_to_set(s.clone()).is_superset(&digits[4])
+        })?;
 
         // find a 6 seg that doesn't contain a 1 => that's 6, and now we get (e)
-        digits[6] = conv_to_set(
-            signal_patterns.remove(
-                signal_patterns
-                    .iter()
-                    .find_position(|&s| {
-                        s.len() == 6 && !conv_to_set(s.clone()).is_superset(&digits[1])
-                    })
-                    .unwrap()
-                    .0,
-            ),
-        );
+        digits[6] = take_pattern(&mut signal_patterns, 6, |s| {
+            s.len() == 6 && !conv_to_set(s.clone()).is_superset(&digits[1])
+        })?;
 
         // last 6 seg is a 0 (we can know d after that)
-        digits[0] = conv_to_set(
-            signal_patterns.remove(
-                signal_patterns
-                    .iter()
-                    .find_position(|&s| s.len() == 6)
-                    .unwrap()
-                    .0,
-            ),
-        );
+        digits[0] = take_pattern(&mut signal_patterns, 0, |s| s.len() == 6)?;
 
         // a - 0, b - 1, c - 2, d -3, e-4, f-5, g-6
 
@@ -172,7 +152,7 @@ pub fn part_02() -> anyhow::Result<usize> {
             .filter(|&s| s.len() == 5)
             .map(|s| conv_to_set(s.to_owned()))
             .find(|dig| *dig != digits[3] && *dig != digits[5])
-            .unwrap();
+            .ok_or_else(|| missing_digit(2))?;
 
         result_digits.reverse();
         let mut multiplier = 1;
@@ -183,7 +163,9 @@ pub fn part_02() -> anyhow::Result<usize> {
             let digit_value = digits
                 .iter()
                 .find_position(|&digit| *digit == d)
-                .expect(&format!("panic {}", result_digit))
+                .ok_or_else(|| {
+                    Error::MissingRule(format!("no digit is displayed as `{}`", result_digit))
+                })?
                 .0;
 
             res += digit_value * multiplier;
@@ -195,34 +177,137 @@ pub fn part_02() -> anyhow::Result<usize> {
     Ok(sum)
 }
 
+/// Removes the first pattern matching `is_digit` and returns its segments.
+fn take_pattern(
+    signal_patterns: &mut Vec<String>,
+    digit: usize,
+    is_digit: impl Fn(&String) -> bool,
+) -> Result<HashSet<char>> {
+    let (ix, _) = signal_patterns
+        .iter()
+        .find_position(|&s| is_digit(s))
+        .ok_or_else(|| missing_digit(digit))?;
+    Ok(conv_to_set(signal_patterns.remove(ix)))
+}
+
+fn missing_digit(digit: usize) -> Error {
+    Error::MissingRule(format!("no signal pattern for digit {}", digit))
+}
+
 fn conv_to_set(s: String) -> HashSet<char> {
     s.chars().collect::<HashSet<_>>()
 }
 
-fn inputs() -> anyhow::Result<Vec<(Vec<String>, Vec<String>)>> {
-    let input_string =
-        std::fs::read_to_string("inputs/08_input.txt").context("Error while reading input")?;
-
+pub fn parse(input_string: &str) -> Result<Vec<(Vec<String>, Vec<String>)>> {
     let mut res = vec![];
     for line in input_string.lines() {
-        let mut split = line.split(" | ");
+        let (signal_patterns, rest) = parse_patterns(input_string, line.trim(), 10)?;
+        let rest = error::strip_prefix(input_string, rest, " | ")?;
+        let (digits, rest) = parse_patterns(input_string, rest, 4)?;
+        error::end_of_line(input_string, rest)?;
 
-        let signal_patterns = split
-            .next()
-            .unwrap()
-            .split(' ')
-            .map(|s| s.to_owned())
-            .collect::<Vec<_>>();
+        res.push((signal_patterns, digits))
+    }
+
+    Ok(res)
+}
+
+/// `count` patterns separated by single spaces at the start of `rest`, and what follows them.
+fn parse_patterns<'a>(
+    input: &str,
+    mut rest: &'a str,
+    count: usize,
+) -> Result<(Vec<String>, &'a str)> {
+    let mut patterns = vec![];
+    for ix in 0..count {
+        if ix > 0 {
+            rest = error::strip_prefix(input, rest, " ")?;
+        }
+        let len = rest
+            .find(|c: char| !('a'..='g').contains(&c))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(error::expected(input, rest, "a pattern of letters a to g"));
+        }
+        let (pattern, after) = rest.split_at(len);
+        patterns.push(pattern.to_owned());
+        rest = after;
+    }
+    Ok((patterns, rest))
+}
 
-        let digits = split
-            .next()
-            .unwrap()
-            .split(' ')
-            .map(|s| s.to_owned())
+/// `size` displays, each with its own random wiring.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const DIGITS: [&str; 10] = [
+        "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+    ];
+
+    let mut lines = vec![];
+    for _ in 0..size {
+        let mut wires = ['a', 'b', 'c', 'd', 'e', 'f', 'g'];
+        rng.shuffle(&mut wires);
+        let scramble = |rng: &mut Rng, digit: &str| {
+            let mut segments = digit
+                .bytes()
+                .map(|s| wires[(s - b'a') as usize])
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut segments);
+            segments.into_iter().collect::<String>()
+        };
+
+        let mut patterns = DIGITS.iter().map(|d| scramble(rng, d)).collect::<Vec<_>>();
+        let output = (0..4)
+            .map(|_| {
+                let digit = DIGITS[rng.below(10)];
+                scramble(rng, digit)
+            })
             .collect::<Vec<_>>();
+        rng.shuffle(&mut patterns);
 
-        res.push((signal_patterns, digits))
+        lines.push(format!("{} | {}", patterns.join(" "), output.join(" ")));
     }
+    lines.join("\n")
+}
 
-    Ok(res)
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        let line =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+        assert_eq!(parse(line).unwrap()[0].1[3], "cdbaf");
+
+        let located = |input: &str| match parse(input) {
+            Err(Error::Parse {
+                column, message, ..
+            }) => (column, message),
+            res => panic!("unexpected {:?}", res),
+        };
+        assert_eq!(
+            located("abc | def"),
+            (
+                5,
+                "expected a pattern of letters a to g, found `|`".to_owned()
+            )
+        );
+        assert_eq!(
+            located(&line.replace("dab", "dxb")),
+            (28, "expected ` `, found `x`".to_owned())
+        );
+        assert_eq!(
+            located(&line.replace(" | ", " ")),
+            (59, "expected ` | `, found ` `".to_owned())
+        );
+        assert_eq!(
+            located(&line.replace(" cdbaf", "")),
+            (79, "expected ` `, found the end of the line".to_owned())
+        );
+        assert_eq!(
+            located(&format!("{} ab", line)),
+            (85, "expected the end of the line, found ` `".to_owned())
+        );
+    }
 }
@@ -1,113 +1,136 @@
 use std::collections::VecDeque;
 
-use anyhow::Context;
+use crate::error::{Error, Result};
+use crate::grid::{Grid, Pos};
+use crate::render::{self, Frame, Picture};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-pub fn part_01() -> anyhow::Result<u32> {
-    let inputs = inputs()?;
+pub struct Day09;
 
-    Ok(solve_01(inputs))
-}
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    const NAME: &'static str = "Smoke Basin";
 
-fn solve_01(inputs: Vec<Vec<u32>>) -> u32 {
-    let max_y = inputs.len();
-    let max_x = inputs[0].len();
+    type Input = Grid<u32>;
+    type Answer1 = u32;
+    type Answer2 = usize;
 
-    let mut sum = 0;
-    for iy in 0..max_y {
-        for ix in 0..max_x {
-            sum += calc_neighbours(ix, iy, &inputs);
-        }
+    fn parse(input: &str) -> Result<Grid<u32>> {
+        parse(input)
     }
-    sum
-}
 
-fn calc_neighbours(ix: usize, iy: usize, inputs: &Vec<Vec<u32>>) -> u32 {
-    let nnxs = get_neighbour_ixs(ix, iy, inputs);
+    fn part_01(heights: &Grid<u32>) -> Result<u32> {
+        solve_part1(heights)
+    }
 
-    let cur = inputs[iy][ix];
-    for (nx, ny) in nnxs {
-        if cur >= inputs[ny][nx] {
-            return 0;
-        }
+    fn part_02(heights: &Grid<u32>) -> Result<usize> {
+        solve_part2(heights)
     }
-    return 1 + cur;
-}
 
-fn get_neighbour_ixs(ix: usize, iy: usize, inputs: &Vec<Vec<u32>>) -> Vec<(usize, usize)> {
-    let max_y = inputs.len();
-    let max_x = inputs[0].len();
+    const INPUT_SIZE: usize = 100;
 
-    let mut nnxs = vec![];
-    if ix > 0 {
-        nnxs.push((ix - 1, iy));
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
     }
-    if iy > 0 {
-        nnxs.push((ix, iy - 1));
-    }
-    if ix < max_x - 1 {
-        nnxs.push((ix + 1, iy));
-    }
-    if iy < max_y - 1 {
-        nnxs.push((ix, iy + 1))
-    }
-    nnxs
 }
 
-pub fn part_02() -> anyhow::Result<usize> {
-    let mut inputs = inputs()?;
+pub fn solve_part1(heights: &Grid<u32>) -> Result<u32> {
+    check_not_empty(heights)?;
+    Ok(solve_01(heights))
+}
 
-    let max_y = inputs.len();
-    let max_x = inputs[0].len();
+pub fn solve_part2(heights: &Grid<u32>) -> Result<usize> {
+    check_not_empty(heights)?;
+    Ok(solve_02(heights))
+}
 
-    let mut res = vec![];
-    for iy in 0..max_y {
-        for ix in 0..max_x {
-            let one = basin_calc(ix, iy, &mut inputs);
-            if one > 0 {
-                res.push(one);
-            }
+fn check_not_empty(heights: &Grid<u32>) -> Result<()> {
+    if heights.is_empty() {
+        return Err(Error::Unsolvable("Empty height map".to_owned()));
+    }
+    Ok(())
+}
+
+fn solve_01(heights: &Grid<u32>) -> u32 {
+    heights
+        .positions()
+        .map(|pos| calc_neighbours(pos, heights))
+        .sum()
+}
+
+fn calc_neighbours(pos: Pos, heights: &Grid<u32>) -> u32 {
+    let cur = heights[pos];
+    for neighbour in heights.neighbours4(pos) {
+        if cur >= heights[neighbour] {
+            return 0;
         }
     }
+    1 + cur
+}
+
+fn solve_02(heights: &Grid<u32>) -> usize {
+    let basins = basins(heights);
+    let count = basins
+        .iter()
+        .filter_map(|(_, id)| *id)
+        .max()
+        .map_or(0, |id| id + 1);
+
+    let mut res = vec![0; count];
+    for id in basins.iter().filter_map(|(_, id)| *id) {
+        res[id] += 1;
+    }
 
     res.sort();
-    Ok(res.iter().rev().take(3).product())
+    res.iter().rev().take(3).product()
 }
 
-fn basin_calc(ix: usize, iy: usize, inputs: &mut Vec<Vec<u32>>) -> usize {
-    let mut num_filled = 0;
-    let mut queue = VecDeque::from([(ix, iy)]);
-    while let Some((x, y)) = queue.pop_front() {
-        if inputs[y][x] == 9 {
+/// Basin of every location, numbered in reading order; locations of height 9 are in none.
+pub fn basins(heights: &Grid<u32>) -> Grid<Option<usize>> {
+    let mut basins = Grid::new(heights.width(), heights.height(), None);
+    let mut count = 0;
+
+    for pos in heights.positions() {
+        if heights[pos] == 9 || basins[pos].is_some() {
             continue;
         }
 
-        inputs[y][x] = 9;
-        num_filled += 1;
-        for index in get_neighbour_ixs(x, y, inputs) {
-            queue.push_front(index);
+        basins[pos] = Some(count);
+        let mut queue = VecDeque::from([pos]);
+        while let Some(pos) = queue.pop_front() {
+            for neighbour in heights.neighbours4(pos) {
+                if heights[neighbour] != 9 && basins[neighbour].is_none() {
+                    basins[neighbour] = Some(count);
+                    queue.push_back(neighbour);
+                }
+            }
         }
+        count += 1;
     }
-    num_filled
+
+    basins
 }
 
-fn inputs() -> anyhow::Result<Vec<Vec<u32>>> {
-    let input_string =
-        std::fs::read_to_string("inputs/09_input.txt").context("Error while reading input")?;
+/// Every basin in a colour of its own, the 9s between them black.
+pub fn render(heights: &Grid<u32>) -> Vec<Frame> {
+    let picture = basins(heights).map(|id| id.map_or(render::BLACK, render::palette));
+    vec![Frame::new("09_basins", Picture::Color(picture))]
+}
 
-    Ok(parse(&input_string))
+pub fn parse(input: &str) -> Result<Grid<u32>> {
+    Grid::parse_digits(input)
 }
 
-fn parse(input: &str) -> Vec<Vec<u32>> {
-    let mut res = vec![];
-    for line in input.lines() {
-        let row = line
-            .trim()
-            .chars()
-            .map(|c| c.to_digit(10).unwrap())
-            .collect();
-        res.push(row)
+/// Height map of `size` by `size`, about a quarter of it being 9s.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut heights = Grid::new(size.max(1), size.max(1), 9);
+    for height in heights.values_mut() {
+        if !rng.one_in(4) {
+            *height = rng.below(9) as u32;
+        }
     }
-    res
+    heights.to_string()
 }
 
 #[cfg(test)]
@@ -123,8 +146,9 @@ mod tests {
         9856789892
         8767896789
         0899965670",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(solve_01(inputs), 17);
+        assert_eq!(solve_01(&inputs), 17);
     }
 }
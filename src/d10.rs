@@ -1,23 +1,64 @@
-use anyhow::Context;
-
 use std::collections::VecDeque;
 
-fn inputs() -> anyhow::Result<Vec<String>> {
-    let input_string =
-        std::fs::read_to_string("inputs/10_input.txt").context("Error while reading input")?;
+use crate::error::{Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const NAME: &'static str = "Syntax Scoring";
+
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<String>> {
+        parse(input)
+    }
+
+    fn part_01(inputs: &Vec<String>) -> Result<u32> {
+        solve_part1(inputs)
+    }
 
-    Ok(input_string
+    fn part_02(inputs: &Vec<String>) -> Result<u64> {
+        solve_part2(inputs)
+    }
+
+    const INPUT_SIZE: usize = 100;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<String>> {
+    input
         .lines()
-        .map(|s| s.to_owned())
-        .collect::<Vec<_>>())
+        .map(|line| match line.find(|c| !"([{<>}])".contains(c)) {
+            Some(ix) => Err(Error::parse_at(
+                input,
+                &line[ix..],
+                "expected only brackets",
+            )),
+            None => Ok(line.to_owned()),
+        })
+        .collect()
 }
 
-pub fn part_01() -> anyhow::Result<u32> {
-    let inputs = inputs()?;
+pub fn solve_part1(inputs: &[String]) -> Result<u32> {
+    Ok(solve_01(inputs))
+}
 
+pub fn solve_part2(inputs: &[String]) -> Result<u64> {
+    solve_02(inputs)
+}
+
+fn solve_01(inputs: &[String]) -> u32 {
     let mut res = 0;
     for input in inputs {
-        match find_corrupted_char(&input) {
+        match find_corrupted_char(input) {
             Some(')') => res += 3,
             Some(']') => res += 57,
             Some('}') => res += 1197,
@@ -26,7 +67,7 @@ pub fn part_01() -> anyhow::Result<u32> {
         }
     }
 
-    Ok(res)
+    res
 }
 
 fn find_corrupted_char(line: &str) -> Option<char> {
@@ -34,11 +75,9 @@ fn find_corrupted_char(line: &str) -> Option<char> {
     for ch in line.chars() {
         if ch == '(' || ch == '[' || ch == '{' || ch == '<' {
             deque.push_front(ch);
-        } else {
-            if let Some(open) = deque.pop_front() {
-                if !are_pairs(open, ch) {
-                    return Some(ch);
-                }
+        } else if let Some(open) = deque.pop_front() {
+            if !are_pairs(open, ch) {
+                return Some(ch);
             }
         }
     }
@@ -46,13 +85,10 @@ fn find_corrupted_char(line: &str) -> Option<char> {
 }
 
 fn are_pairs(left: char, right: char) -> bool {
-    match (left, right) {
-        ('(', ')') => true,
-        ('[', ']') => true,
-        ('{', '}') => true,
-        ('<', '>') => true,
-        _ => false,
-    }
+    matches!(
+        (left, right),
+        ('(', ')') | ('[', ']') | ('{', '}') | ('<', '>')
+    )
 }
 
 fn get_opens(line: &str) -> VecDeque<char> {
@@ -67,37 +103,76 @@ fn get_opens(line: &str) -> VecDeque<char> {
     deque
 }
 
-fn calc_score(deque: &mut VecDeque<char>) -> u64 {
-    let mut score = 0;
-    while !deque.is_empty() {
-        score *= 5;
-        score += match deque.pop_front() {
-            Some('(') => 1,
-            Some('[') => 2,
-            Some('{') => 3,
-            Some('<') => 4,
-            _ => panic!("at the disco"),
+fn calc_score(deque: &mut VecDeque<char>) -> Option<u64> {
+    let mut score = 0u64;
+    while let Some(open) = deque.pop_front() {
+        let points = match open {
+            '(' => 1,
+            '[' => 2,
+            '{' => 3,
+            _ => 4,
         };
+        score = score.checked_mul(5)?.checked_add(points)?;
     }
-    score
+    Some(score)
 }
 
-pub fn part_02() -> anyhow::Result<u64> {
-    let inputs = inputs()?;
-
-    let incompletes: Vec<String> = inputs
-        .into_iter()
+fn solve_02(inputs: &[String]) -> Result<u64> {
+    let incompletes: Vec<&String> = inputs
+        .iter()
         .filter(|s| find_corrupted_char(s).is_none())
         .collect();
 
     let mut sums: Vec<u64> = incompletes
         .iter()
-        .map(|l| calc_score(&mut get_opens(l)))
-        .collect();
+        .map(|l| {
+            calc_score(&mut get_opens(l))
+                .ok_or_else(|| Error::Overflow(format!("completion score of `{}`", l)))
+        })
+        .collect::<Result<_>>()?;
 
     sums.sort();
 
-    Ok(sums[sums.len() / 2])
+    sums.get(sums.len() / 2)
+        .copied()
+        .ok_or_else(|| Error::Unsolvable("All lines are corrupted".to_owned()))
+}
+
+/// `size` lines of chunks, about half of them corrupted and the rest (at least the first one)
+/// incomplete.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+    let mut lines = vec![];
+    for ix in 0..size.max(1) {
+        // balanced chunks nested at most 12 deep, so that completion scores fit in a u64
+        let mut line = vec![];
+        let mut open = vec![];
+        while line.len() < 100 || open.is_empty() {
+            if open.len() < 12 && (open.is_empty() || rng.one_in(2)) {
+                let (opening, closing) = *rng.choose(&PAIRS);
+                line.push(opening);
+                open.push(closing);
+            } else if let Some(closing) = open.pop() {
+                line.push(closing);
+            }
+        }
+
+        // the line stops with chunks still open, so it is incomplete unless corrupted
+        if ix > 0 && rng.one_in(2) {
+            let closing = (0..line.len())
+                .filter(|&ix| ")]}>".contains(line[ix]))
+                .collect::<Vec<_>>();
+            let ix = *rng.choose(&closing);
+            let wrong = ")]}>"
+                .chars()
+                .filter(|&c| c != line[ix])
+                .collect::<Vec<_>>();
+            line[ix] = *rng.choose(&wrong);
+        }
+        lines.push(line.into_iter().collect::<String>());
+    }
+    lines.join("\n")
 }
 
 #[cfg(test)]
@@ -1,96 +1,192 @@
-use anyhow::Context;
-use itertools::Itertools;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Display;
 
-use std::collections::VecDeque;
+use crate::error::{Error, Result};
+use crate::grid::Grid;
+use crate::render::{self, Frame, Picture};
+use crate::rng::Rng;
+use crate::solution::Solution;
+use crate::step::Simulation;
 
-fn inputs() -> anyhow::Result<Vec<Vec<u32>>> {
-    let input_string =
-        std::fs::read_to_string("inputs/11_input.txt").context("Error while reading input")?;
+pub struct Day11;
 
-    Ok(parse(&input_string))
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const NAME: &'static str = "Dumbo Octopus";
+
+    type Input = Grid<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Grid<u32>> {
+        parse(input)
+    }
+
+    fn part_01(octopuses: &Grid<u32>) -> Result<u32> {
+        solve_part1(octopuses)
+    }
+
+    fn part_02(octopuses: &Grid<u32>) -> Result<u32> {
+        solve_part2(octopuses)
+    }
+
+    const INPUT_SIZE: usize = 10;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
 }
 
-fn parse(s: &str) -> Vec<Vec<u32>> {
-    s.lines()
-        .map(|s| s.trim().chars().map(|c| c.to_digit(10).unwrap()).collect())
-        .collect::<Vec<_>>()
+pub fn parse(s: &str) -> Result<Grid<u32>> {
+    Grid::parse_digits(s)
 }
 
-pub fn part_01() -> anyhow::Result<u32> {
-    let mut inputs = inputs()?;
+pub fn solve_part1(octopuses: &Grid<u32>) -> Result<u32> {
+    let mut octopuses = octopuses.clone();
 
     let mut res = 0;
     for _s in 0..100 {
-        res += step(&mut inputs);
+        res += step(&mut octopuses);
     }
 
     Ok(res)
 }
 
-fn step(inputs: &mut Vec<Vec<u32>>) -> u32 {
+pub fn solve_part2(octopuses: &Grid<u32>) -> Result<u32> {
+    if octopuses.is_empty() {
+        return Err(Error::Unsolvable("No octopuses".to_owned()));
+    }
+    let mut octopuses = octopuses.clone();
+
+    let mut seen = HashSet::new();
+    let mut s = 0;
+    while seen.insert(octopuses.clone()) {
+        s += 1;
+        step(&mut octopuses);
+        if octopuses.iter().all(|(_, el)| *el == 0) {
+            return Ok(s);
+        }
+    }
+    Err(Error::Unsolvable(
+        "Octopuses never flash at the same time".to_owned(),
+    ))
+}
+
+fn step(octopuses: &mut Grid<u32>) -> u32 {
     let mut flash_cnt = 0;
 
     let mut flashing = VecDeque::new();
 
-    itertools::iproduct!(0..10, 0..10).for_each(|(x, y)| {
-        inputs[y][x] += 1;
-        if inputs[y][x] == 10 {
+    for pos in octopuses.positions().collect::<Vec<_>>() {
+        octopuses[pos] += 1;
+        if octopuses[pos] == 10 {
             flash_cnt += 1;
-            flashing.push_back((x, y));
+            flashing.push_back(pos);
         }
-    });
-
-    while !flashing.is_empty() {
-        let (ix, iy) = flashing.pop_front().unwrap();
-        let neighbours = neighbours(ix, iy);
-        for (nx, ny) in neighbours {
-            inputs[ny][nx] += 1;
-            if inputs[ny][nx] == 10 {
+    }
+
+    while let Some(pos) = flashing.pop_front() {
+        for neighbour in octopuses.neighbours8(pos).collect::<Vec<_>>() {
+            octopuses[neighbour] += 1;
+            if octopuses[neighbour] == 10 {
                 flash_cnt += 1;
-                flashing.push_back((nx, ny));
+                flashing.push_back(neighbour);
             }
         }
     }
 
-    itertools::iproduct!(0..10, 0..10).for_each(|(x, y)| {
-        if inputs[y][x] > 9 {
-            inputs[y][x] = 0;
+    for energy in octopuses.values_mut() {
+        if *energy > 9 {
+            *energy = 0;
         }
-    });
+    }
     flash_cnt
 }
 
-fn neighbours(ix: usize, iy: usize) -> Vec<(usize, usize)> {
-    let ix = ix as isize;
-    let iy = iy as isize;
+/// Octopuses stepped through one step at a time, until they all flash at once.
+#[derive(Clone, Debug)]
+pub struct Cavern {
+    octopuses: Grid<u32>,
+    steps: u32,
+    flashes: u32,
+}
+
+impl Cavern {
+    pub fn new(octopuses: Grid<u32>) -> Cavern {
+        Cavern {
+            octopuses,
+            steps: 0,
+            flashes: 0,
+        }
+    }
 
-    itertools::iproduct!(-1..=1, -1..=1)
-        .filter(|(dx, dy)| *dx != 0 || *dy != 0)
-        .map(|(dx, dy)| (ix + dx, iy + dy))
-        .filter(|&(x, y)| x >= 0 && x < 10 && y >= 0 && y < 10)
-        .map(|(x, y)| (x as usize, y as usize))
-        .collect()
+    fn synchronized(&self) -> bool {
+        self.steps > 0 && self.octopuses.iter().all(|(_, el)| *el == 0)
+    }
 }
 
-pub fn part_02() -> anyhow::Result<u32> {
-    let mut inputs = inputs()?;
+impl Display for Cavern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "after step {}, {} flashes:", self.steps, self.flashes)?;
+        write!(f, "{}", self.octopuses)
+    }
+}
 
-    let mut s = 0;
-    while true {
-        s += 1;
-        step(&mut inputs);
-        if inputs.iter().all(|row| row.iter().all(|el| *el == 0)) {
-            return Ok(s);
+impl Simulation for Cavern {
+    fn step(&mut self) -> Option<String> {
+        if self.synchronized() {
+            return None;
+        }
+
+        let flashed = step(&mut self.octopuses);
+        self.steps += 1;
+        self.flashes += flashed;
+        if self.synchronized() {
+            return Some(format!("all {} octopuses flashed at once", flashed));
         }
+        Some(format!("{} octopuses flashed", flashed))
     }
+}
 
-    Ok(0)
+/// Energy levels step by step until all octopuses flash at once, for at most 1000 steps. The
+/// ones that just flashed are white.
+pub fn render(octopuses: &Grid<u32>) -> Vec<Frame> {
+    let frame = |s: usize, octopuses: &Grid<u32>| {
+        let picture = octopuses.map(|&energy| match energy {
+            0 => 255,
+            _ => render::shade(energy, 12),
+        });
+        Frame::new(format!("11_step_{:04}", s), Picture::Gray(picture))
+    };
+
+    let mut octopuses = octopuses.clone();
+    let mut frames = vec![frame(0, &octopuses)];
+    for s in 1..=1000 {
+        step(&mut octopuses);
+        frames.push(frame(s, &octopuses));
+        if octopuses.iter().all(|(_, el)| *el == 0) {
+            break;
+        }
+    }
+    frames
 }
 
-fn pprint(n: &Vec<Vec<u32>>) {
-    for iy in 0..10 {
-        let line = n[iy].iter().map(|d| d.to_string()).join("");
-        println!("{}", line);
+/// Grid of `size` by `size` octopuses that flash all at once within 1000 steps.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    loop {
+        // uniformly random grids rarely synchronize, mostly equal energies do quickly
+        let mut octopuses = Grid::new(size, size, rng.below(10) as u32);
+        for energy in octopuses.values_mut() {
+            if rng.one_in(4) {
+                *energy = rng.below(10) as u32;
+            }
+        }
+
+        let mut simulated = octopuses.clone();
+        if (0..1000).any(|_| step(&mut simulated) as usize == size * size) {
+            return octopuses.to_string();
+        }
     }
 }
 
@@ -112,7 +208,7 @@ mod tests {
         5957959665
         6394862637";
 
-        let mut inputs = parse(inputs);
+        let mut inputs = parse(inputs).unwrap();
         let res = step(&mut inputs);
         assert_eq!(res, 35);
         assert_eq!(step(&mut inputs), 45);
@@ -122,7 +218,7 @@ mod tests {
 
     #[test]
     fn test_neighbours() {
-        let ns = neighbours(2, 0);
-        assert_eq!(ns.len(), 5);
+        let octopuses = Grid::new(10, 10, 0);
+        assert_eq!(octopuses.neighbours8((2, 0)).count(), 5);
     }
 }
@@ -1,36 +1,82 @@
 use std::collections::HashMap;
 
-use anyhow::Context;
 use std::collections::VecDeque;
 
-fn inputs() -> anyhow::Result<HashMap<String, Vec<String>>> {
-    let input_string =
-        std::fs::read_to_string("inputs/12_input.txt").context("Error while reading input")?;
+use crate::error::{Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-    Ok(parse(&input_string))
-}
+pub struct Day12;
 
-fn parse(s: &str) -> HashMap<String, Vec<String>> {
-    let mut map = HashMap::new();
-    for line in s.lines() {
-        let mut split = line.trim().split("-");
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    const NAME: &'static str = "Passage Pathing";
 
-        let from1 = split.next().unwrap().to_owned();
-        let to1 = split.next().unwrap().to_owned();
+    type Input = HashMap<String, Vec<String>>;
+    type Answer1 = i32;
+    type Answer2 = i32;
 
-        let from2 = to1.clone();
-        let to2 = from1.clone();
+    fn parse(input: &str) -> Result<HashMap<String, Vec<String>>> {
+        parse(input)
+    }
+
+    fn part_01(map: &HashMap<String, Vec<String>>) -> Result<i32> {
+        solve_part1(map)
+    }
+
+    fn part_02(map: &HashMap<String, Vec<String>>) -> Result<i32> {
+        solve_part2(map)
+    }
+
+    const INPUT_SIZE: usize = 22;
 
-        map.entry(from1).or_insert(vec![]).push(to1);
-        map.entry(from2).or_insert(vec![]).push(to2);
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
     }
-    map
 }
 
-pub fn part_01() -> anyhow::Result<i32> {
-    let inputs = inputs()?;
+pub fn solve_part1(map: &HashMap<String, Vec<String>>) -> Result<i32> {
+    check_big_caves(map)?;
+    solve_01(map, "start".to_owned())
+}
+
+pub fn solve_part2(map: &HashMap<String, Vec<String>>) -> Result<i32> {
+    check_big_caves(map)?;
+    solve_02(map, "start".to_owned())
+}
 
-    Ok(solveR_01(&inputs, "start".to_owned()))
+/// Two connected big caves could be walked back and forth forever.
+fn check_big_caves(map: &HashMap<String, Vec<String>>) -> Result<()> {
+    for (cave, neighbours) in map {
+        if let Some(neighbour) = neighbours
+            .iter()
+            .find(|n| !is_small_cave(cave) && !is_small_cave(n))
+        {
+            return Err(Error::Unsolvable(format!(
+                "Infinitely many paths through big caves {} and {}",
+                cave, neighbour
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn parse(s: &str) -> Result<HashMap<String, Vec<String>>> {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for line in s.lines() {
+        let line = line.trim();
+        let (from1, to1) = line
+            .split_once('-')
+            .ok_or_else(|| Error::parse_at(s, line, "expected a passage like `start-A`"))?;
+        let (from1, to1) = (from1.to_owned(), to1.to_owned());
+
+        let from2 = to1.clone();
+        let to2 = from1.clone();
+
+        map.entry(from1).or_default().push(to1);
+        map.entry(from2).or_default().push(to2);
+    }
+    Ok(map)
 }
 
 fn recurse_01(
@@ -38,118 +84,96 @@ fn recurse_01(
     current: String,
     curr_path: &mut VecDeque<String>,
     mut visited_twice: bool,
-) -> i32 {
+) -> Result<i32> {
     if current == "end" {
-        return 1;
+        return Ok(1);
     }
 
     if is_small_cave(&current) && curr_path.contains(&current) {
         if visited_twice || current == "start" {
-            return 0;
+            return Ok(0);
         }
         visited_twice = true;
     }
 
     let mut res = 0;
     curr_path.push_back(current.clone());
-    let neighbours = map.get(&current).unwrap();
+    let neighbours = map
+        .get(&current)
+        .ok_or_else(|| Error::MissingRule(format!("no passage leads out of cave {}", current)))?;
     for neighbour in neighbours {
-        res += recurse_01(map, neighbour.clone(), curr_path, visited_twice);
+        res += recurse_01(map, neighbour.clone(), curr_path, visited_twice)?;
     }
     curr_path.pop_back();
 
-    res
+    Ok(res)
 }
 
-fn solveR_01(map: &HashMap<String, Vec<String>>, current: String) -> i32 {
+fn solve_01(map: &HashMap<String, Vec<String>>, current: String) -> Result<i32> {
     let mut curr_path = VecDeque::new();
 
-    let res = recurse_01(map, current, &mut curr_path, true);
-    res
+    recurse_01(map, current, &mut curr_path, true)
 }
 
-fn solveR_02(map: &HashMap<String, Vec<String>>, current: String) -> i32 {
+fn solve_02(map: &HashMap<String, Vec<String>>, current: String) -> Result<i32> {
     let mut curr_path = VecDeque::new();
 
-    let res = recurse_01(map, current, &mut curr_path, false);
-    res
+    recurse_01(map, current, &mut curr_path, false)
 }
 
-fn is_small_cave(s: &String) -> bool {
+fn is_small_cave(s: &str) -> bool {
     s.chars().all(|c| c.is_lowercase())
 }
 
-pub fn part_02() -> anyhow::Result<i32> {
-    let inputs = inputs()?;
-
-    Ok(solveR_02(&inputs, "start".to_owned()))
-}
-
-#[cfg(test)]
-mod tests {
-
-    use super::*;
-
-    #[test]
-    fn test_part_01_01() {
-        let map = parse(
-            "start-A
-        start-b
-        A-c
-        A-b
-        b-d
-        A-end
-        b-end",
-        );
-        let res = solveR_01(&map, "start".to_owned());
-
-        assert_eq!(res, 10);
+/// About `size` passages between small and big caves, never joining two big caves and with at
+/// most three passages out of a big cave.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let name = |rng: &mut Rng, big: bool| {
+        let base = if big { b'A' } else { b'a' };
+        (0..2)
+            .map(|_| (base + rng.below(26) as u8) as char)
+            .collect::<String>()
+    };
+    let mut small = vec!["start".to_owned(), "end".to_owned()];
+    while small.len() < size / 3 + 3 {
+        let cave = name(rng, false);
+        if !small.contains(&cave) {
+            small.push(cave);
+        }
     }
-
-    #[test]
-    fn test_part_01_02() {
-        let map = parse(
-            "dc-end
-        HN-start
-        start-kj
-        dc-start
-        dc-HN
-        LN-dc
-        HN-end
-        kj-sa
-        kj-HN
-        kj-dc",
-        );
-        let res = solveR_01(&map, "start".to_owned());
-
-        assert_eq!(res, 19);
+    let mut big = vec![];
+    while big.len() < size / 8 + 1 {
+        let cave = name(rng, true);
+        if !big.contains(&cave) {
+            big.push(cave);
+        }
     }
 
-    #[test]
-    fn test_part_01_03() {
-        let map = parse(
-            "fs-end
-        he-DX
-        fs-he
-        start-DX
-        pj-DX
-        end-zg
-        zg-sl
-        zg-pj
-        pj-he
-        RW-he
-        fs-DX
-        pj-RW
-        zg-RW
-        start-pj
-        he-WI
-        zg-he
-        pj-fs
-        start-RW",
-        );
-
-        let res = solveR_01(&map, "start".to_owned());
-
-        assert_eq!(res, 226);
+    // start and end are connected through the first small cave
+    let mut passages = vec![
+        (small[0].clone(), small[2].clone()),
+        (small[2].clone(), small[1].clone()),
+    ];
+    while passages.len() < size.max(2) {
+        let from = rng.choose(&small).clone();
+        let to = if rng.one_in(2) {
+            rng.choose(&big).clone()
+        } else {
+            rng.choose(&small).clone()
+        };
+        let known = passages
+            .iter()
+            .any(|(a, b)| (*a == from && *b == to) || (*a == to && *b == from));
+        // every neighbour of a big cave doubles the paths through it
+        let crowded = passages.iter().filter(|(_, b)| *b == to).count() >= 3;
+        if from != to && !known && !(crowded && big.contains(&to)) {
+            passages.push((from, to));
+        }
     }
+
+    passages
+        .iter()
+        .map(|(from, to)| format!("{}-{}", from, to))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
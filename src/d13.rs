@@ -1,107 +1,376 @@
-use anyhow::Context;
 use std::collections::HashSet;
 
-pub fn part_01() -> anyhow::Result<usize> {
-    let (mut dots, folds) = inputs()?;
+use crate::error::{self, Error, Result};
+use crate::geom::Point2;
+use crate::grid::Grid;
+use crate::render::{Frame, Picture};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-    let fold = folds.into_iter().next().unwrap();
+pub type Dots = HashSet<Point2>;
+
+/// Line the paper gets folded along: `x=..` is vertical, `y=..` horizontal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fold {
+    X(i32),
+    Y(i32),
+}
+
+impl Fold {
+    /// Where `dot` ends up after the fold, dots above or left of the line stay put.
+    pub fn apply(self, dot: Point2) -> Point2 {
+        match self {
+            Fold::X(x) if dot.x > x => Point2::new(2 * x - dot.x, dot.y),
+            Fold::Y(y) if dot.y > y => Point2::new(dot.x, 2 * y - dot.y),
+            _ => dot,
+        }
+    }
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    const NAME: &'static str = "Transparent Origami";
+
+    type Input = (Dots, Vec<Fold>);
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<(Dots, Vec<Fold>)> {
+        parse(input)
+    }
+
+    fn part_01(input: &(Dots, Vec<Fold>)) -> Result<usize> {
+        solve_part1(input)
+    }
+
+    fn part_02(input: &(Dots, Vec<Fold>)) -> Result<String> {
+        solve_part2(input)
+    }
+
+    const INPUT_SIZE: usize = 900;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn solve_part1((dots, folds): &(Dots, Vec<Fold>)) -> Result<usize> {
+    let mut dots = dots.clone();
+
+    let fold = *folds
+        .first()
+        .ok_or_else(|| Error::Unsolvable("Missing folding instructions".to_owned()))?;
 
     do_fold(fold, &mut dots);
 
     Ok(dots.len())
 }
 
-pub fn part_02() -> anyhow::Result<()> {
-    let (mut dots, folds) = inputs()?;
+pub fn solve_part2((dots, folds): &(Dots, Vec<Fold>)) -> Result<String> {
+    let mut dots = dots.clone();
 
-    for fold in folds {
+    for &fold in folds {
         do_fold(fold, &mut dots);
     }
 
-    print_dots(&dots);
+    read_letters(&dots)
+}
 
-    Ok(())
+fn do_fold(fold: Fold, dots: &mut Dots) {
+    *dots = dots.iter().map(|&dot| fold.apply(dot)).collect();
 }
 
-fn do_fold(fold: (isize, isize), dots: &mut HashSet<(isize, isize)>) {
-    if fold.0 > 0 {
-        let x_fold = fold.0;
-        let starting_dots = dots.iter().copied().collect::<Vec<_>>();
-        for dot in starting_dots {
-            if dot.0 > x_fold {
-                dots.remove(&dot);
-                dots.insert((2 * x_fold - dot.0, dot.1));
-            }
-        }
-    } else {
-        let y_fold = fold.1;
-        let starting_dots = dots.iter().copied().collect::<Vec<_>>();
-        for dot in starting_dots {
-            if dot.1 > y_fold {
-                dots.remove(&dot);
-                dots.insert((dot.0, 2 * y_fold - dot.1));
-            }
+/// The capital letters of the Advent of Code font, 4 dots wide and 6 high.
+const FONT: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Reads the letters spelled by the dots from the top left corner, each 4 dots wide with a
+/// blank column after it.
+pub fn read_letters(dots: &Dots) -> Result<String> {
+    let Some(max_x) = dots.iter().map(|d| d.x).max() else {
+        return Err(Error::Unsolvable("No dots left to read".to_owned()));
+    };
+    if dots.iter().any(|d| d.x < 0 || !(0..6).contains(&d.y)) {
+        return Err(Error::Unsolvable(format!(
+            "Letters are 6 dots high, some dots are outside of them:\n{}",
+            render_dots(dots)
+        )));
+    }
+
+    let mut letters = String::new();
+    let mut unknown = vec![];
+    for left in (0..=max_x).step_by(5) {
+        let glyph = (0..6)
+            .map(|y| {
+                (left..left + 4)
+                    .map(|x| match dots.contains(&Point2::new(x, y)) {
+                        true => '#',
+                        false => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        // a dot in the blank column means the letter is not one of the font's either
+        let blank = (0..6).all(|y| !dots.contains(&Point2::new(left + 4, y)));
+        match FONT.iter().find(|(_, rows)| rows[..] == glyph[..]) {
+            Some(&(letter, _)) if blank => letters.push(letter),
+            _ => unknown.push(format!(
+                "#{} (x={})",
+                letters.len() + unknown.len() + 1,
+                left
+            )),
         }
     }
+
+    if !unknown.is_empty() {
+        return Err(Error::Unsolvable(format!(
+            "Unrecognized letters {} in:\n{}",
+            unknown.join(", "),
+            render_dots(dots)
+        )));
+    }
+    Ok(letters)
 }
 
-fn print_dots(dots: &HashSet<(isize, isize)>) {
-    let max_x = dots.iter().map(|d| d.0).max().unwrap();
-    let max_y = dots.iter().map(|d| d.1).max().unwrap();
+fn render_dots(dots: &Dots) -> String {
+    let max_x = dots.iter().map(|d| d.x).max().unwrap_or(0);
+    let max_y = dots.iter().map(|d| d.y).max().unwrap_or(0);
 
+    let mut lines = vec![];
     for y in 0..=max_y {
         let mut line = String::new();
         for x in 0..=max_x {
-            if dots.contains(&(x, y)) {
+            if dots.contains(&Point2::new(x, y)) {
                 line.push('#');
             } else {
                 line.push('.')
             }
         }
-        println!("{}", line);
+        lines.push(line);
     }
+    lines.join("\n")
 }
 
-fn inputs() -> anyhow::Result<(HashSet<(isize, isize)>, Vec<(isize, isize)>)> {
-    let input_string =
-        std::fs::read_to_string("inputs/13_input.txt").context("Error while reading input")?;
+/// The paper before and after every fold.
+pub fn render((dots, folds): &(Dots, Vec<Fold>)) -> Vec<Frame> {
+    let mut dots = dots.clone();
+    let mut frames = vec![render_fold(0, &dots)];
+    for (ix, &fold) in folds.iter().enumerate() {
+        do_fold(fold, &mut dots);
+        frames.push(render_fold(ix + 1, &dots));
+    }
+    frames
+}
+
+fn render_fold(ix: usize, dots: &Dots) -> Frame {
+    let on_paper = dots
+        .iter()
+        .filter_map(|d| Some((usize::try_from(d.x).ok()?, usize::try_from(d.y).ok()?)))
+        .collect::<Vec<_>>();
+    let width = on_paper.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let height = on_paper.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
 
-    parse(&input_string)
+    let mut paper = Grid::new(width, height, 0);
+    for pos in on_paper {
+        paper[pos] = 255;
+    }
+    Frame::new(format!("13_fold_{:02}", ix), Picture::Gray(paper))
 }
 
-fn parse(input: &str) -> anyhow::Result<(HashSet<(isize, isize)>, Vec<(isize, isize)>)> {
+pub fn parse(input: &str) -> Result<(Dots, Vec<Fold>)> {
     let mut sections_split = input.split("\n\n");
 
-    let dots_section = sections_split.next().context("Missing section with dots")?;
+    let missing = |what: &str| {
+        Error::parse_at(
+            input,
+            &input[input.len()..],
+            format!("missing section with {}", what),
+        )
+    };
+    let dots_section = sections_split.next().ok_or_else(|| missing("dots"))?;
     let folds_section = sections_split
         .next()
-        .context("Missing section with folding instructions")?;
+        .ok_or_else(|| missing("folding instructions"))?;
 
     let dots = dots_section
         .lines()
         .map(|l| {
-            let mut s = l.split(",");
-            (
-                s.next().unwrap().parse::<isize>().unwrap(),
-                s.next().unwrap().parse::<isize>().unwrap(),
-            )
+            let (x, y) = l
+                .split_once(',')
+                .ok_or_else(|| Error::parse_at(input, l, "expected a dot like `6,10`"))?;
+            Ok(Point2::new(
+                error::parse_at(input, x, "coordinate")?,
+                error::parse_at(input, y, "coordinate")?,
+            ))
         })
-        .collect::<HashSet<_>>();
+        .collect::<Result<HashSet<_>>>()?;
 
     let folds = folds_section
         .lines()
         .map(|l| {
-            let mut split = l.split("=");
-            let axis_char = split.next().unwrap().chars().last().unwrap();
-
-            let number = split.next().unwrap().parse::<isize>().unwrap();
-            if axis_char == 'x' {
-                (number, 0)
-            } else {
-                (0, number)
+            let (axis, number) = l
+                .strip_prefix("fold along ")
+                .and_then(|l| l.split_once('='))
+                .ok_or_else(|| {
+                    Error::parse_at(input, l, "expected a fold like `fold along y=7`")
+                })?;
+            let number = error::parse_at(input, number, "fold line")?;
+            match axis {
+                "x" => Ok(Fold::X(number)),
+                "y" => Ok(Fold::Y(number)),
+                _ => Err(Error::parse_at(
+                    input,
+                    axis,
+                    format!("unknown axis `{}`", axis),
+                )),
             }
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>>>()?;
 
     Ok((dots, folds))
 }
+
+/// About `size` dots, up to half the positions the code can unfold into, folding up into eight
+/// random letters after five vertical and seven horizontal folds like the real input.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let (mut width, mut height) = (40, 6);
+    let mut folds = vec![];
+    for ix in 0..12 {
+        if ix < 10 && ix % 2 == 0 {
+            folds.push(Fold::X(width));
+            width = 2 * width + 1;
+        } else {
+            folds.push(Fold::Y(height));
+            height = 2 * height + 1;
+        }
+    }
+    folds.reverse();
+
+    // eight 4x6 letters with a blank column after each
+    let mut code = vec![];
+    for left in (0..40).step_by(5) {
+        let (_, rows) = rng.choose(&FONT);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    code.push(Point2::new(left + x as i32, y as i32));
+                }
+            }
+        }
+    }
+
+    // unfold every dot of the code, mirroring it at random on each fold. Each dot unfolds into
+    // one of 2^12 positions, and stopping at half of them keeps random draws finding new ones.
+    let reachable = code.len() << folds.len();
+    let mut dots = Dots::new();
+    while dots.len() < size.clamp(code.len(), reachable / 2) {
+        for &dot in &code {
+            let dot = folds.iter().rev().fold(dot, |dot, &fold| match fold {
+                Fold::X(x) if rng.one_in(2) => Point2::new(2 * x - dot.x, dot.y),
+                Fold::Y(y) if rng.one_in(2) => Point2::new(dot.x, 2 * y - dot.y),
+                _ => dot,
+            });
+            dots.insert(dot);
+        }
+    }
+
+    let mut dots = dots.into_iter().collect::<Vec<_>>();
+    dots.sort_unstable();
+    rng.shuffle(&mut dots);
+    let mut lines = dots.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+    lines.push(String::new());
+    for fold in folds {
+        lines.push(match fold {
+            Fold::X(x) => format!("fold along x={}", x),
+            Fold::Y(y) => format!("fold along y={}", y),
+        });
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_read_letters() {
+        let dots = |rows: &[&str]| {
+            let mut dots = Dots::new();
+            for (y, row) in rows.iter().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    if c == '#' {
+                        dots.insert(Point2::new(x as i32, y as i32));
+                    }
+                }
+            }
+            dots
+        };
+
+        let code = dots(&[
+            ".###.####.#..#.",
+            "..#.....#.#.#..",
+            "..#....#..##...",
+            "..#...#...#.#..",
+            "..#..#....#.#..",
+            ".###.####.#..#.",
+        ]);
+        assert_eq!(read_letters(&code).unwrap(), "IZK");
+
+        let smudged = dots(&[
+            ".###.####.#..#.",
+            "..#.....#.#.#..",
+            "..#....#..##...",
+            "..#..##...#.#..",
+            "..#..#....#.#..",
+            ".###.####.#..#.",
+        ]);
+        let err = read_letters(&smudged).unwrap_err().to_string();
+        assert!(err.contains("#2 (x=5)"), "{}", err);
+        assert!(!err.contains("#1"), "{}", err);
+
+        let stray = dots(&[
+            ".###.####.#..#.",
+            "..#.....#.#.#..",
+            "..#....#..##...",
+            "..#...#...#.#.#",
+            "..#..#....#.#..",
+            ".###.####.#..#.",
+        ]);
+        let err = read_letters(&stray).unwrap_err().to_string();
+        assert!(err.contains("Unrecognized letters #3 (x=10)"), "{}", err);
+        assert!(err.contains("..#...#...#.#.#"), "{}", err);
+
+        assert!(read_letters(&Dots::new()).is_err());
+        assert!(read_letters(&dots(&["#", "#", "#", "#", "#", "#", "#"])).is_err());
+    }
+
+    #[test]
+    fn test_generate_beyond_reachable() {
+        let input = generate(&mut Rng::new(3), usize::MAX);
+        let (dots, folds) = parse(&input).unwrap();
+        assert_eq!(folds.len(), 12);
+        assert!(dots.len() <= (40 * 6) << 11, "{}", dots.len());
+        assert_eq!(solve_part2(&(dots, folds)).unwrap().len(), 8);
+    }
+}
@@ -1,21 +1,57 @@
-use anyhow::Context;
 use itertools::Itertools;
 use std::collections::HashMap;
 
-pub fn part_01() -> anyhow::Result<u64> {
-    let (starting_polimer, map) = inputs()?;
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-    Ok(solve_01(10, starting_polimer, map))
+pub type Rules = HashMap<(char, char), char>;
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    const NAME: &'static str = "Extended Polymerization";
+
+    type Input = (String, Rules);
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<(String, Rules)> {
+        parse(input)
+    }
+
+    fn part_01(input: &(String, Rules)) -> Result<u64> {
+        solve_part1(input)
+    }
+
+    fn part_02(input: &(String, Rules)) -> Result<u64> {
+        solve_part2(input)
+    }
+
+    const INPUT_SIZE: usize = 20;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn solve_part1((starting_polimer, map): &(String, Rules)) -> Result<u64> {
+    solve_01(10, starting_polimer, map)
+}
+
+pub fn solve_part2((starting_polimer, map): &(String, Rules)) -> Result<u64> {
+    solve_02(40, starting_polimer, map)
 }
 
-fn solve_01(steps: usize, starting_polimer: String, map: HashMap<(char, char), char>) -> u64 {
+pub(crate) fn solve_01(steps: usize, starting_polimer: &str, map: &Rules) -> Result<u64> {
     let mut results = HashMap::new();
     starting_polimer
         .chars()
         .for_each(|c| *results.entry(c).or_insert(0) += 1);
 
     for (c0, c1) in starting_polimer.chars().tuple_windows() {
-        go_deeper(steps, c0, c1, &map, &mut results);
+        go_deeper(steps, c0, c1, map, &mut results)?;
     }
 
     calc_res(&results)
@@ -25,23 +61,39 @@ fn go_deeper(
     steps: usize,
     c0: char,
     c1: char,
-    mappings: &HashMap<(char, char), char>,
+    mappings: &Rules,
     results: &mut HashMap<char, u64>,
-) {
+) -> Result<()> {
     if steps == 0 {
-        return;
+        return Ok(());
     }
 
-    let c2 = *mappings.get(&(c0, c1)).unwrap();
+    let c2 = rule(mappings, (c0, c1))?;
     *results.entry(c2).or_insert(0) += 1;
 
-    go_deeper(steps - 1, c0, c2, mappings, results);
-    go_deeper(steps - 1, c2, c1, mappings, results);
+    go_deeper(steps - 1, c0, c2, mappings, results)?;
+    go_deeper(steps - 1, c2, c1, mappings, results)
 }
 
-pub fn part_02() -> anyhow::Result<u64> {
-    let (starting_polimer, map) = inputs()?;
+fn rule(mappings: &Rules, pair: (char, char)) -> Result<char> {
+    mappings.get(&pair).copied().ok_or_else(|| {
+        Error::MissingRule(format!("no insertion rule for pair {}{}", pair.0, pair.1))
+    })
+}
 
+fn add_count<K: std::hash::Hash + Eq>(
+    counts: &mut HashMap<K, u64>,
+    key: K,
+    cnt: u64,
+) -> Result<()> {
+    let count = counts.entry(key).or_insert(0);
+    *count = count
+        .checked_add(cnt)
+        .ok_or_else(|| Error::Overflow("polymer grew too long to count".to_owned()))?;
+    Ok(())
+}
+
+pub(crate) fn solve_02(steps: usize, starting_polimer: &str, map: &Rules) -> Result<u64> {
     let mut results = HashMap::new();
     starting_polimer
         .chars()
@@ -52,50 +104,112 @@ pub fn part_02() -> anyhow::Result<u64> {
         *pairs.entry((c0, c1)).or_insert(0) += 1;
     }
 
-    for _i in 0..40 {
+    for _i in 0..steps {
         let mut after = HashMap::new();
         for (&from, &cnt) in pairs.iter() {
-            let from = from.clone();
-            let to = map.get(&from).unwrap().clone();
-            *after.entry((from.0, to)).or_insert(0) += cnt;
-            *after.entry((to, from.1)).or_insert(0) += cnt;
-            *results.entry(to).or_insert(0) += cnt;
+            let to = rule(map, from)?;
+            add_count(&mut after, (from.0, to), cnt)?;
+            add_count(&mut after, (to, from.1), cnt)?;
+            add_count(&mut results, to, cnt)?;
         }
         pairs = after;
     }
 
-    Ok(calc_res(&results))
+    calc_res(&results)
 }
 
-fn calc_res(results: &HashMap<char, u64>) -> u64 {
-    let most_common = results.iter().max_by(|x, y| x.1.cmp(y.1)).unwrap();
-    let least_common = results.iter().min_by(|x, y| x.1.cmp(y.1)).unwrap();
+fn calc_res(results: &HashMap<char, u64>) -> Result<u64> {
+    let most_common = results.values().max();
+    let least_common = results.values().min();
 
-    *most_common.1 - *least_common.1
+    match (most_common, least_common) {
+        (Some(most), Some(least)) => Ok(most - least),
+        _ => Err(Error::Unsolvable("Empty polymer template".to_owned())),
+    }
 }
 
-fn inputs() -> anyhow::Result<(String, HashMap<(char, char), char>)> {
-    let input_string =
-        std::fs::read_to_string("inputs/14_input.txt").context("Error while reading input")?;
-
-    let mut split = input_string.split("\n\n");
-
-    let starting_polimer = split.next().context("Bad input")?.trim().to_owned();
+pub fn parse(input_string: &str) -> Result<(String, Rules)> {
+    let (starting_polimer, mappings) = input_string.split_once("\n\n").ok_or_else(|| {
+        Error::parse_at(
+            input_string,
+            &input_string[input_string.len()..],
+            "missing insertion rules",
+        )
+    })?;
+    let starting_polimer = starting_polimer.trim().to_owned();
 
-    let mappings = split.next().context("Bad input")?;
     let mut map = HashMap::new();
     for line in mappings.lines() {
-        let mut split = line.trim().split(" -> ");
-        let mut from = split.next().context("Bad input ()->")?.chars();
-        let to = split.next().context("bad input ->()")?;
-
-        let (c0, c1) = (
-            from.next().context("bad input (c0,_)")?,
-            from.next().context("bad input (_,c1)")?,
-        );
-        let to = to.chars().next().context("bad input (to)")?;
+        // a rule like `CH -> B`, read piece by piece
+        let rest = line.trim();
+        let (c0, rest) = element(input_string, rest)?;
+        let (c1, rest) = element(input_string, rest)?;
+        let rest = error::strip_prefix(input_string, rest, " -> ")?;
+        let (to, rest) = element(input_string, rest)?;
+        error::end_of_line(input_string, rest)?;
         map.insert((c0, c1), to);
     }
 
     Ok((starting_polimer, map))
 }
+
+/// Element at the start of `rest`, an uppercase letter, and what follows it.
+fn element<'a>(input: &str, rest: &'a str) -> Result<(char, &'a str)> {
+    match rest.chars().next() {
+        Some(c) if c.is_ascii_uppercase() => Ok((c, &rest[1..])),
+        _ => Err(error::expected(input, rest, "an element letter")),
+    }
+}
+
+/// Template of `size` elements and an insertion rule for every pair of the ten elements.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let elements = ['B', 'C', 'F', 'H', 'K', 'N', 'O', 'P', 'S', 'V'];
+
+    let template = (0..size.max(2))
+        .map(|_| *rng.choose(&elements))
+        .collect::<String>();
+    let rules = elements
+        .iter()
+        .cartesian_product(elements.iter())
+        .map(|(c0, c1)| format!("{}{} -> {}", c0, c1, rng.choose(&elements)))
+        .collect::<Vec<_>>();
+
+    format!("{}\n\n{}", template, rules.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        let (template, rules) = parse("NNCB\n\nCH -> B\nHH -> N").unwrap();
+        assert_eq!((template.as_str(), rules[&('H', 'H')]), ("NNCB", 'N'));
+
+        let located = |input: &str| match parse(input) {
+            Err(Error::Parse {
+                line,
+                column,
+                message,
+            }) => (line, column, message),
+            res => panic!("unexpected {:?}", res),
+        };
+        assert_eq!(
+            located("NNCB\n\nCH -> B\nHH - N"),
+            (4, 3, "expected ` -> `, found ` `".to_owned())
+        );
+        assert_eq!(
+            located("NNCB\n\nCH -> b"),
+            (3, 7, "expected an element letter, found `b`".to_owned())
+        );
+        assert_eq!(
+            located("NNCB\n\nCHH -> B"),
+            (3, 3, "expected ` -> `, found `H`".to_owned())
+        );
+        assert_eq!(
+            located("NNCB\n\nCH -> BB"),
+            (3, 8, "expected the end of the line, found `B`".to_owned())
+        );
+    }
+}
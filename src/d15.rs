@@ -1,198 +1,175 @@
-use anyhow::Context;
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use crate::error::{Error, Result};
+use crate::grid::{Grid, Pos};
+use crate::render::{self, Frame, Picture};
+use crate::rng::Rng;
+use crate::search::{self, Search};
+use crate::solution::Solution;
 
-pub fn part_01() -> anyhow::Result<u32> {
-    let starting = inputs()?;
+pub struct Day15;
 
-    Ok(solve_01(&starting)[starting.len() - 1][starting[0].len() - 1])
-}
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    const NAME: &'static str = "Chiton";
 
-pub fn part_02() -> anyhow::Result<u32> {
-    let starting = inputs()?;
+    type Input = Grid<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
 
-    let quintupled = quintuple(starting);
+    fn parse(input: &str) -> Result<Grid<u32>> {
+        parse(input)
+    }
 
-    let h = quintupled.len() as isize;
-    let w = quintupled[0].len() as isize;
+    fn part_01(risc_map: &Grid<u32>) -> Result<u32> {
+        solve_part1(risc_map)
+    }
 
-    let res = a_star2(&quintupled, (0, 0), ((h - 1) as i16, (w - 1) as i16)).unwrap();
-    Ok(res) // 2882 too high
-}
+    fn part_02(risc_map: &Grid<u32>) -> Result<u32> {
+        solve_part2(risc_map)
+    }
 
-fn a_start(risc_map: &Vec<Vec<u32>>, start: (isize, isize), goal: (isize, isize)) -> Option<u32> {
-    let mut g_scores = HashMap::new();
-    g_scores.insert(start, 0);
+    const INPUT_SIZE: usize = 100;
 
-    let mut f_scores = HashMap::new();
-    f_scores.insert(start, heuristic(start, goal));
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
 
-    let mut open_set = HashSet::new();
-    open_set.insert((heuristic(start, goal), start));
+pub fn solve_part1(risc_map: &Grid<u32>) -> Result<u32> {
+    lowest_total_risk(risc_map)
+}
 
-    while let Some(&curr) = open_set.iter().next() {
-        open_set.remove(&curr);
+pub fn solve_part2(risc_map: &Grid<u32>) -> Result<u32> {
+    lowest_total_risk(&quintuple(risc_map))
+}
 
-        let (f_score, pos) = curr;
-        if pos == goal {
-            return Some(f_score);
-        }
-        let (y, x) = pos;
-        let g_score = g_scores[&pos];
-
-        for (dy, dx) in itertools::iproduct!(-1..=1, -1..=1) {
-            let (y, x) = (y + dy, x + dx);
-            let risc =
-                if let Some(&risc) = risc_map.get(y as usize).and_then(|row| row.get(x as usize)) {
-                    risc
-                } else {
-                    continue;
-                };
-            let new_pos = (y, x);
-            let tentative_g_score = g_score + risc;
-            let old_g_score = g_scores.get(&new_pos).copied();
-            if tentative_g_score < old_g_score.unwrap_or(u32::MAX) {
-                if old_g_score.is_some() {
-                    open_set.remove(&(f_scores[&new_pos], new_pos));
-                }
-                let new_f_score = tentative_g_score + heuristic(new_pos, goal);
-                f_scores.insert(new_pos, new_f_score);
-                g_scores.insert(new_pos, tentative_g_score);
-                open_set.insert((new_f_score, new_pos));
-            }
-        }
-    }
+fn lowest_total_risk(risc_map: &Grid<u32>) -> Result<u32> {
+    search::a_star(&Cave::new(risc_map)?, (0, 0)).ok_or_else(no_path)
+}
 
-    None
+fn no_path() -> Error {
+    Error::Unsolvable("No path to the bottom right corner".to_owned())
 }
 
-fn heuristic(start: (isize, isize), goal: (isize, isize)) -> u32 {
-    (start.1 - goal.1).abs() as u32 + (start.0 - goal.0).abs() as u32
+/// Risk maps of both parts, darker where the risk is lower, with the lowest risk path in red.
+pub fn render(risc_map: &Grid<u32>) -> Result<Vec<Frame>> {
+    Ok(vec![
+        render_path("15_path", risc_map)?,
+        render_path("15_full_path", &quintuple(risc_map))?,
+    ])
 }
 
-fn solve_01(risc_map: &Vec<Vec<u32>>) -> Vec<Vec<u32>> {
-    let mut result_map = risc_map.clone();
-    result_map[0][0] = 0;
+fn render_path(name: &str, risc_map: &Grid<u32>) -> Result<Frame> {
+    let path = search::a_star_path(&Cave::new(risc_map)?, (0, 0)).ok_or_else(no_path)?;
 
-    for x in 1..risc_map[0].len() {
-        result_map[0][x] = result_map[0][x - 1] + risc_map[0][x];
+    let mut picture = risc_map.map(|&risc| [render::shade(risc, 9); 3]);
+    for pos in path.states {
+        picture[pos] = render::RED;
     }
+    Ok(Frame::new(name, Picture::Color(picture)))
+}
 
-    for y in 1..risc_map.len() {
-        result_map[y][0] = result_map[y - 1][0] + risc_map[y][0];
-    }
+/// Paths from the top left corner to the bottom right one, entering a cell costs its risk.
+struct Cave<'a> {
+    risc_map: &'a Grid<u32>,
+    goal: Pos,
+}
 
-    for y in 1..risc_map.len() {
-        for x in 1..risc_map.len() {
-            result_map[y][x] = std::cmp::min(
-                result_map[y][x - 1] + risc_map[y][x],
-                result_map[y - 1][x] + risc_map[y][x],
-            );
+impl<'a> Cave<'a> {
+    fn new(risc_map: &'a Grid<u32>) -> Result<Cave<'a>> {
+        if risc_map.is_empty() {
+            return Err(Error::Unsolvable("Empty risk map".to_owned()));
         }
+        Ok(Cave {
+            risc_map,
+            goal: (risc_map.width() - 1, risc_map.height() - 1),
+        })
     }
-    result_map
 }
 
-fn inputs() -> anyhow::Result<Vec<Vec<u32>>> {
-    let input_string =
-        std::fs::read_to_string("inputs/15_input.txt").context("Error while reading input")?;
+impl Search for Cave<'_> {
+    type State = Pos;
+    type Cost = u32;
 
-    parse(&input_string)
-}
+    fn neighbours(&self, &pos: &Pos) -> Vec<(Pos, u32)> {
+        self.risc_map
+            .neighbours4(pos)
+            .map(|next| (next, self.risc_map[next]))
+            .collect()
+    }
 
-fn parse(input_str: &str) -> anyhow::Result<Vec<Vec<u32>>> {
-    input_str
-        .lines()
-        .map(|line| {
-            line.trim()
-                .chars()
-                .map(|c| c.to_digit(10).context("not a valid digit"))
-                .collect::<Result<Vec<_>, _>>()
-        })
-        .collect::<Result<Vec<_>, _>>()
+    fn is_goal(&self, &pos: &Pos) -> bool {
+        pos == self.goal
+    }
+
+    // every cell risks at least 1, as `parse` makes sure of
+    fn heuristic(&self, &pos: &Pos) -> u32 {
+        (pos.0.abs_diff(self.goal.0) + pos.1.abs_diff(self.goal.1)) as u32
+    }
 }
 
-fn quintuple(input_map: Vec<Vec<u32>>) -> Vec<Vec<u32>> {
-    let max_y = input_map.len();
-    let max_x = input_map[0].len();
-
-    let mut res = vec![vec![0; max_x * 5]; max_y * 5];
-
-    for y in 0..max_y {
-        for x in 0..max_x {
-            for offset_y in 0..5 {
-                for offset_x in 0..5 {
-                    let cur_y = y + offset_y * max_y;
-                    let cur_x = x + offset_x * max_x;
-                    res[cur_y][cur_x] = input_map[y][x] + offset_y as u32 + offset_x as u32;
-                    if res[cur_y][cur_x] >= 10 {
-                        res[cur_y][cur_x] = res[cur_y][cur_x] % 10 + 1;
-                    }
-                }
+/// Same as [`lowest_total_risk`] without any search, lowering the risk of reaching every
+/// position from its neighbours until nothing changes any more.
+pub(crate) fn relaxed_total_risk(risc_map: &Grid<u32>) -> Result<u32> {
+    if risc_map.is_empty() {
+        return Err(Error::Unsolvable("Empty risk map".to_owned()));
+    }
+    let mut total = Grid::new(risc_map.width(), risc_map.height(), u32::MAX);
+    total[(0, 0)] = 0;
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (pos, &risc) in risc_map.iter() {
+            let best = risc_map
+                .neighbours4(pos)
+                .filter_map(|from| total[from].checked_add(risc))
+                .min()
+                .unwrap_or(u32::MAX);
+            if best < total[pos] {
+                total[pos] = best;
+                changed = true;
             }
         }
     }
 
-    res
+    Ok(total[(total.width() - 1, total.height() - 1)])
 }
 
-fn pprint(map: &Vec<Vec<u32>>) {
-    for y in 0..map.len() {
-        println!("{}", map[y].iter().join(""))
-    }
+/// Risk levels go from 1 to 9, which keeps the A* heuristic from overestimating.
+pub fn parse(input_str: &str) -> Result<Grid<u32>> {
+    Grid::parse_with(input_str, "risk level from 1 to 9", |c| {
+        c.to_digit(10).filter(|&risk| risk > 0)
+    })
 }
 
-fn h(pos: (i16, i16), goal: (i16, i16)) -> u32 {
-    (pos.0 - goal.0).abs() as u32 + (pos.1 - goal.1).abs() as u32
-}
-
-fn a_star2(grid: &Vec<Vec<u32>>, start: (i16, i16), goal: (i16, i16)) -> Option<u32> {
-    let start_f_score = h(start, goal);
-
-    let mut f_scores = HashMap::new();
-    f_scores.insert(start, start_f_score);
-
-    let mut g_scores = HashMap::new();
-    g_scores.insert(start, 0);
-
-    let mut open_set = std::collections::BTreeSet::new();
-    open_set.insert((start_f_score, start));
+pub(crate) fn quintuple(input_map: &Grid<u32>) -> Grid<u32> {
+    let max_x = input_map.width();
+    let max_y = input_map.height();
 
-    while let Some(&curr) = open_set.iter().next() {
-        open_set.remove(&curr);
+    let mut res = Grid::new(max_x * 5, max_y * 5, 0);
 
-        let (f_score, pos) = curr;
-        if pos == goal {
-            return Some(f_score);
-        }
-        let (y, x) = pos;
-        let g_score = g_scores[&pos];
-
-        for (dy, dx) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-            let (y, x) = (y + dy, x + dx);
-            let danger =
-                if let Some(&danger) = grid.get(y as usize).and_then(|row| row.get(x as usize)) {
-                    danger
-                } else {
-                    continue;
-                };
-            let new_pos = (y, x);
-            let tentative_g_score = g_score + danger;
-            let old_g_score = g_scores.get(&new_pos).copied();
-            if tentative_g_score < old_g_score.unwrap_or(u32::MAX) {
-                if old_g_score.is_some() {
-                    open_set.remove(&(f_scores[&new_pos], new_pos));
+    for ((x, y), &risc) in input_map.iter() {
+        for offset_y in 0..5 {
+            for offset_x in 0..5 {
+                let cur = (x + offset_x * max_x, y + offset_y * max_y);
+                res[cur] = risc + offset_y as u32 + offset_x as u32;
+                if res[cur] >= 10 {
+                    res[cur] = res[cur] % 10 + 1;
                 }
-
-                let new_f_score = tentative_g_score + h(new_pos, goal);
-                f_scores.insert(new_pos, new_f_score);
-                g_scores.insert(new_pos, tentative_g_score);
-                open_set.insert((new_f_score, new_pos));
             }
         }
     }
 
-    None
+    res
+}
+
+/// Risk map of `size` by `size`.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut risc_map = Grid::new(size.max(1), size.max(1), 1);
+    for risc in risc_map.values_mut() {
+        *risc = rng.between(1, 9) as u32;
+    }
+    risc_map.to_string()
 }
 
 #[cfg(test)]
@@ -215,9 +192,7 @@ mod tests {
 
         let risc_map = parse(input).unwrap();
 
-        let results_map = solve_01(&risc_map);
-
-        assert_eq!(results_map[risc_map.len() - 1][risc_map[0].len() - 1], 40)
+        assert_eq!(solve_part1(&risc_map).unwrap(), 40)
     }
 
     #[test]
@@ -234,9 +209,17 @@ mod tests {
         2311944581";
 
         let risc_map = parse(input).unwrap();
-        let risc_map = quintuple(risc_map);
+        assert_eq!(quintuple(&risc_map).height(), 50);
+
+        assert_eq!(solve_part2(&risc_map).unwrap(), 315)
+    }
 
-        let results_map = solve_01(&risc_map);
-        assert_eq!(results_map[risc_map.len() - 1][risc_map[0].len() - 1], 315)
+    #[test]
+    fn test_zero_risk() {
+        let input = "590005\n151501\n091190\n059990\n019500\n510190";
+        match parse(input) {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (1, 3)),
+            res => panic!("unexpected {:?}", res.map(|grid| grid.to_string())),
+        }
     }
 }
@@ -1,34 +1,78 @@
-use anyhow::Context;
+use nom::error::ErrorKind;
 use nom::IResult;
 
-pub fn part_01() -> anyhow::Result<u32> {
-    let bits = inputs()?;
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-    let (_, packet) = Packet::parse((&bits, 0)).unwrap();
+pub struct Day16;
 
-    Ok(packet.version_sum())
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    const NAME: &'static str = "Packet Decoder";
+
+    type Input = Packet;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Packet> {
+        parse(input)
+    }
+
+    fn part_01(packet: &Packet) -> Result<u32> {
+        solve_part1(packet)
+    }
+
+    fn part_02(packet: &Packet) -> Result<u64> {
+        solve_part2(packet)
+    }
+
+    const INPUT_SIZE: usize = 60;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
 }
 
-pub fn part_02() -> anyhow::Result<u64> {
-    let bits = inputs()?;
+pub fn parse(input: &str) -> Result<Packet> {
+    let bits = str_to_bit_vec(input)?;
 
-    let (_, packet) = Packet::parse((&bits, 0)).unwrap();
+    let (_, packet) = Packet::parse((&bits, 0)).map_err(|err| {
+        // report the hex digit holding the bit the parser stopped at
+        let (bit, code) = match err {
+            nom::Err::Error(err) | nom::Err::Failure(err) => {
+                ((bits.len() - err.input.0.len()) * 8 + err.input.1, err.code)
+            }
+            nom::Err::Incomplete(_) => (bits.len() * 8, ErrorKind::Eof),
+        };
+        let message = match code {
+            ErrorKind::TooLarge => "literal value does not fit in 64 bits",
+            _ => "packet ends too early",
+        };
+        let (line, column) = error::position(input, input.trim());
+        Error::Parse {
+            line,
+            column: column + bit / 4,
+            message: message.to_owned(),
+        }
+    })?;
 
-    Ok(packet.value())
+    Ok(packet)
 }
 
-fn inputs() -> anyhow::Result<Vec<u8>> {
-    let input_string =
-        std::fs::read_to_string("inputs/16_input.txt").context("Error while reading input")?;
+pub fn solve_part1(packet: &Packet) -> Result<u32> {
+    Ok(packet.version_sum())
+}
 
-    str_to_bit_vec(&input_string)
+pub fn solve_part2(packet: &Packet) -> Result<u64> {
+    packet.value()
 }
 
 #[derive(Debug)]
-struct Packet {
-    version: u8,
-    type_id: u8,
-    content: Content,
+pub struct Packet {
+    pub version: u8,
+    pub type_id: u8,
+    pub content: Content,
 }
 
 impl Packet {
@@ -45,7 +89,8 @@ impl Packet {
         Ok((input, packet))
     }
 
-    fn version_sum(&self) -> u32 {
+    /// Sum of the versions of this packet and all its sub packets.
+    pub fn version_sum(&self) -> u32 {
         match &self.content {
             Content::LiteralPacket { .. } => self.version as u32,
             Content::Operator(Operator { packets, .. }) => {
@@ -58,54 +103,50 @@ impl Packet {
         }
     }
 
-    fn value(&self) -> u64 {
-        match &self.content {
-            Content::LiteralPacket { value, .. } => *value,
-            Content::Operator(operator) => {
-                let packets = &operator.packets;
-
-                match self.type_id {
-                    SUM_TYPE_ID => packets.iter().map(|p| p.value()).sum(),
-                    PRODUCT_TYPE_ID => packets.iter().map(|p| p.value()).product(),
-                    MINIMUM_TYPE_ID => packets.iter().map(|p| p.value()).min().unwrap(),
-                    MAXIMUM_TYPE_ID => packets.iter().map(|p| p.value()).max().unwrap(),
-                    GREATER_THAN_TYPE_ID => {
-                        if packets[0].value() > packets[1].value() {
-                            1
-                        } else {
-                            0
-                        }
-                    }
-                    LESS_THAN_TYPE_ID => {
-                        if packets[0].value() < packets[1].value() {
-                            1
-                        } else {
-                            0
-                        }
-                    }
-                    EQUAL_TYPE_ID => {
-                        if packets[0].value() == packets[1].value() {
-                            1
-                        } else {
-                            0
-                        }
-                    }
-                    _ => panic!("uknown type id"),
-                }
-            }
+    /// Value of the expression the packet encodes.
+    pub fn value(&self) -> Result<u64> {
+        let packets = match &self.content {
+            Content::LiteralPacket { value, .. } => return Ok(*value),
+            Content::Operator(operator) => &operator.packets,
+        };
+        let values = packets
+            .iter()
+            .map(|p| p.value())
+            .collect::<Result<Vec<_>>>()?;
+        let overflow = || Error::Overflow(format!("value of packet with type id {}", self.type_id));
+
+        match (self.type_id, &values[..]) {
+            (SUM_TYPE_ID, _) => values
+                .iter()
+                .try_fold(0u64, |acc, &v| acc.checked_add(v))
+                .ok_or_else(overflow),
+            (PRODUCT_TYPE_ID, _) => values
+                .iter()
+                .try_fold(1u64, |acc, &v| acc.checked_mul(v))
+                .ok_or_else(overflow),
+            (MINIMUM_TYPE_ID, [_, ..]) => Ok(values.iter().copied().min().unwrap_or_default()),
+            (MAXIMUM_TYPE_ID, [_, ..]) => Ok(values.iter().copied().max().unwrap_or_default()),
+            (GREATER_THAN_TYPE_ID, [a, b]) => Ok((a > b) as u64),
+            (LESS_THAN_TYPE_ID, [a, b]) => Ok((a < b) as u64),
+            (EQUAL_TYPE_ID, [a, b]) => Ok((a == b) as u64),
+            (type_id, _) => Err(Error::Unsolvable(format!(
+                "operator with type id {} can't take {} sub packets",
+                type_id,
+                values.len()
+            ))),
         }
     }
 
-    fn bit_length(&self) -> usize {
+    pub fn bit_length(&self) -> usize {
         self.content.bit_length() + 3 + 3
     }
 }
 
 #[derive(Debug)]
-struct Operator {
-    length_type_id: u8,
-    bit_length: usize,
-    packets: Vec<Packet>,
+pub struct Operator {
+    pub length_type_id: u8,
+    pub bit_length: usize,
+    pub packets: Vec<Packet>,
 }
 
 impl Operator {
@@ -126,14 +167,14 @@ impl Operator {
                 packets.push(sub_packet);
             }
 
-            return Ok((
+            Ok((
                 input,
                 Operator {
                     length_type_id,
                     bit_length,
                     packets,
                 },
-            ));
+            ))
         } else {
             let (mut input, number_of_sub_packets): (_, usize) =
                 nom::bits::complete::take(11usize)(input)?;
@@ -146,14 +187,14 @@ impl Operator {
                 packets.push(sub_packet);
             }
 
-            return Ok((
+            Ok((
                 input,
                 Operator {
                     length_type_id,
                     bit_length,
                     packets,
                 },
-            ));
+            ))
         }
     }
 }
@@ -173,6 +214,8 @@ const GREATER_THAN_TYPE_ID: u8 = 5;
 const LESS_THAN_TYPE_ID: u8 = 6;
 const EQUAL_TYPE_ID: u8 = 7;
 
+const LITERAL_MAX_GROUPS: usize = 16;
+
 impl Content {
     fn parse(type_id: u8, input: (&[u8], usize)) -> IResult<(&[u8], usize), Content> {
         if type_id == LITERAL_TYPE_ID {
@@ -180,6 +223,11 @@ impl Content {
             let mut bit_length = 0;
             let mut cur_input = input;
             loop {
+                // 16 groups of 4 bits fill the u64, a 17th would shift its high bits out
+                if bit_length == LITERAL_MAX_GROUPS * 5 {
+                    let err = nom::error::Error::new(cur_input, ErrorKind::TooLarge);
+                    return Err(nom::Err::Failure(err));
+                }
                 let (input, group): (_, u64) = nom::bits::complete::take(5usize)(cur_input)?;
                 cur_input = input;
                 bit_length += 5;
@@ -191,11 +239,11 @@ impl Content {
             }
         } else {
             let (input, operator) = Operator::parse(input)?;
-            return Ok((input, Content::Operator(operator)));
+            Ok((input, Content::Operator(operator)))
         }
     }
 
-    fn bit_length(&self) -> usize {
+    pub fn bit_length(&self) -> usize {
         match &self {
             Self::LiteralPacket { bit_length, .. } => *bit_length,
             Self::Operator(op) => op.bit_length,
@@ -203,8 +251,97 @@ impl Content {
     }
 }
 
-fn str_to_bit_vec(hex_str: &str) -> anyhow::Result<Vec<u8>> {
-    hex::decode(hex_str).context("Bad input hex_string")
+fn str_to_bit_vec(input: &str) -> Result<Vec<u8>> {
+    let hex_str = input.trim();
+    hex::decode(hex_str).map_err(|err| match err {
+        hex::FromHexError::InvalidHexCharacter { index, .. } => Error::parse_at(
+            input,
+            hex_str.get(index..).unwrap_or(hex_str),
+            err.to_string(),
+        ),
+        _ => Error::parse_at(input, &hex_str[hex_str.len()..], err.to_string()),
+    })
+}
+
+/// Transmission of about `size` packets, with every packet's value fitting in 32 bits.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    fn push(bits: &mut Vec<bool>, value: u64, width: usize) {
+        bits.extend((0..width).rev().map(|ix| value >> ix & 1 == 1));
+    }
+
+    fn packet(rng: &mut Rng, budget: &mut usize, depth: usize) -> (Vec<bool>, u64) {
+        let mut bits = vec![];
+        push(&mut bits, rng.below(8) as u64, 3);
+        *budget = budget.saturating_sub(1);
+
+        if *budget == 0 || depth >= 8 || rng.one_in(3) {
+            let value = rng.below(1 << 12) as u64;
+            push(&mut bits, LITERAL_TYPE_ID as u64, 3);
+            for group in (0..3).rev() {
+                push(&mut bits, (group > 0) as u64, 1);
+                push(&mut bits, value >> (4 * group) & 0xf, 4);
+            }
+            return (bits, value);
+        }
+
+        let type_id = *rng.choose(&[0, 1, 2, 3, 5, 6, 7]);
+        let count = match type_id {
+            GREATER_THAN_TYPE_ID | LESS_THAN_TYPE_ID | EQUAL_TYPE_ID => 2,
+            _ => rng.below(4) + 1,
+        };
+        let (sub_bits, values): (Vec<_>, Vec<_>) =
+            (0..count).map(|_| packet(rng, budget, depth + 1)).unzip();
+
+        // fall back on the minimum when the value grows too big
+        let value = match type_id {
+            SUM_TYPE_ID => values.iter().sum(),
+            PRODUCT_TYPE_ID => values
+                .iter()
+                .try_fold(1u64, |acc, &v| acc.checked_mul(v))
+                .unwrap_or(u64::MAX),
+            MAXIMUM_TYPE_ID => values.iter().copied().max().unwrap_or_default(),
+            GREATER_THAN_TYPE_ID => (values[0] > values[1]) as u64,
+            LESS_THAN_TYPE_ID => (values[0] < values[1]) as u64,
+            EQUAL_TYPE_ID => (values[0] == values[1]) as u64,
+            // MINIMUM_TYPE_ID
+            _ => values.iter().copied().min().unwrap_or_default(),
+        };
+        let (type_id, value) = if value > u32::MAX as u64 {
+            (
+                MINIMUM_TYPE_ID,
+                values.iter().copied().min().unwrap_or_default(),
+            )
+        } else {
+            (type_id, value)
+        };
+
+        push(&mut bits, type_id as u64, 3);
+        let sub_bits = sub_bits.concat();
+        if rng.one_in(2) && sub_bits.len() < 1 << 15 {
+            push(&mut bits, 0, 1);
+            push(&mut bits, sub_bits.len() as u64, 15);
+        } else {
+            push(&mut bits, 1, 1);
+            push(&mut bits, count as u64, 11);
+        }
+        bits.extend(sub_bits);
+        (bits, value)
+    }
+
+    let mut budget = size.max(1);
+    let (mut bits, _) = packet(rng, &mut budget, 0);
+    // whole bytes, as the transmission is decoded byte by byte
+    while bits.len() % 8 != 0 {
+        bits.push(false);
+    }
+    bits.chunks(4)
+        .map(|nibble| {
+            format!(
+                "{:X}",
+                nibble.iter().fold(0, |acc, &bit| acc << 1 | bit as u8)
+            )
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -234,4 +371,48 @@ mod tests {
         let (_, packet) = Packet::parse((&bits, 0)).unwrap();
         assert_eq!(packet.version_sum(), 31);
     }
+
+    #[test]
+    fn test_errors() {
+        let err = parse("D2FEx8").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 1,
+                column: 5,
+                ..
+            }
+        ));
+
+        // the literal group starting at bit 16 (5th hex digit) is cut off
+        let err = parse("D2FE").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 1,
+                column: 5,
+                ..
+            }
+        ));
+
+        // 17 groups of a literal, the first one holding high bits a u64 can't keep
+        let err = parse("13FFFFFFFFFFFFFFFFFFFDE0").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 1,
+                column: 22,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("does not fit in 64 bits"));
+
+        // 16 groups fit
+        let packet = parse("13FFFFFFFFFFFFFFFFFFBC").unwrap();
+        assert_eq!(packet.value().unwrap(), u64::MAX);
+
+        // minimum of no sub packets
+        let packet = parse("0A0000").unwrap();
+        assert!(matches!(packet.value(), Err(Error::Unsolvable(_))));
+    }
 }
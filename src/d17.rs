@@ -1,6 +1,79 @@
-pub fn part_01() -> anyhow::Result<i32> {
-    let y1: i32 = -129;
-    let _y2: i32 = -70;
+use crate::error::{self, Error, Result};
+use crate::geom::{Point2, Rect};
+use crate::rng::Rng;
+use crate::solution::Solution;
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    const NAME: &'static str = "Trick Shot";
+
+    type Input = Rect;
+    type Answer1 = i32;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Rect> {
+        parse(input)
+    }
+
+    fn part_01(target: &Rect) -> Result<i32> {
+        solve_part1(target)
+    }
+
+    fn part_02(target: &Rect) -> Result<usize> {
+        solve_part2(target)
+    }
+
+    const INPUT_SIZE: usize = 150;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn solve_part1(target: &Rect) -> Result<i32> {
+    check_target(target)?;
+    solve_01(target)
+}
+
+pub fn solve_part2(target: &Rect) -> Result<usize> {
+    check_target(target)?;
+    check_search(target)?;
+    Ok(solve_02(target))
+}
+
+/// Most probe steps part 2 may simulate, the real inputs taking about ten million.
+const MAX_SEARCH_STEPS: u64 = 1_000_000_000;
+
+/// Part 2 tries every launch up to the target's extents, each flying for up to twice the depth
+/// of the target, which gets out of hand for targets far from the launcher.
+fn check_search(target: &Rect) -> Result<()> {
+    let x2 = target.max.x.unsigned_abs() as u64;
+    let depth = 2 * target.min.y.unsigned_abs() as u64 + 1;
+    let steps = x2.saturating_mul(depth).saturating_mul(depth);
+    if steps > MAX_SEARCH_STEPS {
+        return Err(Error::Overflow(format!(
+            "searching the launches at target area x={}..{}, y={}..{} takes more than {} steps",
+            target.min.x, target.max.x, target.min.y, target.max.y, MAX_SEARCH_STEPS
+        )));
+    }
+    Ok(())
+}
+
+/// Both solvers rely on the target being to the right of and below the launcher.
+fn check_target(target: &Rect) -> Result<()> {
+    if target.min.x <= 0 || target.max.y >= 0 {
+        return Err(Error::Unsolvable(format!(
+            "target area x={}..{}, y={}..{} is not below and to the right of the launcher",
+            target.min.x, target.max.x, target.min.y, target.max.y
+        )));
+    }
+    Ok(())
+}
+
+fn solve_01(target: &Rect) -> Result<i32> {
+    let y1 = target.min.y as i64;
 
     // if Y speed is -128 by the time Y position is 0 again (on the way down), probe will reach highest possible Y.
     // reason is the fact that that is the highest possible Y speed that actually hists the rectangle, with the way it
@@ -16,78 +89,78 @@ pub fn part_01() -> anyhow::Result<i32> {
 
     // This is just simple triangular number series. 1..128
 
-    let y_speed_at_zero = y1.abs() - 1;
-    Ok(y_speed_at_zero * (y_speed_at_zero + 1) / 2)
+    // in i64, where even the triangular number of the lowest i32 fits
+    let y_speed_at_zero = -y1 - 1;
+    let height = y_speed_at_zero * (y_speed_at_zero + 1) / 2;
+    i32::try_from(height).map_err(|_| {
+        Error::Overflow(format!(
+            "highest position {} does not fit in 32 bits",
+            height
+        ))
+    })
 }
 
-pub fn part_02() -> anyhow::Result<usize> {
-    let xs = (150, 171);
-    let ys = (-129i32, -70i32);
-
-    let (_, x2) = xs;
-    let (y1, _) = ys;
-
-    let mut results = vec![];
-
-    for vx in 1..=x2 {
-        // our X speed cannot be over x2, since we would imediatly step over
-        for vy in y1..=y1.abs() {
-            // Y speed cannot be over y1, since we would imediatly step over
+fn solve_02(target: &Rect) -> usize {
+    let x2 = target.max.x as i64;
+    let y1 = target.min.y as i64;
 
-            let mut current_pos = (0, 0);
-            let mut current_v = (vx, vy);
+    // our X speed cannot be over x2, since we would imediatly step over
+    // Y speed cannot be over y1, since we would imediatly step over
+    (1..=x2)
+        .flat_map(|vx| (y1..=-y1).map(move |vy| (vx, vy)))
+        .filter(|&(vx, vy)| hits(target, vx, vy))
+        .count()
+}
 
-            while should_we_continue(current_pos, ys) {
-                if is_inside(current_pos, xs, ys) {
-                    results.push((vx, vy));
-                    break;
-                }
-                let (new_pos, new_v) = step(current_pos, current_v);
-                current_pos = new_pos;
-                current_v = new_v;
-            }
+/// Whether the probe launched with these speeds is ever inside the target. Positions and speeds
+/// are i64, which the farthest shots at any i32 target still fit in.
+fn hits(target: &Rect, mut vx: i64, mut vy: i64) -> bool {
+    let (x1, x2) = (target.min.x as i64, target.max.x as i64);
+    let (y1, y2) = (target.min.y as i64, target.max.y as i64);
+    let (mut x, mut y) = (0, 0);
+
+    // once below the target, the probe never goes up again
+    while y >= y1 {
+        if (x1..=x2).contains(&x) && (y1..=y2).contains(&y) {
+            return true;
         }
+        x += vx;
+        y += vy;
+        vx -= vx.signum();
+        vy -= 1;
     }
-
-    Ok(results.len())
+    false
 }
 
-fn should_we_continue(current_pos: (i32, i32), ys: (i32, i32)) -> bool {
-    let (_, y) = current_pos;
-    // we missed the mark on Y axis
-    if y < ys.0 {
-        // we will never go up again
-        return false;
+/// `target area: x=150..171, y=-129..-70`, read piece by piece so that errors name the expected
+/// piece where it is missing.
+pub fn parse(s: &str) -> Result<Rect> {
+    let mut rest = s.trim();
+    let mut bounds = [0; 4];
+    for (bound, prefix) in bounds
+        .iter_mut()
+        .zip(["target area: x=", "..", ", y=", ".."])
+    {
+        rest = error::strip_prefix(s, rest, prefix)?;
+        (*bound, rest) = error::leading_number(s, rest, "coordinate")?;
     }
-    true
-}
-
-fn step(current_pos: (i32, i32), current_v: (i32, i32)) -> ((i32, i32), (i32, i32)) {
-    let (vx, vy) = current_v;
-    let new_pos = (current_pos.0 + vx, current_pos.1 + vy);
+    error::end_of_line(s, rest)?;
 
-    let new_vx = if vx == 0 {
-        0
-    } else if vx < 0 {
-        vx + 1
-    } else {
-        vx - 1
-    };
-
-    let new_vy = vy - 1;
-
-    (new_pos, (new_vx, new_vy))
+    let [x1, x2, y1, y2] = bounds;
+    Ok(Rect::new(Point2::new(x1, y1), Point2::new(x2, y2)))
 }
 
-fn is_inside(current_pos: (i32, i32), xs: (i32, i32), ys: (i32, i32)) -> bool {
-    if current_pos.0 >= xs.0
-        && current_pos.0 <= xs.1
-        && current_pos.1 >= ys.0
-        && current_pos.1 <= ys.1
-    {
-        return true;
-    }
-    false
+/// Target area about `size` to the right of and below the launcher, wide enough for the probe
+/// to drop into it vertically.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(10) as i64;
+    let x1 = rng.between(size / 2, size);
+    // some triangular number x(x+1)/2 falls between x1 and x2
+    let x2 = x1 + (2.0 * x1 as f64).sqrt() as i64 + rng.between(1, 10);
+    let y1 = -rng.between(size / 2, size);
+    let y2 = (y1 + rng.between(5, size / 2)).min(-1);
+
+    format!("target area: x={}..{}, y={}..{}", x1, x2, y1, y2)
 }
 
 #[cfg(test)]
@@ -96,8 +169,69 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_is_inside() {
-        assert_eq!(should_we_continue((29, -5), (-10, -5)), true);
-        assert_eq!(is_inside((29, -5), (20, 30), (-10, -5)), true);
+    fn test_parse() {
+        let target = parse("target area: x=20..30, y=-10..-5").unwrap();
+        assert_eq!(target, Rect::new(Point2::new(20, -10), Point2::new(30, -5)));
+
+        assert_eq!(solve_01(&target).unwrap(), 45);
+        assert_eq!(solve_02(&target), 112);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let located = |input: &str| match parse(input) {
+            Err(Error::Parse {
+                column, message, ..
+            }) => (column, message),
+            res => panic!("unexpected {:?}", res),
+        };
+        assert_eq!(
+            located("target area: x=20..30, y=-10..x5"),
+            (31, "expected a digit, found `x`".to_owned())
+        );
+        assert_eq!(
+            located("target area: x=20..30 y=-10..-5"),
+            (22, "expected `, y=`, found ` `".to_owned())
+        );
+        assert_eq!(
+            located("target: x=20..30, y=-10..-5"),
+            (1, "expected `target area: x=`, found `t`".to_owned())
+        );
+        assert_eq!(located("target area: x=20..30, y=-10..-5, z=1").0, 33);
+    }
+
+    #[test]
+    fn test_hits() {
+        let target = Rect::new(Point2::new(20, -10), Point2::new(30, -5));
+        assert!(hits(&target, 7, 2));
+        assert!(hits(&target, 6, 9));
+        assert!(hits(&target, 30, -5));
+        assert!(!hits(&target, 17, -4));
+    }
+
+    #[test]
+    fn test_overflow() {
+        for input in [
+            "target area: x=20..30, y=-100000..-5",
+            "target area: x=20..30, y=-2147483648..-5",
+        ] {
+            match solve_part1(&parse(input).unwrap()) {
+                Err(Error::Overflow(_)) => {}
+                res => panic!("unexpected {:?} for {}", res, input),
+            }
+        }
+        let target = parse("target area: x=20..30, y=-65535..-5").unwrap();
+        assert_eq!(solve_part1(&target).unwrap(), 2147385345);
+
+        for input in [
+            "target area: x=20..30, y=-65535..-5",
+            "target area: x=20..30, y=-2147483648..-5",
+            "target area: x=20..2147483647, y=-10..-5",
+        ] {
+            match solve_part2(&parse(input).unwrap()) {
+                Err(Error::Overflow(_)) => {}
+                res => panic!("unexpected {:?} for {}", res, input),
+            }
+        }
     }
 }
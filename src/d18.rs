@@ -1,18 +1,77 @@
+use std::collections::VecDeque;
 use std::fmt::Display;
+use std::ops::Add;
+use std::str::FromStr;
 
 use itertools::Itertools;
 
-use anyhow::Context;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete;
-use nom::combinator::map;
+use nom::combinator::{all_consuming, map};
+use nom::error::{context, VerboseError, VerboseErrorKind};
 use nom::sequence::delimited;
 use nom::sequence::separated_pair;
 use nom::IResult;
 
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
+use crate::step::Simulation;
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    const NAME: &'static str = "Snailfish";
+
+    type Input = Vec<Snailfish>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<Snailfish>> {
+        parse(input)
+    }
+
+    fn part_01(fishes: &Vec<Snailfish>) -> Result<u64> {
+        solve_part1(fishes)
+    }
+
+    fn part_02(fishes: &Vec<Snailfish>) -> Result<u64> {
+        solve_part2(fishes)
+    }
+
+    const INPUT_SIZE: usize = 100;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<Snailfish>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(ix, line)| line.parse().map_err(|err: Error| err.at_line(ix + 1)))
+        .collect()
+}
+
+pub fn solve_part1(fishes: &[Snailfish]) -> Result<u64> {
+    if fishes.is_empty() {
+        return Err(Error::Unsolvable("No snailfish numbers to add".to_owned()));
+    }
+    Ok(solve_01(fishes.to_vec()).magnitude())
+}
+
+pub fn solve_part2(fishes: &[Snailfish]) -> Result<u64> {
+    let max = solve_02(fishes.to_vec())
+        .ok_or_else(|| Error::Unsolvable("Need at least two snailfish numbers".to_owned()))?;
+
+    Ok(max)
+}
+
 #[derive(Clone, Debug)]
-enum Snailfish {
+pub enum Snailfish {
     Number(u32),
     Pair(Box<Snailfish>, Box<Snailfish>),
 }
@@ -25,31 +84,27 @@ impl Snailfish {
         )
     }
 
-    fn from_str(line: &str) -> Self {
-        let (_, fish) = parse_snailfish(line).expect("Bad Input");
-        fish
-    }
-
-    fn magnitude(&self) -> u64 {
+    pub fn magnitude(&self) -> u64 {
         match self {
             Snailfish::Number(val) => *val as u64,
             Snailfish::Pair(s1, s2) => 3 * s1.magnitude() + 2 * s2.magnitude(),
         }
     }
 
-    fn reduce(&mut self) {
-        loop {
-            let exploded = self.explode();
-            if exploded {
-                continue;
-            }
-
-            let splited = self.split();
-            if splited {
-                continue;
-            }
+    /// Explodes and splits until no more action applies.
+    pub fn reduce(&mut self) {
+        while self.reduce_step().is_some() {}
+    }
 
-            break;
+    /// Applies the first action of the reduction, returning which one or `None` when the
+    /// number is already reduced.
+    pub fn reduce_step(&mut self) -> Option<&'static str> {
+        if self.explode() {
+            Some("explode")
+        } else if self.split() {
+            Some("split")
+        } else {
+            None
         }
     }
 
@@ -58,26 +113,26 @@ impl Snailfish {
             Snailfish::Pair(s1, s2) if depth == 4 => match (*s1.clone(), *s2.clone()) {
                 (Snailfish::Number(v1), Snailfish::Number(v2)) => {
                     *self = Snailfish::Number(0);
-                    return (true, index, v1, v2);
+                    (true, index, v1, v2)
                 }
                 (Snailfish::Number(_), Snailfish::Pair(mut s1, mut s2)) => {
                     let new_index = index + 1; // left one is plain number, and should be indexed
                     let (_, new_ix, _, _) = s1.find_and_explode_index(new_index, depth + 1);
                     let (_, new_ix, _, _) = s2.find_and_explode_index(new_ix, depth + 1);
-                    return (false, new_ix, 0, 0);
+                    (false, new_ix, 0, 0)
                 }
                 (Snailfish::Pair(mut s1, mut s2), Snailfish::Number(_)) => {
                     let (_, new_ix, _, _) = s1.find_and_explode_index(index, depth + 1);
                     let (_, new_ix, _, _) = s2.find_and_explode_index(new_ix, depth + 1);
                     let new_ix = new_ix + 1; // right one is plain number, and should be indexed
-                    return (false, new_ix + 1, 0, 0);
+                    (false, new_ix + 1, 0, 0)
                 }
                 (Snailfish::Pair(mut s1, mut s2), Snailfish::Pair(mut s3, mut s4)) => {
                     let (_, new_ix, _, _) = s1.find_and_explode_index(index, depth + 1);
                     let (_, new_ix, _, _) = s2.find_and_explode_index(new_ix, depth + 1);
                     let (_, new_ix, _, _) = s3.find_and_explode_index(new_ix, depth + 1);
                     let (_, new_ix, _, _) = s4.find_and_explode_index(new_ix, depth + 1);
-                    return (false, new_ix, 0, 0);
+                    (false, new_ix, 0, 0)
                 }
             },
             Snailfish::Pair(s1, s2) => {
@@ -88,7 +143,7 @@ impl Snailfish {
 
                 s2.find_and_explode_index(new_ix, depth + 1)
             }
-            Snailfish::Number(_) => return (false, index + 1, 0, 0),
+            Snailfish::Number(_) => (false, index + 1, 0, 0),
         }
     }
 
@@ -102,19 +157,19 @@ impl Snailfish {
         match self {
             Snailfish::Number(v) if cur_ix + 1 == explosion_ix => {
                 *v += left;
-                return (false, cur_ix + 1);
+                (false, cur_ix + 1)
             }
             Snailfish::Number(v) if cur_ix == explosion_ix + 1 => {
                 *v += right;
-                return (true, cur_ix + 1);
+                (true, cur_ix + 1)
             }
-            Snailfish::Number(_) => return (false, cur_ix + 1),
+            Snailfish::Number(_) => (false, cur_ix + 1),
             Snailfish::Pair(s1, s2) => {
                 let (finished, new_ix) = s1.explode_around_index(cur_ix, explosion_ix, left, right);
                 if finished {
                     return (finished, new_ix);
                 }
-                return s2.explode_around_index(new_ix, explosion_ix, left, right);
+                s2.explode_around_index(new_ix, explosion_ix, left, right)
             }
         }
     }
@@ -125,7 +180,7 @@ impl Snailfish {
             return false;
         }
         self.explode_around_index(0, ix, l, r);
-        return true;
+        true
     }
 
     fn split(&mut self) -> bool {
@@ -138,7 +193,7 @@ impl Snailfish {
             }
             Snailfish::Number(_) => false,
             Snailfish::Pair(s1, s2) => {
-                if s1.split() == true {
+                if s1.split() {
                     true
                 } else {
                     s2.split()
@@ -146,9 +201,44 @@ impl Snailfish {
             }
         }
     }
+}
+
+/// Snailfish addition: the pair of both numbers, reduced.
+impl Add for Snailfish {
+    type Output = Snailfish;
 
     fn add(self, other: Snailfish) -> Snailfish {
-        Snailfish::Pair(Box::new(self), Box::new(other))
+        let mut sum = Snailfish::Pair(Box::new(self), Box::new(other));
+        sum.reduce();
+        sum
+    }
+}
+
+impl FromStr for Snailfish {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Snailfish> {
+        let (_, fish) = all_consuming(parse_snailfish)(line.trim()).map_err(|err| {
+            let errors = match err {
+                nom::Err::Error(err) | nom::Err::Failure(err) => err.errors,
+                nom::Err::Incomplete(_) => vec![],
+            };
+            // the innermost expectation, anything left after a whole number being unexpected
+            let (rest, expected) = errors
+                .iter()
+                .find_map(|(rest, kind)| match kind {
+                    VerboseErrorKind::Context(expected) => Some((*rest, *expected)),
+                    _ => None,
+                })
+                .or_else(|| {
+                    errors
+                        .first()
+                        .map(|(rest, _)| (*rest, "the end of the line"))
+                })
+                .unwrap_or((&line[line.len()..], "a snailfish number"));
+            error::expected(line, rest, &format!("{} in snailfish number", expected))
+        })?;
+        Ok(fish)
     }
 }
 
@@ -161,26 +251,66 @@ impl Display for Snailfish {
     }
 }
 
-fn parse_snailfish(line: &str) -> IResult<&str, Snailfish> {
-    let parse_number_fish = map(complete::u32, |num| Snailfish::Number(num));
+/// The sum of part 1 worked out one action at a time: adding the next number, then exploding
+/// and splitting the sum until it is reduced.
+#[derive(Clone, Debug)]
+pub struct Homework {
+    sum: Option<Snailfish>,
+    numbers: VecDeque<Snailfish>,
+}
+
+impl Homework {
+    pub fn new(numbers: Vec<Snailfish>) -> Homework {
+        Homework {
+            sum: None,
+            numbers: numbers.into(),
+        }
+    }
+}
+
+impl Display for Homework {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.sum {
+            Some(sum) => writeln!(f, "sum {} of magnitude {}", sum, sum.magnitude())?,
+            None => writeln!(f, "nothing added yet")?,
+        }
+        write!(f, "{} numbers left", self.numbers.len())
+    }
+}
+
+impl Simulation for Homework {
+    fn step(&mut self) -> Option<String> {
+        if let Some(action) = self.sum.as_mut().and_then(Snailfish::reduce_step) {
+            return Some(action.to_owned());
+        }
 
-    alt((parse_number_fish, parse_pair_fish))(line)
+        let next = self.numbers.pop_front()?;
+        let event = format!("add {}", next);
+        self.sum = Some(match self.sum.take() {
+            Some(sum) => Snailfish::Pair(Box::new(sum), Box::new(next)),
+            None => next,
+        });
+        Some(event)
+    }
 }
 
-fn parse_pair_fish(line: &str) -> IResult<&str, Snailfish> {
-    let parse_pair = separated_pair(parse_snailfish, tag(","), parse_snailfish);
+/// Parser result, with the expected token of every failure as context.
+type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
 
-    let map_and_parse_pair = map(parse_pair, |(s1, s2)| {
-        Snailfish::Pair(Box::new(s1), Box::new(s2))
-    });
+fn parse_snailfish(line: &str) -> ParseResult<'_, Snailfish> {
+    let parse_number_fish = map(complete::u32, Snailfish::Number);
 
-    delimited(tag("["), map_and_parse_pair, tag("]"))(line)
+    context("a number or `[`", alt((parse_number_fish, parse_pair_fish)))(line)
 }
 
-pub fn part_01() -> anyhow::Result<u64> {
-    let fishes = inputs()?;
+fn parse_pair_fish(line: &str) -> ParseResult<'_, Snailfish> {
+    let parse_pair = separated_pair(parse_snailfish, context("`,`", tag(",")), parse_snailfish);
+
+    let map_and_parse_pair = map(parse_pair, |(s1, s2)| {
+        Snailfish::Pair(Box::new(s1), Box::new(s2))
+    });
 
-    Ok(solve_01(fishes).magnitude())
+    delimited(tag("["), map_and_parse_pair, context("`]`", tag("]")))(line)
 }
 
 fn solve_01(mut fishes: Vec<Snailfish>) -> Snailfish {
@@ -188,43 +318,40 @@ fn solve_01(mut fishes: Vec<Snailfish>) -> Snailfish {
     res.reduce();
 
     for other in fishes {
-        res = res.add(other);
-        res.reduce();
+        res = res + other;
     }
 
     res
 }
 
-pub fn part_02() -> anyhow::Result<u64> {
-    let fishes = inputs()?;
-
-    let max = solve_02(fishes);
-
-    Ok(max) // 14429 too high
-}
-
-fn solve_02(fishes: Vec<Snailfish>) -> u64 {
+fn solve_02(fishes: Vec<Snailfish>) -> Option<u64> {
     fishes
         .into_iter()
         .permutations(2)
         .map(|mut perm| {
-            let mut added_fishes = perm.remove(1).add(perm.remove(0));
-            added_fishes.reduce();
+            let added_fishes = perm.remove(1) + perm.remove(0);
             added_fishes.magnitude()
         })
         .max()
-        .unwrap()
 }
 
-fn inputs() -> anyhow::Result<Vec<Snailfish>> {
-    let input_string =
-        std::fs::read_to_string("inputs/18_input.txt").context("Error while reading input")?;
-
-    input_string.lines().map(read_line).collect()
-}
+/// `size` reduced snailfish numbers.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    fn number(rng: &mut Rng, depth: usize) -> Snailfish {
+        if depth == 4 || (depth > 0 && rng.one_in(3)) {
+            Snailfish::Number(rng.below(10) as u32)
+        } else {
+            Snailfish::Pair(
+                Box::new(number(rng, depth + 1)),
+                Box::new(number(rng, depth + 1)),
+            )
+        }
+    }
 
-fn read_line(line: &str) -> anyhow::Result<Snailfish> {
-    Ok(Snailfish::from_str(line))
+    (0..size.max(2))
+        .map(|_| number(rng, 0).to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[cfg(test)]
@@ -247,7 +374,7 @@ mod tests {
         ];
 
         for (fish_str, result) in inputs {
-            let fish = Snailfish::from_str(fish_str);
+            let fish = Snailfish::from_str(fish_str).unwrap();
             assert_eq!(fish.magnitude(), result);
         }
     }
@@ -269,14 +396,25 @@ mod tests {
         ];
 
         for (before_explosion, after_explosion) in inputs {
-            let mut s = Snailfish::from_str(before_explosion);
+            let mut s = Snailfish::from_str(before_explosion).unwrap();
             let exploded = s.explode();
-            assert_eq!(exploded, true);
+            assert!(exploded);
             let s_displayed = format!("{}", s);
             assert_eq!(&s_displayed, after_explosion);
         }
     }
 
+    #[test]
+    fn test_add() {
+        let a = Snailfish::from_str("[[[[4,3],4],4],[7,[[8,4],9]]]").unwrap();
+        let b = Snailfish::from_str("[1,1]").unwrap();
+
+        assert_eq!((a + b).to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+
+        assert!(Snailfish::from_str("[1,2").is_err());
+        assert!(Snailfish::from_str("[1,2]]").is_err());
+    }
+
     #[test]
     fn test_splits() {
         let inputs = [
@@ -291,9 +429,9 @@ mod tests {
         ];
 
         for (before, after) in inputs {
-            let mut s = Snailfish::from_str(before);
+            let mut s = Snailfish::from_str(before).unwrap();
             let split = s.split();
-            assert_eq!(split, true);
+            assert!(split);
             let s_displayed = format!("{}", s);
             assert_eq!(&s_displayed, after);
         }
@@ -316,7 +454,7 @@ mod tests {
 
         let inputs = inputs
             .into_iter()
-            .map(Snailfish::from_str)
+            .map(|line| Snailfish::from_str(line).unwrap())
             .collect::<Vec<_>>();
 
         let res = solve_01(inputs);
@@ -341,11 +479,47 @@ mod tests {
 
         let inputs = inputs
             .into_iter()
-            .map(Snailfish::from_str)
+            .map(|line| Snailfish::from_str(line).unwrap())
             .collect::<Vec<_>>();
 
         let res = solve_02(inputs);
 
-        assert_eq!(res, 3993);
+        assert_eq!(res, Some(3993));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse("[1,2]\n[[1,2],x]").unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 8,
+                ..
+            }
+        ));
+
+        let located = |line: &str| match line.parse::<Snailfish>() {
+            Err(Error::Parse {
+                column, message, ..
+            }) => (column, message),
+            res => panic!("unexpected {:?}", res),
+        };
+        assert_eq!(
+            located("[[1,2],x]"),
+            (
+                8,
+                "expected a number or `[` in snailfish number, found `x`".to_owned()
+            )
+        );
+        assert_eq!(located("[[1;2],3]").0, 4);
+        assert!(located("[[1;2],3]").1.starts_with("expected `,`"));
+        assert!(located("[1,[2,3]").1.starts_with("expected `]`"));
+        assert_eq!(located("[1,[2,3]").0, 9);
+        assert!(located("[1,2]]")
+            .1
+            .starts_with("expected the end of the line"));
+        assert_eq!(located("[1,2]]").0, 6);
     }
 }
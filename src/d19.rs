@@ -1,32 +1,72 @@
-use anyhow::Context;
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
-pub fn part_01() -> anyhow::Result<usize> {
-    let inputs = inputs()?;
-    let (set, _) = solve(inputs);
+use crate::error::{self, Error, Result};
+use crate::geom::{Cuboid, Point3, Rotation, Vec3};
+use crate::rng::Rng;
+use crate::solution::Solution;
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    const NAME: &'static str = "Beacon Scanner";
+
+    type Input = Vec<Sonar>;
+    type Answer1 = usize;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Vec<Sonar>> {
+        parse(input)
+    }
+
+    fn part_01(inputs: &Vec<Sonar>) -> Result<usize> {
+        solve_part1(inputs)
+    }
+
+    fn part_02(inputs: &Vec<Sonar>) -> Result<u32> {
+        solve_part2(inputs)
+    }
+
+    const INPUT_SIZE: usize = 35;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<Sonar>> {
+    input
+        .split("\n\n")
+        .map(|s| parse_one(s).map_err(|err| err.at_line(error::position(input, s).0)))
+        .collect()
+}
+
+pub fn solve_part1(inputs: &[Sonar]) -> Result<usize> {
+    let (set, _) = solve(inputs.to_vec())?;
 
     Ok(set.len())
 }
 
-pub fn part_02() -> anyhow::Result<i32> {
-    let inputs = inputs()?;
-    let (_, positions) = solve(inputs);
+pub fn solve_part2(inputs: &[Sonar]) -> Result<u32> {
+    let (_, positions) = solve(inputs.to_vec())?;
 
-    let res = positions
+    positions
         .into_iter()
         .permutations(2)
-        .map(|perm| manhatten_distance(&perm[0], &perm[1]))
-        .max();
-
-    Ok(res.unwrap())
+        .map(|perm| perm[0].manhattan(perm[1]))
+        .max()
+        .ok_or_else(|| Error::Unsolvable("Need at least two scanners".to_owned()))
 }
 
-fn solve(mut inputs: Vec<Sonar>) -> (Sonar, Vec<Coord>) {
+fn solve(mut inputs: Vec<Sonar>) -> Result<(Sonar, Vec<Point3>)> {
+    if inputs.is_empty() {
+        return Err(Error::Unsolvable("No scanners".to_owned()));
+    }
     let mut set = inputs.remove(0);
-    let mut positions = vec![(0, 0, 0)];
+    let mut positions = vec![Point3::ORIGIN];
 
-    'outer: while inputs.len() > 0 {
+    'outer: while !inputs.is_empty() {
         for (ix, input) in inputs.iter().enumerate() {
             if let Some((new_set, position, _)) = try_fit_par(&set, input, 12) {
                 set = new_set;
@@ -35,44 +75,47 @@ fn solve(mut inputs: Vec<Sonar>) -> (Sonar, Vec<Coord>) {
                 continue 'outer;
             }
         }
-        panic!("No pair found!");
+        return Err(Error::Unsolvable(format!(
+            "{} scanners don't overlap with the others",
+            inputs.len()
+        )));
     }
 
-    (set, positions)
+    Ok((set, positions))
 }
 
-fn try_fit_par(s1: &Sonar, s2: &Sonar, number: usize) -> Option<(HashSet<Coord>, Coord, usize)> {
-    for rot in ROTATIONS {
+fn try_fit_par(s1: &Sonar, s2: &Sonar, number: usize) -> Option<(Sonar, Point3, usize)> {
+    for rot in Rotation::ALL {
+        let unrot = rot.inverse();
         let mut m = HashMap::new();
         for c2 in s2 {
             for c1 in s1 {
-                let c2_unrot = unrotate(c2, &rot);
-                let delta = sub_vectors(&c2_unrot, c1);
+                let c2_unrot = unrot.apply_point(*c2);
+                let delta = c2_unrot - *c1;
                 let entry = m.entry(delta).or_insert(0);
                 *entry += 1;
                 if *entry >= number {
                     let (coords_set, count) =
-                        fit_after_rotation_and_transposition(s1, s2, &rot, &delta);
-                    return Some((coords_set, minus_vector(&delta), count));
+                        fit_after_rotation_and_transposition(s1, s2, &unrot, delta);
+                    return Some((coords_set, Point3::ORIGIN - delta, count));
                 }
             }
         }
     }
 
-    return None;
+    None
 }
 
 fn fit_after_rotation_and_transposition(
     s1: &Sonar,
     s2: &Sonar,
-    rot: &[[i32; 3]; 3],
-    delta: &Coord,
+    unrot: &Rotation,
+    delta: Vec3,
 ) -> (Sonar, usize) {
     let mut count = 0;
     let mut coords_set = s1.clone();
     for c2 in s2 {
-        let c2_unrot = unrotate(c2, rot);
-        let c2_unrot_and_transposed = sub_vectors(&c2_unrot, delta);
+        let c2_unrot_and_transposed = unrot.apply_point(*c2) - delta;
         if coords_set.contains(&c2_unrot_and_transposed) {
             count += 1;
         } else {
@@ -83,131 +126,97 @@ fn fit_after_rotation_and_transposition(
     (coords_set, count)
 }
 
-const ROTATIONS: [[[i32; 3]; 3]; 24] = [
-    // https://www.euclideanspace.com/maths/algebra/matrix/transforms/examples/index.htm
-
-    // 0 - normal
-    [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
-    // 1 - Rotate 0,90,0
-    [[0, 0, 1], [0, 1, 0], [-1, 0, 0]],
-    // 2 - rotate 0,180,0
-    [[-1, 0, 0], [0, 1, 0], [0, 0, -1]],
-    // 3 - rotate 0,270,0
-    [[0, 0, -1], [0, 1, 0], [1, 0, 0]],
-    // 4 - rotate 0,0,90
-    [[0, -1, 0], [1, 0, 0], [0, 0, 1]],
-    // 5 - rotate 0,90,90
-    [[0, 0, 1], [1, 0, 0], [0, 1, 0]],
-    // 6 - rotate 0,180,90
-    [[0, 1, 0], [1, 0, 0], [0, 0, -1]],
-    // 7 - rotate 0,270,90
-    [[0, 0, -1], [1, 0, 0], [0, -1, 0]],
-    // 8 - rotate 0,0,270
-    [[0, 1, 0], [-1, 0, 0], [0, 0, 1]],
-    // 9 - rotate 0,90,270
-    [[0, 0, 1], [-1, 0, 0], [0, -1, 0]],
-    // 10 - rotate 0,180,270
-    [[0, -1, 0], [-1, 0, 0], [0, 0, -1]],
-    // 11 - rotate 0,270,270
-    [[0, 0, -1], [-1, 0, 0], [0, 1, 0]],
-    // 12 - rotate 90,0,0
-    [[1, 0, 0], [0, 0, -1], [0, 1, 0]],
-    // 13 - rotate 90,90,0
-    [[0, 1, 0], [0, 0, -1], [-1, 0, 0]],
-    // 14 - rotate 90,180,0
-    [[-1, 0, 0], [0, 0, -1], [0, -1, 0]],
-    // 15 - rotate 90,270,0
-    [[0, -1, 0], [0, 0, -1], [1, 0, 0]],
-    // 16 - rotate 180,0,0
-    [[1, 0, 0], [0, -1, 0], [0, 0, -1]],
-    // 17 - rotate 180,90,0
-    [[0, 0, -1], [0, -1, 0], [-1, 0, 0]],
-    // 18 - rotate 180,180,0
-    [[-1, 0, 0], [0, -1, 0], [0, 0, 1]],
-    // 19 - rotate 180,270,0
-    [[0, 0, 1], [0, -1, 0], [1, 0, 0]],
-    // 20 - rotate 270,0,0
-    [[1, 0, 0], [0, 0, 1], [0, -1, 0]],
-    // 21 - rotate 270,90,0
-    [[0, -1, 0], [0, 0, 1], [-1, 0, 0]],
-    // 22 - rotate 270,180,0
-    [[-1, 0, 0], [0, 0, 1], [0, 1, 0]],
-    // 23 - rotate 270,270,0
-    [[0, 1, 0], [0, 0, 1], [1, 0, 0]],
-];
-
-fn rotate(c: &Coord, rotation_matrix: &[[i32; 3]; 3]) -> Coord {
-    let new_x =
-        rotation_matrix[0][0] * c.0 + rotation_matrix[0][1] * c.1 + rotation_matrix[0][2] * c.2;
-    let new_y =
-        rotation_matrix[1][0] * c.0 + rotation_matrix[1][1] * c.1 + rotation_matrix[1][2] * c.2;
-    let new_z =
-        rotation_matrix[2][0] * c.0 + rotation_matrix[2][1] * c.1 + rotation_matrix[2][2] * c.2;
-    (new_x, new_y, new_z)
-}
+pub type Sonar = HashSet<Point3>;
 
-fn unrotate(c: &Coord, rotation_matrix: &[[i32; 3]; 3]) -> Coord {
-    // transpose rotation matrix
-    let new_x =
-        rotation_matrix[0][0] * c.0 + rotation_matrix[1][0] * c.1 + rotation_matrix[2][0] * c.2;
-    let new_y =
-        rotation_matrix[0][1] * c.0 + rotation_matrix[1][1] * c.1 + rotation_matrix[2][1] * c.2;
-    let new_z =
-        rotation_matrix[0][2] * c.0 + rotation_matrix[1][2] * c.1 + rotation_matrix[2][2] * c.2;
-    (new_x, new_y, new_z)
-}
+/// A `---` header line followed by beacons like `-1,2,3`, one per line, read piece by
+/// piece so that errors name the expected piece where it is missing.
+fn parse_one(s: &str) -> Result<Sonar> {
+    let mut lines = s.lines().map(str::trim);
 
-const SONAR_DISTANCE: i32 = 1000;
-
-fn add_vectors(c1: &Coord, c2: &Coord) -> Coord {
-    (c1.0 + c2.0, c1.1 + c2.1, c1.2 + c2.2)
-}
-
-fn sub_vectors(c1: &Coord, c2: &Coord) -> Coord {
-    (c1.0 - c2.0, c1.1 - c2.1, c1.2 - c2.2)
-}
-
-fn minus_vector(c1: &Coord) -> Coord {
-    (-1 * c1.0, -1 * c1.1, -1 * c1.2)
-}
+    // only a label, `--- scanner 0 ---` in puzzle inputs
+    let header = lines.next().unwrap_or_default();
+    error::strip_prefix(s, header, "---")?;
 
-fn manhatten_distance(c1: &Coord, c2: &Coord) -> i32 {
-    (c1.0 - c2.0).abs() + (c1.1 - c2.1).abs() + (c1.2 - c2.2).abs()
+    lines
+        .map(|line| {
+            let mut rest = line;
+            let mut coords = [0; 3];
+            for (ix, coord) in coords.iter_mut().enumerate() {
+                if ix > 0 {
+                    rest = error::strip_prefix(s, rest, ",")?;
+                }
+                (*coord, rest) = error::leading_number(s, rest, "coordinate")?;
+            }
+            error::end_of_line(s, rest)?;
+            Ok(Point3::new(coords[0], coords[1], coords[2]))
+        })
+        .collect()
 }
 
-type Coord = (i32, i32, i32);
-type Sonar = HashSet<Coord>;
-
-fn inputs() -> anyhow::Result<Vec<Sonar>> {
-    let input_string =
-        std::fs::read_to_string("inputs/19_input.txt").context("Error while reading input")?;
-
-    Ok(parse(&input_string))
-}
+/// `size` scanners, each overlapping with an earlier one in at least 12 beacons and reporting
+/// every beacon of the hidden cloud in its range (at least 25), in a random orientation.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    fn random_in(rng: &mut Rng, min: Point3, max: Point3) -> Point3 {
+        let mut coordinate = |from: i32, to: i32| rng.between(from as i64, to as i64) as i32;
+        Point3::new(
+            coordinate(min.x, max.x),
+            coordinate(min.y, max.y),
+            coordinate(min.z, max.z),
+        )
+    }
+    let range = Vec3::new(1000, 1000, 1000);
+    let in_range = |scanner: Point3| Cuboid::new(scanner - range, scanner + range);
+
+    let mut scanners = vec![Point3::ORIGIN];
+    let mut beacons = HashSet::new();
+    while beacons.len() < 25 {
+        beacons.insert(random_in(
+            rng,
+            Point3::ORIGIN - range,
+            Point3::ORIGIN + range,
+        ));
+    }
 
-fn parse(s: &str) -> Vec<Sonar> {
-    let split_by_scanner = s.split("\n\n");
-    let mut result = vec![];
+    while scanners.len() < size.max(2) {
+        let parent = *rng.choose(&scanners);
+        let mut offset = || rng.between(500, 1100) as i32 * if rng.one_in(2) { -1 } else { 1 };
+        let scanner = parent + Vec3::new(offset(), offset(), offset());
+        // keeps the scanners apart, or they would report most of the cloud
+        if scanners.iter().any(|s| s.chebyshev(scanner) < 1000) {
+            continue;
+        }
 
-    for scanner in split_by_scanner {
-        let scanner_coords = parse_one(scanner);
-        result.push(scanner_coords);
+        // parent and scanner see the same cube
+        let Some(shared) = in_range(parent).intersection(&in_range(scanner)) else {
+            continue;
+        };
+        let mut seen = 0;
+        while seen < 12 {
+            seen += beacons.insert(random_in(rng, shared.min, shared.max)) as usize;
+        }
+        let own = in_range(scanner);
+        while beacons.iter().filter(|&&b| own.contains(b)).count() < 25 {
+            beacons.insert(random_in(rng, own.min, own.max));
+        }
+        scanners.push(scanner);
     }
-    result
-}
 
-fn parse_one(s: &str) -> Sonar {
-    s.lines()
-        .skip(1)
-        .map(|l| {
-            let (x, y, z) = l.trim().split(',').collect_tuple().unwrap();
-            (
-                x.parse::<i32>().unwrap(),
-                y.parse::<i32>().unwrap(),
-                z.parse::<i32>().unwrap(),
-            )
-        })
-        .collect()
+    let mut beacons = beacons.into_iter().collect::<Vec<_>>();
+    beacons.sort_unstable();
+
+    let mut reports = vec![];
+    for (ix, &scanner) in scanners.iter().enumerate() {
+        let rotation = rng.choose(&Rotation::ALL);
+        let mut report = vec![format!("--- scanner {} ---", ix)];
+        for &beacon in &beacons {
+            if in_range(scanner).contains(beacon) {
+                let v = rotation.apply(beacon - scanner);
+                report.push(format!("{},{},{}", v.x, v.y, v.z));
+            }
+        }
+        reports.push(report.join("\n"));
+    }
+    reports.join("\n\n")
 }
 
 #[cfg(test)]
@@ -216,13 +225,31 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_rotate_unrotate() {
-        let dot = (3, 5, 7);
-        for rot in ROTATIONS.iter() {
-            let rotated = rotate(&dot, &rot);
-            let res = unrotate(&rotated, &rot);
-            assert_eq!(dot, res);
-        }
+    fn test_parse_errors() {
+        let located = |input: &str| match parse(input) {
+            Err(Error::Parse {
+                line,
+                column,
+                message,
+            }) => (line, column, message),
+            res => panic!("unexpected {:?}", res.map(|sonars| sonars.len())),
+        };
+        assert_eq!(
+            located("--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n4,5;6"),
+            (5, 4, "expected `,`, found `;`".to_owned())
+        );
+        assert_eq!(
+            located("--- scanner 0 ---\n1,2,3,4"),
+            (2, 6, "expected the end of the line, found `,`".to_owned())
+        );
+        assert_eq!(
+            located("--- scanner 0 ---\n1,x,3"),
+            (2, 3, "expected a digit, found `x`".to_owned())
+        );
+        assert_eq!(
+            located("1,2,3\n4,5,6"),
+            (1, 1, "expected `---`, found `1`".to_owned())
+        );
     }
 
     #[test]
@@ -232,14 +259,16 @@ mod tests {
         0,2,0
         4,1,0
         3,3,0",
-        );
+        )
+        .unwrap();
 
         let s2 = parse_one(
             "--- scanner 1 ---
         -1,-1,0
         -5,0,0
         -2,1,0",
-        );
+        )
+        .unwrap();
 
         let res = try_fit_par(&s1, &s2, 3);
 
@@ -251,50 +280,42 @@ mod tests {
     #[test]
     fn test_second_input() {
         let resulting = resulting();
+        let scanners = example();
 
-        let s0 = s0();
-
-        let s1 = s1();
-
-        let res = try_fit_par(&s0, &s1, 12);
+        let res = try_fit_par(&scanners[0], &scanners[1], 12);
         let (set, delta, n) = res.unwrap();
         assert_eq!(n, 12);
 
-        assert_eq!(delta, (68, -1246, -43));
+        assert_eq!(delta, Point3::new(68, -1246, -43));
         assert_eq!(set.len(), 38);
 
         assert!(resulting.is_superset(&set));
 
-        let s4 = s4();
-
-        let res = try_fit_par(&set, &s4, 12);
+        let res = try_fit_par(&set, &scanners[4], 12);
         let (set, delta, n) = res.unwrap();
         assert_eq!(n, 12);
 
-        assert_eq!(delta, (-20, -1133, 1061));
+        assert_eq!(delta, Point3::new(-20, -1133, 1061));
         assert_eq!(set.len(), 52);
 
         assert!(resulting.is_superset(&set));
 
-        let s2 = s2();
-
-        let res = try_fit_par(&set, &s2, 12);
+        let res = try_fit_par(&set, &scanners[2], 12);
 
         let (set, delta, n) = res.unwrap();
         assert_eq!(n, 12);
 
-        assert_eq!(delta, (1105, -1205, 1229));
+        assert_eq!(delta, Point3::new(1105, -1205, 1229));
         assert_eq!(set.len(), 66);
 
         assert!(resulting.is_superset(&set));
 
-        let s3 = s3();
-        let res = try_fit_par(&set, &s3, 12);
+        let res = try_fit_par(&set, &scanners[3], 12);
 
         let (set, delta, n) = res.unwrap();
         assert_eq!(n, 12);
 
-        assert_eq!(delta, (-92, -2380, -20));
+        assert_eq!(delta, Point3::new(-92, -2380, -20));
         assert_eq!(set.len(), 79);
     }
 
@@ -302,9 +323,7 @@ mod tests {
     fn try_with_resulting() {
         let resulting = resulting();
 
-        let s3 = s3();
-
-        let res = try_fit_par(&resulting, &s3, 12);
+        let res = try_fit_par(&resulting, &example()[3], 12);
         res.unwrap();
     }
 
@@ -325,15 +344,17 @@ mod tests {
             (-620, -3212, 371),
             (686, -3108, -505),
         ]
-        .into();
-        let s3_pos = minus_vector(&(-92, -2380, -20));
+        .into_iter()
+        .map(|(x, y, z)| Point3::new(x, y, z))
+        .collect();
+        let s3_pos = -Vec3::new(-92, -2380, -20);
 
-        let mut s3 = s3();
+        let mut s3 = example().swap_remove(3);
 
         'outer: for one in difference.iter() {
-            for (_ix, rot) in ROTATIONS.iter().enumerate() {
-                let diff = add_vectors(one, &s3_pos);
-                let rot = rotate(&diff, &rot);
+            for rot in Rotation::ALL.iter() {
+                let diff = *one + s3_pos;
+                let rot = rot.apply_point(diff);
 
                 if s3.contains(&rot) {
                     s3.remove(&rot);
@@ -427,162 +448,10 @@ mod tests {
         1889,-1729,1762
         1994,-1805,1792",
         )
+        .unwrap()
     }
 
-    fn s0() -> Sonar {
-        parse_one(
-            "--- scanner 0 ---
-        404,-588,-901
-        528,-643,409
-        -838,591,734
-        390,-675,-793
-        -537,-823,-458
-        -485,-357,347
-        -345,-311,381
-        -661,-816,-575
-        -876,649,763
-        -618,-824,-621
-        553,345,-567
-        474,580,667
-        -447,-329,318
-        -584,868,-557
-        544,-627,-890
-        564,392,-477
-        455,729,728
-        -892,524,684
-        -689,845,-530
-        423,-701,434
-        7,-33,-71
-        630,319,-379
-        443,580,662
-        -789,900,-551
-        459,-707,401",
-        )
-    }
-
-    fn s1() -> Sonar {
-        parse_one(
-            "--- scanner 1 ---
-        686,422,578
-        605,423,415
-        515,917,-361
-        -336,658,858
-        95,138,22
-        -476,619,847
-        -340,-569,-846
-        567,-361,727
-        -460,603,-452
-        669,-402,600
-        729,430,532
-        -500,-761,534
-        -322,571,750
-        -466,-666,-811
-        -429,-592,574
-        -355,545,-477
-        703,-491,-529
-        -328,-685,520
-        413,935,-424
-        -391,539,-444
-        586,-435,557
-        -364,-763,-893
-        807,-499,-711
-        755,-354,-619
-        553,889,-390",
-        )
-    }
-
-    fn s4() -> Sonar {
-        parse_one(
-            "--- scanner 4 ---
-        727,592,562
-        -293,-554,779
-        441,611,-461
-        -714,465,-776
-        -743,427,-804
-        -660,-479,-426
-        832,-632,460
-        927,-485,-438
-        408,393,-506
-        466,436,-512
-        110,16,151
-        -258,-428,682
-        -393,719,612
-        -211,-452,876
-        808,-476,-593
-        -575,615,604
-        -485,667,467
-        -680,325,-822
-        -627,-443,-432
-        872,-547,-609
-        833,512,582
-        807,604,487
-        839,-516,451
-        891,-625,532
-        -652,-548,-490
-        30,-46,-14",
-        )
-    }
-
-    fn s2() -> Sonar {
-        parse_one(
-            "--- scanner 2 ---
-        649,640,665
-        682,-795,504
-        -784,533,-524
-        -644,584,-595
-        -588,-843,648
-        -30,6,44
-        -674,560,763
-        500,723,-460
-        609,671,-379
-        -555,-800,653
-        -675,-892,-343
-        697,-426,-610
-        578,704,681
-        493,664,-388
-        -671,-858,530
-        -667,343,800
-        571,-461,-707
-        -138,-166,112
-        -889,563,-600
-        646,-828,498
-        640,759,510
-        -630,509,768
-        -681,-892,-333
-        673,-379,-804
-        -742,-814,-386
-        577,-820,562",
-        )
-    }
-
-    fn s3() -> Sonar {
-        parse_one(
-            "--- scanner 3 ---
-        -589,542,597
-        605,-692,669
-        -500,565,-823
-        -660,373,557
-        -458,-679,-417
-        -488,449,543
-        -626,468,-788
-        338,-750,-386
-        528,-832,-391
-        562,-778,733
-        -938,-730,414
-        543,643,-506
-        -524,371,-870
-        407,773,750
-        -104,29,83
-        378,-903,-323
-        -778,-728,485
-        426,699,580
-        -438,-605,-362
-        -469,-447,-387
-        509,732,623
-        647,635,-688
-        -868,-804,481
-        614,-800,639
-        595,780,-596",
-        )
+    fn example() -> Vec<Sonar> {
+        parse(include_str!("../inputs/examples/19_example.txt")).unwrap()
     }
 }
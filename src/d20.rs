@@ -1,54 +1,100 @@
-use anyhow::Context;
-use std::collections::HashMap;
+use crate::error::{self, Error, Result};
+use crate::grid::Grid;
+use crate::render::{self, Frame, Picture};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-pub fn part_01() -> anyhow::Result<usize> {
-    let (index, char_set) = inputs()?;
+pub type Image = Grid<char>;
 
-    let res = solve_01(char_set, &index, 2);
-    Ok(num_of_lighted(&res))
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    const NAME: &'static str = "Trench Map";
+
+    type Input = (Vec<char>, Image);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<(Vec<char>, Image)> {
+        parse(input)
+    }
+
+    fn part_01(input: &(Vec<char>, Image)) -> Result<usize> {
+        solve_part1(input)
+    }
+
+    fn part_02(input: &(Vec<char>, Image)) -> Result<usize> {
+        solve_part2(input)
+    }
+
+    const INPUT_SIZE: usize = 100;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
 }
 
-pub fn part_02() -> anyhow::Result<usize> {
-    let (index, char_map) = inputs()?;
+pub fn solve_part1((index, image): &(Vec<char>, Image)) -> Result<usize> {
+    let res = solve_01(image.clone(), '.', index, 2);
+    num_of_lighted(&res)
+}
 
-    let res = solve_01(char_map, &index, 50);
-    Ok(num_of_lighted(&res))
+pub fn solve_part2((index, image): &(Vec<char>, Image)) -> Result<usize> {
+    let res = solve_01(image.clone(), '.', index, 50);
+    num_of_lighted(&res)
 }
 
+/// Enhances the image `iterations` times. The image grows by a pixel on each side every time,
+/// and `background` is the colour of the infinitely many pixels around it.
 fn solve_01(
-    mut char_map: HashMap<(i32, i32), char>,
+    mut image: Image,
+    mut background: char,
     index: &[char],
     iterations: usize,
-) -> HashMap<(i32, i32), char> {
-    for i in 0..iterations {
-        let mut new_map = HashMap::new();
-        for (c, _) in char_map.iter() {
-            for c2 in DGTS.iter().map(|d| (c.0 + d.0, c.1 + d.1)) {
-                if new_map.contains_key(&c2) {
-                    continue;
-                }
-                let ch = if index[0] == '.' || i % 2 == 0 {
-                    '.'
-                } else {
-                    '#'
-                };
-                let bin_code = build_number(c2, &char_map, ch);
-                new_map.insert(c2, index[bin_code]);
-            }
+) -> (Image, char) {
+    for _ in 0..iterations {
+        let mut new_image = Grid::new(image.width() + 2, image.height() + 2, '.');
+        for (x, y) in new_image.positions().collect::<Vec<_>>() {
+            let bin_code = build_number((x as isize - 1, y as isize - 1), &image, background);
+            new_image[(x, y)] = index[bin_code];
         }
-        char_map = new_map;
+        image = new_image;
+        background = index[if background == '#' { 511 } else { 0 }];
     }
-    char_map
+    (image, background)
 }
 
-fn num_of_lighted(m: &HashMap<(i32, i32), char>) -> usize {
-    m.iter().filter(|&(_, v)| *v == '#').count()
+/// The image before and after each of the 50 enhancements of part 2, lit pixels white.
+pub fn render((index, image): &(Vec<char>, Image)) -> Vec<Frame> {
+    let mut state = (image.clone(), '.');
+    let mut frames = vec![];
+    for ix in 0..=50 {
+        if ix > 0 {
+            state = solve_01(state.0, state.1, index, 1);
+        }
+        let picture = render::cells(&state.0, &[('#', render::WHITE)]);
+        frames.push(Frame::new(
+            format!("20_enhance_{:02}", ix),
+            Picture::Color(picture),
+        ));
+    }
+    frames
+}
+
+fn num_of_lighted((image, background): &(Image, char)) -> Result<usize> {
+    if *background == '#' {
+        return Err(Error::Unsolvable(
+            "Infinitely many pixels are lit".to_owned(),
+        ));
+    }
+    Ok(image.iter().filter(|&(_, v)| *v == '#').count())
 }
 
 //-1,-1 -1,0 -1,1
 //0,-1 0,0 0,1
 //1,-1 1,0, 1,1
-const DGTS: [(i32, i32); 9] = [
+const DGTS: [(isize, isize); 9] = [
     (1, 1),
     (1, 0),
     (1, -1),
@@ -60,47 +106,76 @@ const DGTS: [(i32, i32); 9] = [
     (-1, -1),
 ];
 
-fn build_number(
-    central_coor: (i32, i32),
-    char_map: &HashMap<(i32, i32), char>,
-    unknown: char,
-) -> usize {
+/// Index of the pixel at `(x, y)` (which may lie outside of the image) in the enhancement
+/// algorithm, with every pixel outside of the image being `unknown`.
+fn build_number((x, y): (isize, isize), image: &Image, unknown: char) -> usize {
     let mut result = 0;
 
-    for (ix, d) in DGTS.iter().enumerate() {
-        let c = (central_coor.0 + d.0, central_coor.1 + d.1);
+    for (ix, (dy, dx)) in DGTS.iter().enumerate() {
+        let pixel = usize::try_from(x + dx)
+            .ok()
+            .zip(usize::try_from(y + dy).ok())
+            .and_then(|pos| image.get(pos))
+            .unwrap_or(&unknown);
 
-        match char_map.get(&c) {
-            Some('#') => result |= 1 << ix,
-            None if unknown == '#' => result |= 1 << ix,
-            Some('.') => (),
-            _ => (),
+        if *pixel == '#' {
+            result |= 1 << ix;
         }
     }
 
     result
 }
 
-fn inputs() -> anyhow::Result<(Vec<char>, HashMap<(i32, i32), char>)> {
-    let input_string =
-        std::fs::read_to_string("inputs/20_input.txt").context("Error while reading input")?;
+pub fn parse(input: &str) -> Result<(Vec<char>, Image)> {
+    let (index, image) = input.split_once("\n\n").ok_or_else(|| {
+        Error::parse_at(
+            input,
+            &input[input.len()..],
+            "missing empty line between the index and the image",
+        )
+    })?;
+
+    let index = index.trim();
+    if let Some(ix) = index.find(|c| c != '#' && c != '.') {
+        return Err(Error::parse_at(
+            input,
+            &index[ix..],
+            "index may only contain `#` and `.`",
+        ));
+    }
+    if index.len() != 512 {
+        return Err(Error::parse_at(
+            input,
+            index,
+            format!("index must have 512 characters, not {}", index.len()),
+        ));
+    }
+    let index = index.chars().collect();
 
-    Ok(parse(&input_string))
-}
+    let image = Grid::parse_with(image, "pixel", |c| matches!(c, '#' | '.').then_some(c))
+        .map_err(|err| err.at_line(error::position(input, image).0))?;
 
-fn parse(input_string: &str) -> (Vec<char>, HashMap<(i32, i32), char>) {
-    let mut split = input_string.split("\n\n");
+    Ok((index, image))
+}
 
-    let index = split.next().unwrap().chars().collect();
+/// Random enhancement algorithm and an image of `size` by `size`. The background may flicker
+/// but is always dark after an even number of steps.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut index = (0..512)
+        .map(|_| if rng.one_in(2) { '#' } else { '.' })
+        .collect::<Vec<_>>();
+    if index[0] == '#' {
+        index[511] = '.';
+    }
 
-    let mut char_set = HashMap::new();
-    for (y, line) in split.next().unwrap().lines().enumerate() {
-        for (x, chr) in line.trim().chars().enumerate() {
-            char_set.insert((y as i32, x as i32), chr);
+    let mut image = Grid::new(size.max(1), size.max(1), '.');
+    for pixel in image.values_mut() {
+        if rng.one_in(2) {
+            *pixel = '#';
         }
     }
 
-    (index, char_set)
+    format!("{}\n\n{}", index.iter().collect::<String>(), image)
 }
 
 #[cfg(test)]
@@ -118,81 +193,23 @@ mod tests {
         ..#..
         ..###";
 
-        let (index, char_map) = parse(input);
+        let (index, image) = parse(input).unwrap();
 
-        let num = build_number((2, 2), &char_map, '.');
+        let num = build_number((2, 2), &image, '.');
         assert_eq!(num, 34);
 
-        let res = solve_01(char_map, &index, 1);
+        let (res, background) = solve_01(image, '.', &index, 1);
 
-        assert_eq!(num_of_lighted(&res), 24);
+        assert_eq!(num_of_lighted(&(res.clone(), background)).unwrap(), 24);
 
-        let res = solve_01(res, &index, 1);
-        assert_eq!(num_of_lighted(&res), 35);
+        let res = solve_01(res, background, &index, 1);
+        assert_eq!(num_of_lighted(&res).unwrap(), 35);
     }
 
     #[test]
     fn tst_build_number() {
-        let map = [(1, 1)].into_iter().map(|e| (e, '#')).collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 1);
-
-        let map = [(1, 1), (1, 0)].into_iter().map(|e| (e, '#')).collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 3);
-
-        let map = [(1, 1), (1, 0), (1, -1)]
-            .into_iter()
-            .map(|e| (e, '#'))
-            .collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 7);
-
-        let map = [(1, 1), (1, 0), (1, -1), (0, 1)]
-            .into_iter()
-            .map(|e| (e, '#'))
-            .collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 15);
-
-        let map = [(1, 1), (1, 0), (1, -1), (0, 1), (0, 0)]
-            .into_iter()
-            .map(|e| (e, '#'))
-            .collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 31);
-
-        let map = [(1, 1), (1, 0), (1, -1), (0, 1), (0, 0), (0, -1)]
-            .into_iter()
-            .map(|e| (e, '#'))
-            .collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 63);
-
-        let map = [(1, 1), (1, 0), (1, -1), (0, 1), (0, 0), (0, -1), (-1, 1)]
-            .into_iter()
-            .map(|e| (e, '#'))
-            .collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 127);
-
-        let map = [
-            (1, 1),
-            (1, 0),
-            (1, -1),
-            (0, 1),
-            (0, 0),
-            (0, -1),
-            (-1, 1),
-            (-1, 0),
-        ]
-        .into_iter()
-        .map(|e| (e, '#'))
-        .collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 255);
-
-        let map = [
+        // (dy, dx) around the center, from the lowest bit up
+        let lit = [
             (1, 1),
             (1, 0),
             (1, -1),
@@ -202,11 +219,18 @@ mod tests {
             (-1, 1),
             (-1, 0),
             (-1, -1),
-        ]
-        .into_iter()
-        .map(|e| (e, '#'))
-        .collect();
-        let num = build_number((0, 0), &map, '.');
-        assert_eq!(num, 511);
+        ];
+        let expected = [1, 3, 7, 15, 31, 63, 127, 255, 511];
+
+        for (n, num) in expected.into_iter().enumerate() {
+            let mut image = Grid::new(3, 3, '.');
+            for &(dy, dx) in &lit[..=n] {
+                image[((1 + dx) as usize, (1 + dy) as usize)] = '#';
+            }
+            assert_eq!(build_number((1, 1), &image, '.'), num);
+        }
+
+        let image = Grid::new(1, 1, '.');
+        assert_eq!(build_number((0, 0), &image, '#'), 0b111_101_111);
     }
 }
@@ -1,43 +1,129 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
-// this is my input
-const PLAYER_1: u32 = 6;
-const PLAYER_2: u32 = 9;
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::solution::Solution;
+use crate::step::Simulation;
 
 const BOARD_SIZE: u32 = 10;
 
-pub fn part_01() -> anyhow::Result<u32> {
-    let players = [Player::new(PLAYER_1), Player::new(PLAYER_2)];
+pub struct Day21;
 
-    let (players, winner, dice) = solve_01(players);
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    const NAME: &'static str = "Dirac Dice";
+
+    type Input = [Player; 2];
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<[Player; 2]> {
+        parse(input)
+    }
+
+    fn part_01(players: &[Player; 2]) -> Result<u32> {
+        solve_part1(players)
+    }
+
+    fn part_02(players: &[Player; 2]) -> Result<u64> {
+        solve_part2(players)
+    }
+
+    const INPUT_SIZE: usize = 1;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn solve_part1(players: &[Player; 2]) -> Result<u32> {
+    let (players, winner, dice) = solve_01(players.clone());
 
     let looser = (winner + 1) % 2;
 
     Ok(players[looser].score * dice.number_of_rolls)
 }
 
-pub fn part_02() -> anyhow::Result<u64> {
-    let players = [Player::new(PLAYER_1), Player::new(PLAYER_2)];
+/// Game of part 1 played one turn at a time.
+#[derive(Clone, Debug)]
+pub struct Game {
+    players: [Player; 2],
+    dice: DeterministicDice,
+    turn: usize,
+}
+
+impl Game {
+    pub fn new(players: [Player; 2]) -> Game {
+        Game {
+            players,
+            dice: DeterministicDice::new(),
+            turn: 0,
+        }
+    }
+
+    fn winner(&self) -> Option<usize> {
+        self.players.iter().position(|player| player.score >= 1000)
+    }
+
+    /// Rolls three times for the player whose turn it is, returning what they rolled.
+    fn play_turn(&mut self) -> u32 {
+        let player_roll = self.dice.roll() + self.dice.roll() + self.dice.roll();
+        self.players[self.turn % 2].turn_move(player_roll);
+        self.turn += 1;
+        player_roll
+    }
+}
+
+impl Display for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (ix, player) in self.players.iter().enumerate() {
+            writeln!(
+                f,
+                "player {}: space {}, score {}",
+                ix + 1,
+                player.pos,
+                player.score
+            )?;
+        }
+        write!(f, "{} rolls", self.dice.number_of_rolls)
+    }
+}
 
-    let res = solve_02(players);
+impl Simulation for Game {
+    fn step(&mut self) -> Option<String> {
+        if self.winner().is_some() {
+            return None;
+        }
+
+        let player = self.turn % 2;
+        let roll = self.play_turn();
+        let mut event = format!(
+            "player {} rolls {} and moves to space {}",
+            player + 1,
+            roll,
+            self.players[player].pos
+        );
+        if self.winner().is_some() {
+            event.push_str(", winning the game");
+        }
+        Some(event)
+    }
+}
+
+pub fn solve_part2(players: &[Player; 2]) -> Result<u64> {
+    let res = solve_02(players.clone());
 
     Ok(std::cmp::max(res[0], res[1]))
 }
 
-fn solve_01(mut players: [Player; 2]) -> ([Player; 2], usize, DeterministicDice) {
-    let mut dice = DeterministicDice::new();
-    let winner;
-    'game: loop {
-        for ix in 0..=1 {
-            let player_roll = dice.roll() + dice.roll() + dice.roll();
-            players[ix].turn_move(player_roll);
-            if players[ix].score >= 1000 {
-                winner = ix;
-                break 'game;
-            }
+fn solve_01(players: [Player; 2]) -> ([Player; 2], usize, DeterministicDice) {
+    let mut game = Game::new(players);
+    loop {
+        game.play_turn();
+        if let Some(winner) = game.winner() {
+            return (game.players, winner, game.dice);
         }
     }
-    (players, winner, dice)
 }
 
 /*
@@ -86,7 +172,7 @@ fn solve_02(players: [Player; 2]) -> [u64; 2] {
     recurse(players, 0)
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct DeterministicDice {
     number_of_rolls: u32,
 }
@@ -100,20 +186,20 @@ impl DeterministicDice {
         self.number_of_rolls += 1;
         let mut ret = self.number_of_rolls;
         if ret > 100 {
-            ret = ret % 100;
+            ret %= 100;
         }
         ret
     }
 }
 
 #[derive(Debug, Clone)]
-struct Player {
-    pos: u32,
-    score: u32,
+pub struct Player {
+    pub pos: u32,
+    pub score: u32,
 }
 
 impl Player {
-    fn new(starting_pos: u32) -> Self {
+    pub fn new(starting_pos: u32) -> Self {
         Player {
             pos: starting_pos,
             score: 0,
@@ -123,7 +209,7 @@ impl Player {
     fn turn_move(&mut self, moves: u32) {
         let mut pos = self.pos + moves % BOARD_SIZE;
         if pos > BOARD_SIZE {
-            pos = pos % BOARD_SIZE;
+            pos %= BOARD_SIZE;
         }
 
         self.score += pos;
@@ -131,10 +217,58 @@ impl Player {
     }
 }
 
+pub fn parse(s: &str) -> Result<[Player; 2]> {
+    // Player 1 starting position: 6
+    let mut positions = s.lines().map(|line| {
+        let (_, pos) = line
+            .split_once(": ")
+            .ok_or_else(|| Error::parse_at(s, line.trim(), "missing starting position"))?;
+        let pos_str = pos.trim();
+        let pos = error::parse_at(s, pos_str, "starting position")?;
+        if !(1..=BOARD_SIZE).contains(&pos) {
+            return Err(Error::parse_at(
+                s,
+                pos_str,
+                format!("starting position must be between 1 and {}", BOARD_SIZE),
+            ));
+        }
+        Ok(pos)
+    });
+
+    let missing =
+        |player: u8| Error::parse_at(s, &s[s.len()..], format!("missing player {}", player));
+    let player_1 = positions.next().ok_or_else(|| missing(1))??;
+    let player_2 = positions.next().ok_or_else(|| missing(2))??;
+
+    Ok([Player::new(player_1), Player::new(player_2)])
+}
+
+/// Random starting positions, `size` doesn't matter.
+pub fn generate(rng: &mut Rng, _size: usize) -> String {
+    let mut position = || rng.between(1, BOARD_SIZE as i64);
+    format!(
+        "Player 1 starting position: {}\nPlayer 2 starting position: {}",
+        position(),
+        position()
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse() {
+        let players = parse(
+            "Player 1 starting position: 4
+        Player 2 starting position: 8",
+        )
+        .unwrap();
+
+        assert_eq!(players[0].pos, 4);
+        assert_eq!(players[1].pos, 8);
+    }
+
     #[test]
     fn test_inputs() {
         let players = [Player::new(4), Player::new(8)];
@@ -1,139 +1,233 @@
-use anyhow::Context;
 use std::collections::HashSet;
 
-pub fn part_01() -> anyhow::Result<usize> {
-    let inputs = inputs()?;
+use crate::error::{self, Error, Result};
+use crate::geom::{Cuboid, Point3};
+use crate::rng::Rng;
+use crate::solution::Solution;
 
-    let mut lighted = HashSet::new();
+pub struct Day22;
 
-    for (light_on, (x0, x1), (y0, y1), (z0, z1)) in inputs {
-        if (x0 > 50 && x1 > 50) || (x0 < -50 && x1 < -50) {
-            continue;
-        }
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    const NAME: &'static str = "Reactor Reboot";
 
-        if (y0 > 50 && y1 > 50) || (y0 < -50 && y1 < -50) {
-            continue;
-        }
+    type Input = Vec<Step>;
+    type Answer1 = usize;
+    type Answer2 = u64;
 
-        if (z0 > 50 && z1 > 50) || (z0 < -50 && z1 < -50) {
-            continue;
-        }
+    fn parse(input: &str) -> Result<Vec<Step>> {
+        parse(input)
+    }
 
-        for ix in x0..=x1 {
-            for iy in y0..=y1 {
-                for iz in z0..=z1 {
-                    if ix.abs() > 50 && iy.abs() > 50 && iz.abs() > 50 {
-                        continue;
-                    }
+    fn part_01(inputs: &Vec<Step>) -> Result<usize> {
+        solve_part1(inputs)
+    }
+
+    fn part_02(inputs: &Vec<Step>) -> Result<u64> {
+        solve_part2(inputs)
+    }
+
+    const INPUT_SIZE: usize = 420;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
+}
+
+pub fn solve_part1(inputs: &[Step]) -> Result<usize> {
+    Ok(solve_01(inputs))
+}
+
+pub fn solve_part2(inputs: &[Step]) -> Result<u64> {
+    solve_02(inputs).ok_or_else(|| Error::Overflow("volume of lit cubes".to_owned()))
+}
+
+/// Region of the cubes counted in part 1.
+pub(crate) fn initialization_area() -> Cuboid {
+    Cuboid::new(Point3::new(-50, -50, -50), Point3::new(50, 50, 50))
+}
+
+/// Lit cubes of the initialization area, switching every single cube.
+pub(crate) fn solve_01(inputs: &[Step]) -> usize {
+    let region = initialization_area();
+    let mut lighted = HashSet::new();
+
+    for step in inputs {
+        let Some(c) = step.cuboid.intersection(&region) else {
+            continue;
+        };
 
-                    if light_on {
-                        lighted.insert((ix, iy, iz));
+        for ix in c.min.x..=c.max.x {
+            for iy in c.min.y..=c.max.y {
+                for iz in c.min.z..=c.max.z {
+                    if step.on {
+                        lighted.insert(Point3::new(ix, iy, iz));
                     } else {
-                        lighted.remove(&(ix, iy, iz));
+                        lighted.remove(&Point3::new(ix, iy, iz));
                     }
                 }
             }
         }
     }
 
-    Ok(lighted.len())
+    lighted.len()
 }
 
-pub fn part_02() -> anyhow::Result<u64> {
-    let inputs = inputs()?;
-
-    let mut volume = 0;
+fn solve_02(inputs: &[Step]) -> Option<u64> {
+    let cuboids = inputs.iter().map(|step| step.cuboid).collect::<Vec<_>>();
+    let mut volume = 0u64;
 
-    for (ix, input) in inputs.iter().enumerate() {
-        if input.0 == false {
+    for (ix, step) in inputs.iter().enumerate() {
+        if !step.on {
             continue;
         }
 
-        volume += unique_volume(input, &inputs[ix + 1..]);
+        volume = volume.checked_add(unique_volume(&step.cuboid, &cuboids[ix + 1..])?)?;
     }
 
-    Ok(volume) // too low 1261788327439723 / too high 1525886909356651
+    Some(volume)
 }
 
-fn unique_volume(c: &Cuboid, others: &[Cuboid]) -> u64 {
+/// Volume of `c` not covered by any of the `others`.
+fn unique_volume(c: &Cuboid, others: &[Cuboid]) -> Option<u64> {
     let intersection_cubes = others
         .iter()
-        .filter_map(|other| intersect(c, other))
+        .filter_map(|other| c.intersection(other))
         .collect::<Vec<_>>();
 
     // we must remove intersections from intersected cubes
-    let unique_intersecting_volume: u64 = intersection_cubes
+    let unique_intersecting_volume = intersection_cubes
         .iter()
         .enumerate()
-        .map(|(ix, c)| unique_volume(c, &intersection_cubes[ix + 1..]))
-        .sum();
-    v(c) - unique_intersecting_volume
+        .try_fold(0u64, |acc, (ix, c)| {
+            acc.checked_add(unique_volume(c, &intersection_cubes[ix + 1..])?)
+        })?;
+    c.volume()?.checked_sub(unique_intersecting_volume)
 }
 
-type Cuboid = (bool, (i32, i32), (i32, i32), (i32, i32));
+/// One reboot step, switching every cube in `cuboid` on or off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub on: bool,
+    pub cuboid: Cuboid,
+}
 
-use std::cmp::{max, min};
+pub fn parse(s: &str) -> Result<Vec<Step>> {
+    let mut res = vec![];
+    for line in s.lines() {
+        let line = line.trim();
+        let (instruction_str, coords) = line.split_once(' ').ok_or_else(|| {
+            Error::parse_at(s, line, "expected a step like `on x=1..2,y=1..2,z=1..2`")
+        })?;
+
+        let ins = match instruction_str {
+            "on" => true,
+            "off" => false,
+            _ => {
+                return Err(Error::parse_at(
+                    s,
+                    instruction_str,
+                    "step must be `on` or `off`",
+                ))
+            }
+        };
+        let [x, y, z] = parse_ranges(s, coords.trim())?;
 
-fn have_intersect(c0: &Cuboid, c1: &Cuboid) -> bool {
-    let (_, (c0x0, c0x1), (c0y0, c0y1), (c0z0, c0z1)) = c0;
-    let (_, (c1x0, c1x1), (c1y0, c1y1), (c1z0, c1z1)) = c1;
+        let from = Point3::new(x.0, y.0, z.0);
+        let to = Point3::new(x.1, y.1, z.1);
 
-    c0x0 < c1x1 && c0x1 > c1x0 && c0y0 < c1y1 && c0y1 > c1y0 && c0z0 < c1z1 && c0z1 > c1z0
+        res.push(Step {
+            on: ins,
+            cuboid: Cuboid::new(from, to),
+        })
+    }
+    Ok(res)
 }
 
-fn intersect(c0: &Cuboid, c1: &Cuboid) -> Option<Cuboid> {
-    if !have_intersect(c0, c1) {
-        return None;
+/// Ranges of `x=8088..31780,y=59042..84353,z=-35793..-10401`, read piece by piece so that errors
+/// name the expected piece where it is missing.
+fn parse_ranges(input: &str, coords: &str) -> Result<[(i32, i32); 3]> {
+    let mut rest = coords;
+    let mut ranges = [(0, 0); 3];
+    for (range, axis) in ranges.iter_mut().zip(["x=", ",y=", ",z="]) {
+        let mut bounds = [0; 2];
+        for (bound, prefix) in bounds.iter_mut().zip([axis, ".."]) {
+            rest = error::strip_prefix(input, rest, prefix)?;
+            (*bound, rest) = error::leading_number(input, rest, "coordinate")?;
+        }
+        *range = (bounds[0], bounds[1]);
     }
 
-    let (_, (c0x0, c0x1), (c0y0, c0y1), (c0z0, c0z1)) = c0;
-    let (_, (c1x0, c1x1), (c1y0, c1y1), (c1z0, c1z1)) = c1;
-
-    let x0 = min(max(*c0x0, *c1x0), *c1x1); // max(*c0x0, *c1x0);
-    let x1 = min(max(*c0x1, *c1x0), *c1x1); // min(*c0x1, *c1x1);
-
-    let y0 = min(max(*c0y0, *c1y0), *c1y1);
-    let y1 = min(max(*c0y1, *c1y0), *c1y1);
-
-    let z0 = min(max(*c0z0, *c1z0), *c1z1);
-    let z1 = min(max(*c0z1, *c1z0), *c1z1);
-
-    Some((true, (x0, x1), (y0, y1), (z0, z1)))
+    error::end_of_line(input, rest)?;
+    Ok(ranges)
 }
 
-fn v(c: &Cuboid) -> u64 {
-    ((c.1 .1 - c.1 .0).abs() as u64 + 1)
-        * ((c.2 .1 - c.2 .0).abs() as u64 + 1)
-        * ((c.3 .1 - c.3 .0).abs() as u64 + 1)
-}
-
-fn inputs() -> anyhow::Result<Vec<Cuboid>> {
-    let input_string =
-        std::fs::read_to_string("inputs/22_input.txt").context("Error while reading input")?;
-
-    parse(&input_string)
+/// `size` reboot steps, the first twentieth of them inside the initialization area.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lines = vec![];
+    for ix in 0..size.max(1) {
+        let (reach, length) = if ix <= size / 20 {
+            (50, 50)
+        } else {
+            (90_000, 30_000)
+        };
+        let mut range = || {
+            let from = rng.between(-reach, reach - length / 5);
+            let to = (from + rng.between(length / 5, length)).min(reach);
+            format!("{}..{}", from, to)
+        };
+        let (x, y, z) = (range(), range(), range());
+        let on = ix == 0 || !rng.one_in(4);
+
+        lines.push(format!(
+            "{} x={},y={},z={}",
+            if on { "on" } else { "off" },
+            x,
+            y,
+            z
+        ));
+    }
+    lines.join("\n")
 }
 
-fn parse(s: &str) -> anyhow::Result<Vec<Cuboid>> {
-    let mut res = vec![];
-    for line in s.lines() {
-        let mut split = line.trim().split(" ");
-        let instruction_str = split.next().context("Bad input line")?.trim();
-
-        let ins = if instruction_str == "on" { true } else { false };
-        let coords = split.next().context("Bad input line")?.trim();
-
-        // x=8088..31780,y=59042..84353,z=-35793..-10401
-        let re =
-            regex::Regex::new(r"x=(-?\d+)..(-?\d+),y=(-?\d+)..(-?\d+),z=(-?\d+)..(-?\d+)").unwrap();
-
-        let caps = re.captures(coords).context("Bad format for coords")?;
-
-        let x = (caps[1].parse::<i32>()?, caps[2].parse::<i32>()?);
-        let y = (caps[3].parse::<i32>()?, caps[4].parse::<i32>()?);
-        let z = (caps[5].parse::<i32>()?, caps[6].parse::<i32>()?);
-
-        res.push((ins, x, y, z))
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        let steps = parse("on x=-5..2,y=3..4,z=5..6\noff x=1..1,y=-3..-2,z=0..0").unwrap();
+        assert_eq!(
+            steps[1].cuboid,
+            Cuboid::new(Point3::new(1, -3, 0), Point3::new(1, -2, 0))
+        );
+
+        let located = |input: &str| match parse(input) {
+            Err(Error::Parse {
+                column, message, ..
+            }) => (column, message),
+            res => panic!("unexpected {:?}", res.map(|steps| steps.len())),
+        };
+        assert_eq!(
+            located("on x=1..2,y=3..4,z=5..q"),
+            (23, "expected a digit, found `q`".to_owned())
+        );
+        assert_eq!(
+            located("on x=1..2;y=3..4,z=5..6"),
+            (10, "expected `,y=`, found `;`".to_owned())
+        );
+        assert_eq!(
+            located("on x=1.2,y=3..4,z=5..6"),
+            (7, "expected `..`, found `.`".to_owned())
+        );
+        assert_eq!(
+            located("on x=1..2,y=3..4"),
+            (17, "expected `,z=`, found the end of the line".to_owned())
+        );
+        assert_eq!(located("on x=1..2,y=3..4,z=5..6,").0, 24);
+        assert!(located("on x=1..99999999999,y=3..4,z=5..6")
+            .1
+            .contains("coordinate"));
     }
-    Ok(res)
 }
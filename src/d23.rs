@@ -1,53 +1,87 @@
-use anyhow::Context;
-use std::{collections::HashMap, panic};
+use std::collections::HashMap;
+use std::fmt::Display;
 
-pub fn part_01() -> anyhow::Result<usize> {
-    let inputs = inputs()?;
+use crate::error::{self, Error, Result};
+use crate::rng::Rng;
+use crate::search::{self, Search};
+use crate::solution::Solution;
+use crate::step::Simulation;
 
-    let mut state = to_map(inputs);
+pub struct Day23;
 
-        let res = solve_01(&mut state, 2);
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    const NAME: &'static str = "Amphipod";
 
-    Ok(res)
+    type Input = Vec<Amphypod>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
+    fn parse(input: &str) -> Result<Vec<Amphypod>> {
+        parse(input)
+    }
+
+    fn part_01(inputs: &Vec<Amphypod>) -> Result<usize> {
+        solve_part1(inputs)
+    }
+
+    fn part_02(inputs: &Vec<Amphypod>) -> Result<usize> {
+        solve_part2(inputs)
+    }
+
+    const INPUT_SIZE: usize = 1;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        generate(rng, size)
+    }
 }
 
-pub fn part_02() -> anyhow::Result<usize> {
-    let inputs = inputs()?;
+pub fn solve_part1(inputs: &[Amphypod]) -> Result<usize> {
+    let state = to_map(inputs.to_vec());
 
-    let mut state = to_map(inputs);
+    let res = solve_01(&state, 2);
 
-    let a = state.remove(&(2, 2)).unwrap();
+    finished(res)
+}
+
+pub fn solve_part2(inputs: &[Amphypod]) -> Result<usize> {
+    let mut state = to_map(inputs.to_vec());
+
+    let a = state.remove(&(2, 2)).ok_or_else(|| missing_amphipod(2))?;
     state.insert((2, 2),'D');
     state.insert((2, 3),'D');
     state.insert((2, 4),a);
 
-    let a = state.remove(&(4, 2)).unwrap();
+    let a = state.remove(&(4, 2)).ok_or_else(|| missing_amphipod(4))?;
     state.insert((4, 2),'C');
     state.insert((4, 3),'B');
     state.insert((4, 4),a);
 
-    let a = state.remove(&(6, 2)).unwrap();
+    let a = state.remove(&(6, 2)).ok_or_else(|| missing_amphipod(6))?;
     state.insert((6, 2),'B');
     state.insert((6, 3),'A');
     state.insert((6, 4),a);
 
-    let a = state.remove(&(8, 2)).unwrap();
+    let a = state.remove(&(8, 2)).ok_or_else(|| missing_amphipod(8))?;
     state.insert((8, 2),'A');
     state.insert((8, 3),'C');
     state.insert((8, 4),a);
 
-        let res = solve_01(&mut state, 4);
+    let res = solve_01(&state, 4);
 
-    Ok(res)
+    finished(res)
 }
 
+fn finished(cost: Option<usize>) -> Result<usize> {
+    cost.ok_or_else(stuck)
+}
 
-fn inputs() -> anyhow::Result<Vec<Amphypod>> {
-    let input_string =
-        std::fs::read_to_string("inputs/23_input.txt").context("Error while reading input")?;
+fn stuck() -> Error {
+    Error::Unsolvable("Amphipods can't reach their side rooms".to_owned())
+}
 
-    parse(&input_string)
+fn missing_amphipod(x: usize) -> Error {
+    Error::Unsolvable(format!("Missing amphipod in the side room at x={}", x))
 }
 
 fn amphypod_finished(a: &Amphypod) -> bool {
@@ -78,86 +112,136 @@ fn amphypod_finished_actual(a: &Amphypod, state: &HashMap<(usize, usize), char>,
         }
     }
 
-    return true;
-}
-
-fn all_finished(state: &HashMap<(usize, usize), char>) -> bool {
-    for (k, v) in state {
-        if !amphypod_finished(&(*k, *v)) {
-            return false;
-        }
-    }
     true
 }
 
-fn solve_01(state: &mut HashMap<(usize, usize), char>, max_depth: usize) -> usize {
-
-    fn recurse(state: &mut HashMap<(usize, usize), char>, cur_cost: usize, best_so_far: usize, cache: &mut HashMap<Vec<Amphypod>, usize>, max_depth: usize) -> usize {
+fn solve_01(state: &HashMap<(usize, usize), char>, max_depth: usize) -> Option<usize> {
+    search::a_star(&Burrow { max_depth }, to_vec(state))
+}
 
-        if all_finished(state) {
-            // println!("bsof = {}, cur ={}, stt{:?}", best_so_far, cur_cost, state);
-            return cur_cost;
-        }
+/// The cheapest organization of part 1, replayed one move at a time.
+#[derive(Clone, Debug)]
+pub struct Organization {
+    path: Vec<Vec<Amphypod>>,
+    costs: Vec<usize>,
+    moves: usize,
+}
 
-        if cur_cost > best_so_far {
-            return best_so_far;
-        }
+impl Organization {
+    pub fn new(inputs: &[Amphypod]) -> Result<Organization> {
+        let burrow = Burrow { max_depth: 2 };
+        let path =
+            search::a_star_path(&burrow, to_vec(&to_map(inputs.to_vec()))).ok_or_else(stuck)?;
+
+        let costs = path
+            .states
+            .windows(2)
+            .map(|states| {
+                burrow
+                    .neighbours(&states[0])
+                    .into_iter()
+                    .find(|(next, _)| *next == states[1])
+                    .map_or(0, |(_, cost)| cost)
+            })
+            .collect();
+
+        Ok(Organization {
+            path: path.states,
+            costs,
+            moves: 0,
+        })
+    }
 
-        let mut moves = vec![];
+    fn energy(&self) -> usize {
+        self.costs[..self.moves].iter().sum()
+    }
+}
 
-        for (k, v) in state.iter() {
-            let a = (*k, *v);
-            if amphypod_finished_actual(&a, state, max_depth) {
-                continue;
+impl Display for Organization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = to_map(self.path[self.moves].clone());
+        let cell = |pos: (usize, usize)| state.get(&pos).copied().unwrap_or('.');
+
+        writeln!(f, "{} energy used", self.energy())?;
+        writeln!(f, "#############")?;
+        writeln!(
+            f,
+            "#{}#",
+            (0..=10).map(|x| cell((x, 0))).collect::<String>()
+        )?;
+        for y in 1..=2 {
+            let rooms = [2, 4, 6, 8].map(|x| cell((x, y)).to_string()).join("#");
+            match y {
+                1 => writeln!(f, "###{}###", rooms)?,
+                _ => writeln!(f, "  #{}#", rooms)?,
             }
-            
-            possible_moves(state, *k, max_depth)
-                .into_iter()
-                .map(|a2| (a, a2, distance_cost(&a, &a2)))
-                .for_each(|el| moves.push(el));
         }
+        write!(f, "  #########")
+    }
+}
 
-        let mut new_best_so_far = best_so_far;
+impl Simulation for Organization {
+    fn step(&mut self) -> Option<String> {
+        let (from, to) = (self.path.get(self.moves)?, self.path.get(self.moves + 1)?);
+        let left = from.iter().find(|a| !to.contains(a))?;
+        let arrived = to.iter().find(|a| !from.contains(a))?;
+        let cost = self.costs[self.moves];
+        self.moves += 1;
+
+        Some(format!(
+            "{} moves from {:?} to {:?} for {} energy",
+            left.1, left.0, arrived.0, cost
+        ))
+    }
+}
 
-        // sort by distance
-        moves.sort_by(|m1, m2| m1.2.cmp(&m2.2));
+/// Amphipods moving into their side rooms, the state being all of them sorted by position.
+struct Burrow {
+    max_depth: usize,
+}
 
-        for (a1, a2, distance) in moves {
+impl Search for Burrow {
+    type State = Vec<Amphypod>;
+    type Cost = usize;
 
-            state.remove(&a1.0);
-            state.insert(a2.0, a2.1);
+    fn neighbours(&self, amphypods: &Vec<Amphypod>) -> Vec<(Vec<Amphypod>, usize)> {
+        let state = to_map(amphypods.clone());
+        let mut moves = vec![];
 
-            let next_cost = cur_cost + distance;
-            let cache_line  = to_vec(state);
-            if let Some(prev_cost) = cache.get(&cache_line) {
-                if *prev_cost <= next_cost {
-                    state.remove(&a2.0);
-                    state.insert(a1.0, a1.1);
-                    continue;
-                }
+        for a in amphypods {
+            for a2 in possible_moves(&state, a.0, self.max_depth) {
+                let mut next = state.clone();
+                next.remove(&a.0);
+                next.insert(a2.0, a2.1);
+                moves.push((to_vec(&next), distance_cost(a, &a2)));
             }
-            cache.insert(cache_line, next_cost);
-
-            let res = recurse(state, next_cost, new_best_so_far, cache, max_depth);
-            new_best_so_far = min(res, new_best_so_far);
-
-            state.remove(&a2.0);
-            state.insert(a1.0, a1.1);
         }
 
-        new_best_so_far
+        moves
+    }
+
+    fn is_goal(&self, amphypods: &Vec<Amphypod>) -> bool {
+        amphypods.iter().all(amphypod_finished)
     }
 
-    let mut cache = HashMap::new();
-    recurse(state, 0, usize::MAX, &mut cache, max_depth)
+    // amphipods outside of their side room have to walk at least into its first cell
+    fn heuristic(&self, amphypods: &Vec<Amphypod>) -> usize {
+        amphypods
+            .iter()
+            .filter(|a| !amphypod_finished(a))
+            .map(|a| distance_cost(a, &((sideroom_x(a.1), 1), a.1)))
+            .sum()
+    }
 }
 
 fn possible_moves(state: &HashMap<(usize, usize), char>, ix: (usize, usize), max_depth: usize) -> Vec<Amphypod> {
 
-    let a = *state.get(&ix).unwrap();
-
     let mut moves = vec![];
 
+    let Some(&a) = state.get(&ix) else {
+        return moves;
+    };
+
     if amphypod_finished_actual(&(ix, a), state, max_depth) {
         return moves;
     }
@@ -229,8 +313,7 @@ fn possible_moves(state: &HashMap<(usize, usize), char>, ix: (usize, usize), max
 fn go_to_sideroom(ix: (usize, usize), a:char, state: &HashMap<(usize, usize), char>, max_depth: usize) -> Option<Amphypod> {
     let final_x = sideroom_x(a);
         for y in 1..=max_depth {
-            let a2 = state.get(&(final_x, y));
-            if a2.is_some() && *a2.unwrap() != a {
+            if state.get(&(final_x, y)).is_some_and(|&a2| a2 != a) {
                 return None;
             }
         }
@@ -254,62 +337,93 @@ fn go_to_sideroom(ix: (usize, usize), a:char, state: &HashMap<(usize, usize), ch
 
 use std::cmp::{min, max};
 
+// parse only lets amphipods A to D through
 fn sideroom_x(a: char) -> usize {
     match a {
         'A' => 2,
         'B' => 4,
         'C' => 6,
-        'D' => 8,
-        _ => panic!("wrong letter")
+        _ => 8,
     }
 }
 
 fn distance_cost(a1: &Amphypod, a2: &Amphypod) -> usize {
 
-    let x = (a1.0.0 as isize - a2.0.0 as isize).abs() as usize;
+    let x = (a1.0.0 as isize - a2.0.0 as isize).unsigned_abs();
     let y = a1.0.1 + a2.0.1;
 
     let cost = match a1.1 {
         'A' => 1,
         'B' => 10, 
         'C' => 100,
-        'D' => 1000,
-        _ => panic!("unexpected letter")
+        _ => 1000,
     };
 
     (x + y) * cost
 }
 
 
-type Amphypod = ((usize, usize), char);
-
-fn parse(s: &str) -> anyhow::Result<Vec<Amphypod>> {
-    let mut lines = s.lines().skip(1);
-    let corridor_line = lines.next().context("bad input")?.trim();
-    let first_line = lines.next().context("bad input")?.trim();
-    let second_line = lines.next().context("bad input")?.trim();
-
-    let mut corridors: Vec<_> = corridor_line.chars().enumerate()
-        .filter(|(_ix, ch)| *ch == 'A' || *ch == 'B' || *ch == 'C' || *ch == 'D')
-        .map(|(ix, ch)| ((ix - 1, 0), ch))
-        .collect();
-
-    let first_liners: Vec<_> = first_line
-        .chars()
-        .enumerate()
-        .filter(|(_ix, ch)| *ch == 'A' || *ch == 'B' || *ch == 'C' || *ch == 'D')
-        .map(|(ix, ch)| ((ix - 1, 1), ch))
-        .collect();
-    let second_liners: Vec<_> = second_line
-        .chars()
-        .enumerate()
-        .filter(|(_ix, ch)| *ch == 'A' || *ch == 'B' || *ch == 'C' || *ch == 'D')
-        .map(|(ix, ch)| ((ix + 1, 2), ch))
-        .collect();
-
-        corridors.extend(first_liners);
-        corridors.extend(second_liners);
-    Ok(corridors)
+pub type Amphypod = ((usize, usize), char);
+
+/// Rows of the burrow, trimmed, with `.` where an amphipod may stand and the number of columns
+/// each row starts to the right of the hallway. Rows are matched a character at a time, so errors
+/// name the expected character where the input goes wrong.
+const BURROW: [(&str, usize); 5] = [
+    ("#############", 0),
+    ("#...........#", 0),
+    ("###.#.#.#.###", 0),
+    ("#.#.#.#.#", 2),
+    ("#########", 2),
+];
+
+pub fn parse(s: &str) -> Result<Vec<Amphypod>> {
+    let mut lines = s.lines().map(str::trim);
+    let mut amphypods = vec![];
+    let mut letters = vec![];
+
+    for (y, (row, indent)) in BURROW.iter().enumerate() {
+        let line = lines.next().ok_or_else(|| {
+            let message = format!("expected burrow row `{}`, found the end of the input", row);
+            Error::parse_at(s, &s[s.len()..], message)
+        })?;
+        let mut rest = line;
+
+        for (ix, cell) in row.chars().enumerate() {
+            match (cell, rest.chars().next()) {
+                ('#', Some('#')) | ('.', Some('.')) => {}
+                ('.', Some(ch @ 'A'..='D')) => {
+                    amphypods.push(((ix + indent - 1, y - 1), ch));
+                    letters.push(rest);
+                }
+                ('#', _) => return Err(error::expected(s, rest, "`#`")),
+                _ => return Err(error::expected(s, rest, "`.` or an amphipod A to D")),
+            }
+            rest = &rest[1..];
+        }
+        error::end_of_line(s, rest)?;
+    }
+    if let Some(line) = lines.find(|line| !line.is_empty()) {
+        return Err(error::expected(s, line, "the end of the input"));
+    }
+
+    for letter in ['A', 'B', 'C', 'D'] {
+        let mut of_type = letters.iter().filter(|rest| rest.starts_with(letter));
+        if let Some(third) = of_type.nth(2) {
+            let message = format!("expected two amphipods of type {}, found a third", letter);
+            return Err(Error::parse_at(s, third, message));
+        }
+    }
+    for letter in ['A', 'B', 'C', 'D'] {
+        let count = letters
+            .iter()
+            .filter(|rest| rest.starts_with(letter))
+            .count();
+        if count != 2 {
+            let message = format!("expected two amphipods of type {}, found {}", letter, count);
+            return Err(Error::parse_at(s, &s[s.len()..], message));
+        }
+    }
+    Ok(amphypods)
 }
 
 fn to_map(v: Vec<Amphypod>) -> HashMap<(usize, usize), char> {
@@ -322,6 +436,24 @@ fn to_vec(m: &HashMap<(usize, usize), char>) -> Vec<Amphypod> {
     v
 }
 
+/// Amphipods shuffled into the side rooms, `size` doesn't matter.
+pub fn generate(rng: &mut Rng, _size: usize) -> String {
+    let mut amphypods = ['A', 'A', 'B', 'B', 'C', 'C', 'D', 'D'];
+    rng.shuffle(&mut amphypods);
+    let row = |row: &[char]| {
+        row.iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+            .join("#")
+    };
+
+    format!(
+        "#############\n#...........#\n###{}###\n  #{}#\n  #########",
+        row(&amphypods[..4]),
+        row(&amphypods[4..])
+    )
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -345,6 +477,55 @@ mod tests {
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn test_parse_broken_burrow() {
+        let located = |s: &str| match parse(s) {
+            Err(Error::Parse {
+                line,
+                column,
+                message,
+            }) => (line, column, message),
+            res => panic!("unexpected {:?}", res),
+        };
+
+        let s = "#############\nA...........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########";
+        assert_eq!(located(s), (2, 1, "expected `#`, found `A`".to_owned()));
+
+        let s = "#############\n#...........#\n##B#C#B#D####\n  #A#D#C#A#\n  #########";
+        assert_eq!(located(s), (3, 3, "expected `#`, found `B`".to_owned()));
+
+        let s = "#############\n#..x........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########";
+        assert_eq!(
+            located(s),
+            (
+                2,
+                4,
+                "expected `.` or an amphipod A to D, found `x`".to_owned()
+            )
+        );
+
+        let s = "#############\n#...........#\n###B#C#B#D####\n  #A#D#C#A#\n  #########";
+        assert_eq!(
+            located(s),
+            (3, 14, "expected the end of the line, found `#`".to_owned())
+        );
+
+        let s = "#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#";
+        assert_eq!(located(s).0, 4);
+        assert_eq!(located(s).1, 12);
+        assert!(located(s).2.ends_with("found the end of the input"));
+
+        let s = "#############\n#.B.........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########";
+        assert_eq!(
+            located(s),
+            (
+                3,
+                8,
+                "expected two amphipods of type B, found a third".to_owned()
+            )
+        );
+    }
+
     #[test]
     fn test_inputs_01() {
         let s = "  #############
@@ -355,11 +536,11 @@ mod tests {
 
         let input = parse(s).unwrap();
 
-        let mut state = to_map(input);
+        let state = to_map(input);
 
-        let res = solve_01(&mut state, 2);
+        let res = solve_01(&state, 2);
 
-        assert_eq!(res, 8);
+        assert_eq!(res, Some(8));
 
         let s = "  #############
                         #.....D.D.A.#
@@ -369,11 +550,11 @@ mod tests {
 
         let input = parse(s).unwrap();
 
-        let mut state = to_map(input);
+        let state = to_map(input);
 
-        let res = solve_01(&mut state, 2);
+        let res = solve_01(&state, 2);
 
-        assert_eq!(res, 7008);
+        assert_eq!(res, Some(7008));
 
         let s = "  #############
                         #.....D.....#
@@ -383,11 +564,11 @@ mod tests {
 
         let input = parse(s).unwrap();
 
-        let mut state = to_map(input);
+        let state = to_map(input);
 
-        let res = solve_01(&mut state, 2);
+        let res = solve_01(&state, 2);
 
-        assert_eq!(res, 9011);
+        assert_eq!(res, Some(9011));
 
         let s = "  #############
         #...........#
@@ -397,11 +578,11 @@ mod tests {
 
         let input = parse(s).unwrap();
 
-        let mut state = to_map(input);
+        let state = to_map(input);
 
-        let res = solve_01(&mut state, 2);
+        let res = solve_01(&state, 2);
 
-        assert_eq!(res, 12521);
+        assert_eq!(res, Some(12521));
     }
 
     #[test]
@@ -0,0 +1,243 @@
+use std::fmt;
+
+use crate::error::Result;
+use crate::rng::Rng;
+use crate::{d06, d14, d15, d22};
+
+/// A question answered both by a naive implementation, simple enough to be trusted, and by the
+/// optimized one used by the solution.
+pub struct Pair {
+    pub day: u8,
+    pub name: &'static str,
+    pub naive: fn(&str) -> Result<String>,
+    pub optimized: fn(&str) -> Result<String>,
+    /// Random input of the given size.
+    pub generate: fn(&mut Rng, usize) -> String,
+    /// Largest input size the naive implementation still answers quickly.
+    pub max_size: usize,
+}
+
+/// Every pair of implementations, in calendar order.
+pub static PAIRS: &[Pair] = &[
+    Pair {
+        day: 6,
+        name: "lanternfish after 80 days",
+        naive: |input| Ok(d06::simulate(80, &d06::parse(input)?).to_string()),
+        optimized: |input| Ok(d06::solve_part1(&d06::parse(input)?)?.to_string()),
+        generate: d06::generate,
+        max_size: 40,
+    },
+    Pair {
+        day: 14,
+        name: "polymer after 10 steps",
+        naive: |input| {
+            let (template, rules) = d14::parse(input)?;
+            Ok(d14::solve_01(10, &template, &rules)?.to_string())
+        },
+        optimized: |input| {
+            let (template, rules) = d14::parse(input)?;
+            Ok(d14::solve_02(10, &template, &rules)?.to_string())
+        },
+        generate: d14::generate,
+        max_size: 20,
+    },
+    Pair {
+        day: 15,
+        name: "lowest total risk",
+        naive: |input| Ok(d15::relaxed_total_risk(&d15::parse(input)?)?.to_string()),
+        optimized: |input| Ok(d15::solve_part1(&d15::parse(input)?)?.to_string()),
+        generate: d15::generate,
+        max_size: 12,
+    },
+    Pair {
+        day: 15,
+        name: "lowest total risk of the full map",
+        naive: |input| {
+            let risc_map = d15::quintuple(&d15::parse(input)?);
+            Ok(d15::relaxed_total_risk(&risc_map)?.to_string())
+        },
+        optimized: |input| Ok(d15::solve_part2(&d15::parse(input)?)?.to_string()),
+        generate: d15::generate,
+        max_size: 6,
+    },
+    Pair {
+        day: 22,
+        name: "lit cubes of the initialization area",
+        naive: |input| Ok(d22::solve_01(&d22::parse(input)?).to_string()),
+        optimized: |input| {
+            let area = d22::initialization_area();
+            let steps = d22::parse(input)?
+                .into_iter()
+                .filter_map(|step| {
+                    let cuboid = step.cuboid.intersection(&area)?;
+                    Some(d22::Step { cuboid, ..step })
+                })
+                .collect::<Vec<_>>();
+            Ok(d22::solve_part2(&steps)?.to_string())
+        },
+        generate: small_reboot,
+        max_size: 12,
+    },
+];
+
+/// Reboot steps close to the origin, so that switching every single cube stays cheap (the real
+/// generator mostly covers the whole initialization area).
+fn small_reboot(rng: &mut Rng, size: usize) -> String {
+    let mut lines = vec![];
+    for ix in 0..size.max(1) {
+        let mut range = || {
+            let from = rng.between(-10, 8);
+            format!("{}..{}", from, rng.between(from, 10))
+        };
+        let (x, y, z) = (range(), range(), range());
+        let on = ix == 0 || !rng.one_in(3);
+
+        lines.push(format!(
+            "{} x={},y={},z={}",
+            if on { "on" } else { "off" },
+            x,
+            y,
+            z
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Answer of one implementation, any two errors counting as the same answer.
+pub type Outcome = std::result::Result<String, String>;
+
+/// Input on which the two implementations of a pair disagree.
+#[derive(Debug)]
+pub struct Mismatch {
+    pub seed: u64,
+    /// Size the input was generated with, before shrinking it.
+    pub size: usize,
+    pub input: String,
+    pub naive: Outcome,
+    pub optimized: Outcome,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |outcome: &Outcome| match outcome {
+            Ok(answer) => answer.clone(),
+            Err(err) => format!("error: {}", err),
+        };
+        writeln!(
+            f,
+            "naive {} <> optimized {} (seed {}, size {}) on:",
+            show(&self.naive),
+            show(&self.optimized),
+            self.seed,
+            self.size
+        )?;
+        write!(f, "{}", self.input)
+    }
+}
+
+impl Pair {
+    fn outcomes(&self, input: &str) -> (Outcome, Outcome) {
+        let outcome = |res: Result<String>| res.map_err(|err| err.to_string());
+        (
+            outcome((self.naive)(input)),
+            outcome((self.optimized)(input)),
+        )
+    }
+
+    fn disagree(&self, input: &str) -> bool {
+        match self.outcomes(input) {
+            (Ok(naive), Ok(optimized)) => naive != optimized,
+            (Err(_), Err(_)) => false,
+            _ => true,
+        }
+    }
+
+    /// Compares both implementations on inputs of growing sizes up to `max_size`, `seeds` of each
+    /// size, and returns the first mismatch shrunk to as few lines as still disagree. Returns
+    /// `None` when they agreed on all of them.
+    pub fn find_mismatch(&self, seeds: u64, max_size: usize) -> Option<Mismatch> {
+        for size in 1..=max_size {
+            for seed in 0..seeds {
+                let input = (self.generate)(&mut Rng::new(seed), size);
+                if self.disagree(&input) {
+                    let input = self.shrink(input);
+                    let (naive, optimized) = self.outcomes(&input);
+                    return Some(Mismatch {
+                        seed,
+                        size,
+                        input,
+                        naive,
+                        optimized,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Drops lines of a disagreeing input (or the comma separated items of a single line one)
+    /// for as long as the implementations keep disagreeing.
+    fn shrink(&self, input: String) -> String {
+        let separator = if input.trim().contains('\n') {
+            "\n"
+        } else {
+            ","
+        };
+        let mut pieces = input
+            .trim()
+            .split(separator)
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+
+        let mut ix = 0;
+        while ix < pieces.len() && pieces.len() > 1 {
+            let mut candidate = pieces.clone();
+            candidate.remove(ix);
+            if self.disagree(&candidate.join(separator)) {
+                pieces = candidate;
+            } else {
+                ix += 1;
+            }
+        }
+
+        pieces.join(separator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_pairs_agree() {
+        for pair in PAIRS {
+            if let Some(mismatch) = pair.find_mismatch(3, pair.max_size) {
+                panic!("D{:02} {}: {}", pair.day, pair.name, mismatch);
+            }
+        }
+    }
+
+    #[test]
+    fn test_smallest_mismatch() {
+        // off by one as soon as there are fish with timers 1 and 5
+        let pair = Pair {
+            day: 6,
+            name: "broken lanternfish",
+            naive: |input| Ok(d06::parse(input)?.len().to_string()),
+            optimized: |input| {
+                let fish = d06::parse(input)?;
+                let broken = fish.contains(&1) && fish.contains(&5);
+                Ok((fish.len() + usize::from(broken)).to_string())
+            },
+            generate: d06::generate,
+            max_size: 20,
+        };
+
+        let mismatch = pair.find_mismatch(5, pair.max_size).unwrap();
+        assert!(mismatch.size >= 2);
+        assert!(["1,5", "5,1"].contains(&mismatch.input.as_str()));
+        assert_eq!(mismatch.naive, Ok("2".to_owned()));
+        assert_eq!(mismatch.optimized, Ok("3".to_owned()));
+    }
+}
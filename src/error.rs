@@ -0,0 +1,323 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Everything that can go wrong while parsing an input or solving a puzzle.
+#[derive(Debug)]
+pub enum Error {
+    /// Input does not have the expected format, positions are 1-based.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Input is well formed, but has no answer.
+    Unsolvable(String),
+    /// A rule the solver needs (insertion pair, segment mapping, ...) is not in the input.
+    MissingRule(String),
+    /// An intermediate value does not fit its integer type.
+    Overflow(String),
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "Parse error at line {}, column {}: {}",
+                line, column, message
+            ),
+            Error::Unsolvable(message) => write!(f, "Unsolvable input: {}", message),
+            Error::MissingRule(message) => write!(f, "Missing rule: {}", message),
+            Error::Overflow(message) => write!(f, "Overflow: {}", message),
+            Error::Io(err) => write!(f, "Error while reading input: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl Error {
+    /// Parse error located at `fragment`, which should be a slice of `input`.
+    pub fn parse_at(input: &str, fragment: &str, message: impl Into<String>) -> Error {
+        let (line, column) = position(input, fragment);
+        Error::Parse {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Snippet of the input line a parse error points at, `None` for other errors.
+    pub fn snippet(&self, input: &str) -> Option<String> {
+        match self {
+            Error::Parse { line, column, .. } => Some(snippet(input, *line, *column)),
+            _ => None,
+        }
+    }
+
+    /// Parse errors become [`Located`] ones, with the snippet of `input` they point at.
+    pub fn locate(self, input: &str) -> anyhow::Error {
+        match self.snippet(input) {
+            Some(snippet) => anyhow::Error::new(Located {
+                error: self,
+                snippet,
+            }),
+            None => anyhow::Error::new(self),
+        }
+    }
+
+    /// Moves a parse error located within a single line to line `line` of the whole input.
+    pub fn at_line(self, line: usize) -> Error {
+        match self {
+            Error::Parse {
+                line: line_in_fragment,
+                column,
+                message,
+            } => Error::Parse {
+                line: line + line_in_fragment - 1,
+                column,
+                message,
+            },
+            err => err,
+        }
+    }
+}
+
+/// Parse error carrying the input line it points at, see [`Error::snippet`].
+#[derive(Debug)]
+pub struct Located {
+    pub error: Error,
+    pub snippet: String,
+}
+
+impl Display for Located {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for Located {}
+
+/// `err` with its causes, followed by the indented input line of a located parse error.
+pub fn describe(err: &anyhow::Error) -> String {
+    match err.downcast_ref::<Located>() {
+        Some(located) => {
+            let snippet = located
+                .snippet
+                .lines()
+                .map(|line| format!("\n    {}", line));
+            format!("{:#}{}", err, snippet.collect::<String>())
+        }
+        None => format!("{:#}", err),
+    }
+}
+
+/// Parse error at `rest`, a slice of `input` which does not start with `what`.
+pub fn expected(input: &str, rest: &str, what: &str) -> Error {
+    let found = match rest.chars().next() {
+        Some(c) => format!("`{}`", c),
+        None => "the end of the line".to_owned(),
+    };
+    Error::parse_at(input, rest, format!("expected {}, found {}", what, found))
+}
+
+/// `rest` without `prefix`, or a parse error naming `prefix` when it does not start with it.
+pub fn strip_prefix<'a>(input: &str, rest: &'a str, prefix: &str) -> Result<&'a str> {
+    rest.strip_prefix(prefix)
+        .ok_or_else(|| expected(input, rest, &format!("`{}`", prefix)))
+}
+
+/// Nothing but `rest` left, or a parse error when the line goes on.
+pub fn end_of_line(input: &str, rest: &str) -> Result<()> {
+    if !rest.is_empty() {
+        return Err(expected(input, rest, "the end of the line"));
+    }
+    Ok(())
+}
+
+/// Integer at the start of `rest`, possibly negative, and what follows it.
+pub fn leading_number<'a, T>(input: &str, rest: &'a str, what: &str) -> Result<(T, &'a str)>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let unsigned = rest.strip_prefix('-').unwrap_or(rest);
+    let digits = unsigned
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(unsigned.len());
+    if digits == 0 {
+        return Err(expected(input, unsigned, "a digit"));
+    }
+    let (number, after) = rest.split_at(rest.len() - unsigned.len() + digits);
+    Ok((parse_at(input, number, what)?, after))
+}
+
+/// Longest part of an input line shown in a snippet, in characters.
+const SNIPPET_WIDTH: usize = 72;
+
+/// Line `line` of `input`, numbered, with a caret under `column` (both 1-based). Long lines are
+/// cut around the column.
+pub fn snippet(input: &str, line: usize, column: usize) -> String {
+    let text = input.lines().nth(line - 1).unwrap_or_default();
+    let chars = text.chars().map(|c| if c == '\t' { ' ' } else { c });
+    let chars = chars.collect::<Vec<_>>();
+
+    let column = column.max(1) - 1;
+    let start = if chars.len() > SNIPPET_WIDTH {
+        column
+            .saturating_sub(SNIPPET_WIDTH / 2)
+            .min(chars.len() - SNIPPET_WIDTH)
+    } else {
+        0
+    };
+    let end = chars.len().min(start + SNIPPET_WIDTH);
+
+    let mut shown = chars[start..end].iter().collect::<String>();
+    let mut caret = column - start;
+    if start > 0 {
+        shown.insert_str(0, "...");
+        caret += 3;
+    }
+    if end < chars.len() {
+        shown.push_str("...");
+    }
+
+    let number = line.to_string();
+    format!(
+        "{} | {}\n{} | {}^",
+        number,
+        shown,
+        " ".repeat(number.len()),
+        " ".repeat(caret)
+    )
+}
+
+/// Line and column (both 1-based) where `fragment` starts inside `input`. Fragments that are not
+/// a slice of `input` are placed at its end.
+pub fn position(input: &str, fragment: &str) -> (usize, usize) {
+    let offset = (fragment.as_ptr() as usize)
+        .checked_sub(input.as_ptr() as usize)
+        .filter(|&offset| offset <= input.len() && input.is_char_boundary(offset))
+        .unwrap_or(input.len());
+
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+/// Parses `fragment`, a slice of `input`, reporting its position when it is not a valid `T`.
+pub fn parse_at<T>(input: &str, fragment: &str, what: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    fragment.parse::<T>().map_err(|err| {
+        Error::parse_at(
+            input,
+            fragment,
+            format!("bad {} `{}`: {}", what, fragment, err),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let input = "forward 5\ndown x\nup 3";
+
+        assert_eq!(position(input, &input[0..7]), (1, 1));
+        assert_eq!(position(input, &input[8..9]), (1, 9));
+        assert_eq!(position(input, &input[15..16]), (2, 6));
+        assert_eq!(position(input, &input[input.len()..]), (3, 5));
+        assert_eq!(position(input, "elsewhere"), (3, 5));
+    }
+
+    #[test]
+    fn test_snippet() {
+        let input = "forward 5\ndown x\nup 3";
+        let err = parse_at::<u32>(input, &input[15..16], "value").unwrap_err();
+        assert_eq!(err.snippet(input).unwrap(), "2 | down x\n  |      ^");
+        assert_eq!(snippet(input, 3, 5), "3 | up 3\n  |     ^");
+        assert_eq!(snippet(input, 4, 1), "4 | \n  | ^");
+        assert!(Error::Unsolvable(String::new()).snippet(input).is_none());
+
+        let long = format!("{}x{}", "#".repeat(100), "#".repeat(100));
+        let shown = snippet(&long, 1, 101);
+        let (text, caret) = shown.split_once('\n').unwrap();
+        assert!(text.starts_with("1 | ...#") && text.ends_with("#..."));
+        assert_eq!(text.find('x'), caret.find('^'));
+    }
+
+    #[test]
+    fn test_expected() {
+        let input = "x=-12..y";
+
+        let rest = strip_prefix(input, input, "x=").unwrap();
+        let (number, rest) = leading_number::<i32>(input, rest, "bound").unwrap();
+        assert_eq!((number, rest), (-12, "..y"));
+        let rest = strip_prefix(input, rest, "..").unwrap();
+
+        let err = leading_number::<i32>(input, rest, "bound").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parse error at line 1, column 8: expected a digit, found `y`"
+        );
+        let err = strip_prefix(input, &input[8..], ",").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parse error at line 1, column 9: expected `,`, found the end of the line"
+        );
+    }
+
+    #[test]
+    fn test_parse_at() {
+        let input = "forward 5\ndown x";
+
+        assert_eq!(parse_at::<u32>(input, &input[8..9], "value").unwrap(), 5);
+
+        let err = parse_at::<u32>(input, &input[15..16], "value").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 6,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Parse error at line 2, column 6: bad value `x`: invalid digit found in string"
+        );
+    }
+}
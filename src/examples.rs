@@ -0,0 +1,123 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+use crate::answers::Answers;
+
+/// Directory of the puzzle examples, whatever the working directory is.
+pub fn default_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("inputs")
+        .join("examples")
+}
+
+/// Example input `NN_name.txt` of day `NN`, with its expected answers in `NN_name.answers`
+/// (in the format of `answers.txt`).
+#[derive(Debug)]
+pub struct Example {
+    pub day: u8,
+    pub name: String,
+    pub input: PathBuf,
+    pub answers: Answers,
+}
+
+/// Every example in `dir`, ordered by file name.
+pub fn discover(dir: &Path) -> anyhow::Result<Vec<Example>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Error while reading examples in {}", dir.display()))?;
+
+    let mut inputs = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "txt") {
+            inputs.push(path);
+        }
+    }
+    inputs.sort();
+
+    inputs.into_iter().map(|input| load(&input)).collect()
+}
+
+fn load(input: &Path) -> anyhow::Result<Example> {
+    let name = input
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_owned();
+    let day = name
+        .split_once('_')
+        .and_then(|(day, _)| day.parse::<u8>().ok())
+        .with_context(|| format!("Example {} must be named like 01_example.txt", name))?;
+
+    let answers_path = input.with_extension("answers");
+    let answers = Answers::load(&answers_path)?;
+    if let Some((other, part)) = answers.keys().find(|&(other, _)| other != day) {
+        bail!(
+            "{} lists an answer of day {} part {} for an example of day {}",
+            answers_path.display(),
+            other,
+            part,
+            day
+        );
+    }
+
+    Ok(Example {
+        day,
+        name,
+        input: input.to_owned(),
+        answers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::answers::Verdict;
+    use crate::registry;
+
+    #[test]
+    fn test_examples() {
+        let examples = discover(&default_dir()).unwrap();
+        assert!(!examples.is_empty());
+
+        let mut failures = vec![];
+        for example in &examples {
+            let solution = registry::find(example.day).unwrap();
+            let input = std::fs::read_to_string(&example.input).unwrap();
+            let parsed = match solution.parse(&input) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    failures.push(format!("{}: {:#}", example.name, err));
+                    continue;
+                }
+            };
+
+            for part in [1, 2] {
+                let Some(expected) = example.answers.get(example.day, part) else {
+                    continue;
+                };
+                if expected.answer.is_none() {
+                    continue;
+                }
+                let answer = match part {
+                    1 => parsed.part_01(),
+                    _ => parsed.part_02(),
+                };
+                match answer {
+                    Ok(answer) => {
+                        if expected.check(&answer) != Verdict::Correct {
+                            failures.push(format!(
+                                "{} part {}: got {}, expected {:?}",
+                                example.name, part, answer, expected.answer
+                            ));
+                        }
+                    }
+                    Err(err) => failures.push(format!("{} part {}: {:#}", example.name, part, err)),
+                }
+            }
+        }
+
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,406 @@
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Point (or offset) on an integer plane.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point2 {
+    pub const ORIGIN: Point2 = Point2 { x: 0, y: 0 };
+
+    pub const fn new(x: i32, y: i32) -> Point2 {
+        Point2 { x, y }
+    }
+
+    pub fn manhattan(self, other: Point2) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Number of king moves between the points.
+    pub fn chebyshev(self, other: Point2) -> u32 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Sign of both coordinates, the single step pointing the same way.
+    pub fn signum(self) -> Point2 {
+        Point2::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl Add for Point2 {
+    type Output = Point2;
+
+    fn add(self, other: Point2) -> Point2 {
+        Point2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point2 {
+    fn add_assign(&mut self, other: Point2) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point2 {
+    type Output = Point2;
+
+    fn sub(self, other: Point2) -> Point2 {
+        Point2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point2 {
+    fn sub_assign(&mut self, other: Point2) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Point2 {
+    type Output = Point2;
+
+    fn neg(self) -> Point2 {
+        Point2::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i32> for Point2 {
+    type Output = Point2;
+
+    fn mul(self, k: i32) -> Point2 {
+        Point2::new(self.x * k, self.y * k)
+    }
+}
+
+impl Display for Point2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+/// Position in integer space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// Displacement between two [`Point3`]s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vec3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Point3 {
+    pub const ORIGIN: Point3 = Point3 { x: 0, y: 0, z: 0 };
+
+    pub const fn new(x: i32, y: i32, z: i32) -> Point3 {
+        Point3 { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Point3) -> u32 {
+        (self - other).manhattan_len()
+    }
+
+    pub fn chebyshev(self, other: Point3) -> u32 {
+        let d = self - other;
+        d.x.unsigned_abs()
+            .max(d.y.unsigned_abs())
+            .max(d.z.unsigned_abs())
+    }
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3 { x: 0, y: 0, z: 0 };
+
+    pub const fn new(x: i32, y: i32, z: i32) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+
+    pub fn manhattan_len(self) -> u32 {
+        self.x.unsigned_abs() + self.y.unsigned_abs() + self.z.unsigned_abs()
+    }
+}
+
+impl Add<Vec3> for Point3 {
+    type Output = Point3;
+
+    fn add(self, v: Vec3) -> Point3 {
+        Point3::new(self.x + v.x, self.y + v.y, self.z + v.z)
+    }
+}
+
+impl Sub<Vec3> for Point3 {
+    type Output = Point3;
+
+    fn sub(self, v: Vec3) -> Point3 {
+        Point3::new(self.x - v.x, self.y - v.y, self.z - v.z)
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Point3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<i32> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, k: i32) -> Vec3 {
+        Vec3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+impl Display for Point3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+/// Axis-aligned rectangle, both corners included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub min: Point2,
+    pub max: Point2,
+}
+
+impl Rect {
+    /// Rectangle spanning both corners, whichever order they come in.
+    pub fn new(a: Point2, b: Point2) -> Rect {
+        Rect {
+            min: Point2::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point2::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    pub fn contains(&self, p: Point2) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+}
+
+/// Axis-aligned box of unit cubes, both corners included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cuboid {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Cuboid {
+    /// Cuboid spanning both corners, whichever order they come in.
+    pub fn new(a: Point3, b: Point3) -> Cuboid {
+        Cuboid {
+            min: Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    pub fn contains(&self, p: Point3) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    /// Cubes both cuboids share, if any.
+    pub fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
+        let min = Point3::new(
+            self.min.x.max(other.min.x),
+            self.min.y.max(other.min.y),
+            self.min.z.max(other.min.z),
+        );
+        let max = Point3::new(
+            self.max.x.min(other.max.x),
+            self.max.y.min(other.max.y),
+            self.max.z.min(other.max.z),
+        );
+        (min.x <= max.x && min.y <= max.y && min.z <= max.z).then_some(Cuboid { min, max })
+    }
+
+    /// Number of unit cubes, `None` when it doesn't fit a `u64`.
+    pub fn volume(&self) -> Option<u64> {
+        let side = |min: i32, max: i32| max.abs_diff(min) as u64 + 1;
+        side(self.min.x, self.max.x)
+            .checked_mul(side(self.min.y, self.max.y))?
+            .checked_mul(side(self.min.z, self.max.z))
+    }
+}
+
+/// Rotation by multiples of 90 degrees, as a matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rotation(pub [[i32; 3]; 3]);
+
+impl Rotation {
+    /// All 24 orientations of a cube, the identity first.
+    // https://www.euclideanspace.com/maths/algebra/matrix/transforms/examples/index.htm
+    pub const ALL: [Rotation; 24] = [
+        // 0 - normal
+        Rotation([[1, 0, 0], [0, 1, 0], [0, 0, 1]]),
+        // 1 - Rotate 0,90,0
+        Rotation([[0, 0, 1], [0, 1, 0], [-1, 0, 0]]),
+        // 2 - rotate 0,180,0
+        Rotation([[-1, 0, 0], [0, 1, 0], [0, 0, -1]]),
+        // 3 - rotate 0,270,0
+        Rotation([[0, 0, -1], [0, 1, 0], [1, 0, 0]]),
+        // 4 - rotate 0,0,90
+        Rotation([[0, -1, 0], [1, 0, 0], [0, 0, 1]]),
+        // 5 - rotate 0,90,90
+        Rotation([[0, 0, 1], [1, 0, 0], [0, 1, 0]]),
+        // 6 - rotate 0,180,90
+        Rotation([[0, 1, 0], [1, 0, 0], [0, 0, -1]]),
+        // 7 - rotate 0,270,90
+        Rotation([[0, 0, -1], [1, 0, 0], [0, -1, 0]]),
+        // 8 - rotate 0,0,270
+        Rotation([[0, 1, 0], [-1, 0, 0], [0, 0, 1]]),
+        // 9 - rotate 0,90,270
+        Rotation([[0, 0, 1], [-1, 0, 0], [0, -1, 0]]),
+        // 10 - rotate 0,180,270
+        Rotation([[0, -1, 0], [-1, 0, 0], [0, 0, -1]]),
+        // 11 - rotate 0,270,270
+        Rotation([[0, 0, -1], [-1, 0, 0], [0, 1, 0]]),
+        // 12 - rotate 90,0,0
+        Rotation([[1, 0, 0], [0, 0, -1], [0, 1, 0]]),
+        // 13 - rotate 90,90,0
+        Rotation([[0, 1, 0], [0, 0, -1], [-1, 0, 0]]),
+        // 14 - rotate 90,180,0
+        Rotation([[-1, 0, 0], [0, 0, -1], [0, -1, 0]]),
+        // 15 - rotate 90,270,0
+        Rotation([[0, -1, 0], [0, 0, -1], [1, 0, 0]]),
+        // 16 - rotate 180,0,0
+        Rotation([[1, 0, 0], [0, -1, 0], [0, 0, -1]]),
+        // 17 - rotate 180,90,0
+        Rotation([[0, 0, -1], [0, -1, 0], [-1, 0, 0]]),
+        // 18 - rotate 180,180,0
+        Rotation([[-1, 0, 0], [0, -1, 0], [0, 0, 1]]),
+        // 19 - rotate 180,270,0
+        Rotation([[0, 0, 1], [0, -1, 0], [1, 0, 0]]),
+        // 20 - rotate 270,0,0
+        Rotation([[1, 0, 0], [0, 0, 1], [0, -1, 0]]),
+        // 21 - rotate 270,90,0
+        Rotation([[0, -1, 0], [0, 0, 1], [-1, 0, 0]]),
+        // 22 - rotate 270,180,0
+        Rotation([[-1, 0, 0], [0, 0, 1], [0, 1, 0]]),
+        // 23 - rotate 270,270,0
+        Rotation([[0, 1, 0], [0, 0, 1], [1, 0, 0]]),
+    ];
+
+    /// The opposite rotation, which for a rotation matrix is its transpose.
+    pub fn inverse(&self) -> Rotation {
+        let m = &self.0;
+        Rotation([
+            [m[0][0], m[1][0], m[2][0]],
+            [m[0][1], m[1][1], m[2][1]],
+            [m[0][2], m[1][2], m[2][2]],
+        ])
+    }
+
+    pub fn apply(&self, v: Vec3) -> Vec3 {
+        let m = &self.0;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+
+    /// Rotates `p` around the origin.
+    pub fn apply_point(&self, p: Point3) -> Point3 {
+        Point3::ORIGIN + self.apply(p - Point3::ORIGIN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let a = Point2::new(1, -2);
+        let b = Point2::new(-3, 5);
+
+        assert_eq!(a.manhattan(b), 11);
+        assert_eq!(a.chebyshev(b), 7);
+        assert_eq!((b - a).signum(), Point2::new(-1, 1));
+        assert_eq!(a + (b - a).signum() * 2, Point2::new(-1, 0));
+
+        let p = Point3::new(1105, -1205, 1229);
+        let q = Point3::new(-92, -2380, -20);
+        assert_eq!(p.manhattan(q), 3621);
+        assert_eq!(p.chebyshev(q), 1249);
+        assert_eq!(q + (p - q), p);
+    }
+
+    #[test]
+    fn test_rotations() {
+        let v = Vec3::new(3, 5, 7);
+        let mut rotated = vec![];
+        for rot in Rotation::ALL.iter() {
+            assert_eq!(rot.inverse().apply(rot.apply(v)), v);
+            rotated.push(rot.apply(v));
+        }
+
+        rotated.sort();
+        rotated.dedup();
+        assert_eq!(rotated.len(), 24);
+    }
+
+    #[test]
+    fn test_cuboids() {
+        let a = Cuboid::new(Point3::new(10, 10, 10), Point3::new(12, 12, 12));
+        let b = Cuboid::new(Point3::new(13, 13, 13), Point3::new(11, 11, 11));
+
+        assert_eq!(a.volume(), Some(27));
+        assert_eq!(
+            a.intersection(&b),
+            Some(Cuboid::new(
+                Point3::new(11, 11, 11),
+                Point3::new(12, 12, 12)
+            ))
+        );
+        assert!(a.contains(Point3::new(12, 10, 11)));
+
+        // touching faces still share a layer of cubes
+        let c = Cuboid::new(Point3::new(12, 0, 0), Point3::new(20, 20, 20));
+        assert_eq!(a.intersection(&c).and_then(|i| i.volume()), Some(9));
+
+        let far = Cuboid::new(Point3::new(13, 0, 0), Point3::new(20, 20, 20));
+        assert_eq!(a.intersection(&far), None);
+
+        let huge = Cuboid::new(
+            Point3::new(i32::MIN, i32::MIN, i32::MIN),
+            Point3::new(i32::MAX, i32::MAX, i32::MAX),
+        );
+        assert_eq!(huge.volume(), None);
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+use crate::error::{Error, Result};
+
+/// Position in a grid, `(x, y)` with `(0, 0)` in the top left corner.
+pub type Pos = (usize, usize);
+
+const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Rectangular grid of cells stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Grid of the given size with every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Grid made of `rows`, or `None` when they don't all have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        let height = if width == 0 { 0 } else { rows.len() };
+        Some(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses one cell per character, rows being the (trimmed) lines of `input`.
+    pub fn parse_with(
+        input: &str,
+        what: &str,
+        cell: impl Fn(char) -> Option<T>,
+    ) -> Result<Grid<T>> {
+        let mut rows: Vec<Vec<T>> = vec![];
+        for line in input.lines() {
+            let line = line.trim();
+            let row = line
+                .char_indices()
+                .map(|(ix, c)| {
+                    cell(c).ok_or_else(|| {
+                        Error::parse_at(
+                            input,
+                            &line[ix..],
+                            format!("`{}` is not a valid {}", c, what),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(first) = rows.first().filter(|first| first.len() != row.len()) {
+                return Err(Error::parse_at(
+                    input,
+                    line,
+                    format!("expected {} cells like the first row", first.len()),
+                ));
+            }
+            rows.push(row);
+        }
+        // rows all have the same length by now
+        Ok(Grid::from_rows(rows).unwrap_or_default())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.1 * self.width + pos.0])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.1 * self.width + pos.0])
+        } else {
+            None
+        }
+    }
+
+    /// Cell at `pos` moved by `delta`, if that is still inside the grid.
+    pub fn offset(&self, (x, y): Pos, (dx, dy): (isize, isize)) -> Option<Pos> {
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(pos).then_some(pos)
+    }
+
+    /// All positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |ix| (ix % width, ix / width))
+    }
+
+    /// All cells with their positions, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Orthogonal neighbours of `pos` inside the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |&delta| self.offset(pos, delta))
+    }
+
+    /// Orthogonal and diagonal neighbours of `pos` inside the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |&delta| self.offset(pos, delta))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<u32> {
+    /// Grid of single digit numbers, like a height or risk map.
+    pub fn parse_digits(input: &str) -> Result<Grid<u32>> {
+        Grid::parse_with(input, "digit", |c| c.to_digit(10))
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(input: &str) -> Result<Grid<char>> {
+        Grid::parse_with(input, "character", Some)
+    }
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Grid {
+            width: 0,
+            height: 0,
+            cells: vec![],
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        match self.get(pos) {
+            Some(cell) => cell,
+            None => panic!(
+                "{:?} is outside of a {}x{} grid",
+                pos, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(pos) {
+            Some(cell) => cell,
+            None => panic!("{:?} is outside of a {}x{} grid", pos, width, height),
+        }
+    }
+}
+
+/// One line per row, cells written next to each other.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid = Grid::parse_digits(
+            "2199
+        3987",
+        )
+        .unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (4, 2));
+        assert_eq!(grid[(1, 0)], 1);
+        assert_eq!(grid[(0, 1)], 3);
+        assert_eq!(grid.get((4, 0)), None);
+        assert_eq!(grid.to_string(), "2199\n3987");
+
+        let err = Grid::parse_digits("219\n3x8").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 2,
+                ..
+            }
+        ));
+
+        let err = Grid::parse_chars("#.#\n#.").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                line: 2,
+                column: 1,
+                ..
+            }
+        ));
+
+        assert!(Grid::parse_chars("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 2, 0);
+
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours4((1, 1)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1), (2, 1)]
+        );
+        assert_eq!(
+            grid.neighbours8((2, 0)).collect::<Vec<_>>(),
+            [(1, 0), (1, 1), (2, 1)]
+        );
+        assert_eq!(grid.neighbours8((1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let grid = Grid::from_rows(vec![vec!['a', 'b'], vec!['c', 'd']]).unwrap();
+
+        assert_eq!(
+            grid.iter().collect::<Vec<_>>(),
+            [
+                ((0, 0), &'a'),
+                ((1, 0), &'b'),
+                ((0, 1), &'c'),
+                ((1, 1), &'d')
+            ]
+        );
+        assert!(Grid::from_rows(vec![vec![1], vec![2, 3]]).is_none());
+    }
+}
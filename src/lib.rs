@@ -0,0 +1,46 @@
+//! Solutions to [Advent of Code 2021](https://adventofcode.com/2021).
+//!
+//! Every `dNN` module exposes the domain types of its day together with `parse`, `solve_part1`,
+//! `solve_part2` and `generate` (random inputs), and a `DayNN` type implementing
+//! [`solution::Solution`]. The [`registry`] lists all of them for the runner.
+
+pub mod answers;
+pub mod bench;
+pub mod d01;
+pub mod d02;
+pub mod d03;
+pub mod d04;
+pub mod d05;
+pub mod d06;
+pub mod d07;
+pub mod d08;
+pub mod d09;
+pub mod d10;
+pub mod d11;
+pub mod d12;
+pub mod d13;
+pub mod d14;
+pub mod d15;
+pub mod d16;
+pub mod d17;
+pub mod d18;
+pub mod d19;
+pub mod d20;
+pub mod d21;
+pub mod d22;
+pub mod d23;
+pub mod differential;
+pub mod error;
+pub mod examples;
+pub mod geom;
+pub mod grid;
+pub mod output;
+pub mod pool;
+pub mod registry;
+pub mod render;
+pub mod rng;
+pub mod search;
+pub mod solution;
+pub mod step;
+pub mod watch;
+pub mod window;
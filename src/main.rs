@@ -1,94 +1,565 @@
-mod d01;
-mod d02;
-mod d03;
-mod d04;
-mod d05;
-mod d06;
-mod d07;
-mod d08;
-mod d09;
-mod d10;
-mod d11;
-mod d12;
-mod d13;
-mod d14;
-mod d15;
-mod d16;
-mod d17;
-mod d18;
-mod d19;
-mod d20;
-mod d21;
-mod d22;
-mod d23;
+mod cli;
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+
+use aoc_2021::answers::{self, Answers, Expected, Verdict};
+use aoc_2021::bench::{self, Baseline};
+use aoc_2021::d02::{self, Mission};
+use aoc_2021::differential;
+use aoc_2021::error::describe;
+use aoc_2021::output::{Format, PartResult};
+use aoc_2021::rng::Rng;
+use aoc_2021::solution::{DynSolution, Parsed};
+use aoc_2021::watch::{self, Watcher};
+use aoc_2021::window;
+use aoc_2021::{d01, examples, pool, registry, render, step};
+
+use cli::{
+    BenchArgs, Command, DiffArgs, GenerateArgs, MissionArgs, RenderArgs, RunArgs, StatsArgs,
+    StepArgs, SweepArgs, VerifyArgs, WatchArgs,
+};
 
 fn main() {
-    println!("D01 Part 1 = {:?}", d01::part_01());
-    println!("D01 Part 2 = {:?}", d01::part_02());
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("Error: {:#}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let res = match command {
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+        Command::Run(args) => run(&args),
+        Command::Verify(args) => verify(&args),
+        Command::Bench(args) => bench(&args),
+        Command::Generate(args) => generate(&args),
+        Command::Diff(args) => diff(&args),
+        Command::Render(args) => render(&args),
+        Command::Step(args) => step(&args),
+        Command::Watch(args) => watch(&args),
+        Command::Sweep(args) => sweep(&args),
+        Command::Stats(args) => stats(&args),
+        Command::Mission(args) => mission(&args),
+    };
+
+    if let Err(err) = res {
+        eprintln!("Error: {}", describe(&err));
+        std::process::exit(1);
+    }
+}
+
+/// Solutions of the selected days. A single day must be solved, unsolved days of a range or list
+/// are skipped with a notice.
+fn selected_solutions(days: &Option<Vec<u8>>) -> anyhow::Result<Vec<&'static dyn DynSolution>> {
+    let days = match days {
+        None => return Ok(registry::SOLUTIONS.to_vec()),
+        Some(days) => days,
+    };
+    if let [day] = days[..] {
+        let solution = registry::find(day).with_context(|| format!("Day {} is not solved", day))?;
+        return Ok(vec![solution]);
+    }
+
+    let (solved, unsolved): (Vec<u8>, Vec<u8>) =
+        days.iter().partition(|&&day| registry::find(day).is_some());
+    if solved.is_empty() {
+        bail!("None of the selected days is solved");
+    }
+    if !unsolved.is_empty() {
+        let unsolved = unsolved.iter().map(|day| day.to_string()).collect::<Vec<_>>();
+        eprintln!("Skipping unsolved days {}", unsolved.join(", "));
+    }
+    Ok(solved.into_iter().filter_map(registry::find).collect())
+}
+
+fn run(args: &RunArgs) -> anyhow::Result<()> {
+    let solutions = selected_solutions(&args.days)?;
+    let parts = match args.part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+
+    // every selected part is a task of its own, the parts of a day share its parsed input
+    let parsed = solutions
+        .iter()
+        .map(|_| OnceLock::new())
+        .collect::<Vec<OnceLock<anyhow::Result<Box<dyn Parsed>>>>>();
+    let tasks = (0..solutions.len())
+        .flat_map(|ix| parts.iter().map(move |&part| (ix, part)))
+        .collect::<Vec<_>>();
+
+    let run_task = |&(ix, part): &(usize, u8)| {
+        let solution = solutions[ix];
+        let day = solution.day();
+        let path = args
+            .input
+            .clone()
+            .unwrap_or_else(|| solution.default_input());
+
+        let res = match parsed[ix].get_or_init(|| parse_input(solution, &path)) {
+            Ok(parsed) => {
+                let (answer, elapsed) = bench::time(|| match part {
+                    1 => parsed.part_01(),
+                    _ => parsed.part_02(),
+                });
+                PartResult {
+                    day,
+                    part,
+                    answer,
+                    elapsed,
+                }
+            }
+            // text output reports a parse error once per day
+            Err(err) if args.format == Format::Text => {
+                let line = (part == parts[0]).then(|| {
+                    format!(
+                        "D{:02} {} = Error in {}: {}",
+                        day,
+                        solution.name(),
+                        path.display(),
+                        describe(err)
+                    )
+                });
+                return (true, line);
+            }
+            Err(err) => PartResult {
+                day,
+                part,
+                answer: Err(anyhow!("Error in {}: {:#}", path.display(), err)),
+                elapsed: Duration::ZERO,
+            },
+        };
+
+        (res.answer.is_err(), Some(res.format(args.format)))
+    };
+
+    if let Some(header) = args.format.header() {
+        println!("{}", header);
+    }
+
+    let mut failed = 0;
+    pool::run_ordered(&tasks, args.jobs, run_task, |(failure, line)| {
+        if let Some(line) = line {
+            println!("{}", line);
+        }
+        failed += failure as usize;
+    });
+
+    if failed > 0 {
+        bail!("{} of {} parts failed", failed, tasks.len());
+    }
+    Ok(())
+}
+
+/// Runs the selected days on their default input and compares every answer with the accepted one.
+fn verify(args: &VerifyArgs) -> anyhow::Result<()> {
+    let answers_path = args.answers.clone().unwrap_or_else(answers::default_path);
+    let answers = Answers::load(&answers_path)?;
+    let unknown = Expected::default();
+
+    let (mut correct, mut unchecked, mut failed) = (0, 0, 0);
+
+    for solution in selected_solutions(&args.days)? {
+        let day = solution.day();
+        let path = solution.default_input();
+
+        let parsed = match parse_input(solution, &path) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                println!(
+                    "D{:02} {} = Error in {}: {}",
+                    day,
+                    solution.name(),
+                    path.display(),
+                    describe(&err)
+                );
+                failed += 2;
+                continue;
+            }
+        };
+
+        for (part, answer) in [(1, parsed.part_01()), (2, parsed.part_02())] {
+            let answer = match answer {
+                Ok(answer) => answer,
+                Err(err) => {
+                    println!("D{:02} Part {} = Error: {:#}", day, part, err);
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            let expected = answers.get(day, part).unwrap_or(&unknown);
+            match expected.check(&answer) {
+                Verdict::Correct => {
+                    println!("D{:02} Part {} = {} ok", day, part, answer);
+                    correct += 1;
+                }
+                Verdict::Regression {
+                    expected,
+                    rejection,
+                } => {
+                    let rejection = rejection
+                        .map(|r| format!(", rejected before as {}", r))
+                        .unwrap_or_default();
+                    println!(
+                        "D{:02} Part {} = {} REGRESSION, expected {}{}",
+                        day, part, answer, expected, rejection
+                    );
+                    failed += 1;
+                }
+                Verdict::Rejected(rejection) => {
+                    println!(
+                        "D{:02} Part {} = {} REJECTED before as {}",
+                        day, part, answer, rejection
+                    );
+                    failed += 1;
+                }
+                Verdict::Unchecked => {
+                    println!("D{:02} Part {} = unchecked, no accepted answer", day, part);
+                    unchecked += 1;
+                }
+            }
+        }
+    }
+
+    println!(
+        "\n{} correct, {} unchecked, {} failed",
+        correct, unchecked, failed
+    );
+
+    if failed > 0 {
+        bail!("{} answers failed verification", failed);
+    }
+    Ok(())
+}
 
-    println!("D02 Part 1 = {:?}", d02::part_01());
-    println!("D02 Part 2 = {:?}", d02::part_02());
+/// Times parsing and both parts of the selected days, optionally saving the timings as a baseline
+/// and comparing them with an earlier one.
+fn bench(args: &BenchArgs) -> anyhow::Result<()> {
+    let previous = args.compare.as_deref().map(Baseline::load).transpose()?;
+    let mut baseline = Baseline::default();
+    let mut regressions = 0;
 
-    println!("D03 Part 1 = {:?}", d03::part_01());
-    println!("D03 Part 2 = {:?}", d03::part_02());
+    println!(
+        "{:<4} {:<6} {:>10} {:>10} {:>10}",
+        "day", "phase", "min", "median", "p95"
+    );
 
-    println!("D04 Part 1 = {:?}", d04::part_01());
-    println!("D04 Part 2 = {:?}", d04::part_02());
+    for solution in selected_solutions(&args.days)? {
+        let day = solution.day();
+        let path = solution.default_input();
 
-    println!("D05 Part 1 = {:?}", d05::part_01());
-    println!("D05 Part 2 = {:?}", d05::part_02());
+        let timings = std::fs::read_to_string(&path)
+            .context("Error while reading input")
+            .and_then(|input| bench::day(solution, &input, args.iterations));
 
-    println!("D06 Part 1 = {:?}", d06::part_01());
-    println!("D06 Part 2 = {:?}", d06::part_02());
+        let timings = match timings {
+            Ok(timings) => timings,
+            Err(err) => {
+                println!(
+                    "D{:02} {} = Error in {}: {}",
+                    day,
+                    solution.name(),
+                    path.display(),
+                    describe(&err)
+                );
+                continue;
+            }
+        };
 
-    println!("D07 Part 1 = {:?}", d07::part_01());
-    println!("D07 Part 2 = {:?}", d07::part_02());
+        for (phase, stats) in timings {
+            let comparison = match previous.as_ref().and_then(|p| p.get(day, phase)) {
+                Some(base) => {
+                    let change = bench::change(base, &stats);
+                    let flag = if change > args.threshold {
+                        regressions += 1;
+                        " REGRESSION"
+                    } else {
+                        ""
+                    };
+                    format!(" {:>+8.1}%{}", change, flag)
+                }
+                None => String::new(),
+            };
 
-    println!("D08 Part 1 = {:?}", d08::part_01());
-    println!("D08 Part 2 = {:?}", d08::part_02());
+            println!(
+                "D{:02}  {:<6} {:>10.2?} {:>10.2?} {:>10.2?}{}",
+                day, phase, stats.min, stats.median, stats.p95, comparison
+            );
+            baseline.insert(day, phase, stats);
+        }
+    }
 
-    println!("D09 Part 1 = {:?}", d09::part_01());
-    println!("D09 Part 2 = {:?}", d09::part_02());
+    if let Some(path) = &args.save {
+        baseline.save(path)?;
+    }
 
-    println!("D10 Part 1 = {:?}", d10::part_01());
-    println!("D10 Part 2 = {:?}", d10::part_02());
+    if regressions > 0 {
+        bail!(
+            "{} phases got more than {}% slower than the baseline",
+            regressions,
+            args.threshold
+        );
+    }
+    Ok(())
+}
+
+/// Writes a random input for the selected day to the output file or stdout.
+fn generate(args: &GenerateArgs) -> anyhow::Result<()> {
+    let solution =
+        registry::find(args.day).with_context(|| format!("Day {} is not solved", args.day))?;
+    let size = args.size.unwrap_or_else(|| solution.input_size());
+    let input = solution.generate(&mut Rng::new(args.seed), size);
+
+    match &args.output {
+        Some(path) => std::fs::write(path, input + "\n")
+            .with_context(|| format!("Error while writing {}", path.display())),
+        None => {
+            println!("{}", input);
+            Ok(())
+        }
+    }
+}
+
+/// Compares the naive and optimized implementations of the selected days on generated inputs.
+fn diff(args: &DiffArgs) -> anyhow::Result<()> {
+    let mut mismatches = 0;
+
+    for pair in differential::PAIRS {
+        if args
+            .days
+            .as_ref()
+            .is_some_and(|days| !days.contains(&pair.day))
+        {
+            continue;
+        }
+
+        let max_size = args.max_size.unwrap_or(pair.max_size);
+        match pair.find_mismatch(args.seeds, max_size) {
+            Some(mismatch) => {
+                println!("D{:02} {} = MISMATCH, {}", pair.day, pair.name, mismatch);
+                mismatches += 1;
+            }
+            None => println!(
+                "D{:02} {} = agree up to size {}",
+                pair.day, pair.name, max_size
+            ),
+        }
+    }
+
+    if mismatches > 0 {
+        bail!("{} implementations disagree", mismatches);
+    }
+    Ok(())
+}
+
+/// Draws the selected day from its input into the output directory.
+fn render(args: &RenderArgs) -> anyhow::Result<()> {
+    let renderer =
+        render::find(args.day).with_context(|| format!("Day {} cannot be rendered", args.day))?;
+    let solution =
+        registry::find(args.day).with_context(|| format!("Day {} is not solved", args.day))?;
+    let path = args
+        .input
+        .clone()
+        .unwrap_or_else(|| solution.default_input());
+
+    let input = std::fs::read_to_string(&path)
+        .with_context(|| format!("Error while reading {}", path.display()))?;
+    let frames = (renderer.render)(&input)
+        .map_err(|err| err.locate(&input))
+        .with_context(|| format!("Error in {}", path.display()))?;
+    let paths = render::save(&frames, &args.output, args.scale)?;
+
+    println!(
+        "D{:02} {}: {} images in {}",
+        args.day,
+        renderer.name,
+        paths.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// Steps through the simulation of the selected day, reading commands from stdin.
+fn step(args: &StepArgs) -> anyhow::Result<()> {
+    let stepper = step::find(args.day)
+        .with_context(|| format!("Day {} cannot be stepped through", args.day))?;
+    let solution =
+        registry::find(args.day).with_context(|| format!("Day {} is not solved", args.day))?;
+    let path = args
+        .input
+        .clone()
+        .unwrap_or_else(|| solution.default_input());
 
-    println!("D11 Part 1 = {:?}", d11::part_01());
-    println!("D11 Part 2 = {:?}", d11::part_02());
+    let input = std::fs::read_to_string(&path)
+        .with_context(|| format!("Error while reading {}", path.display()))?;
+    let mut session = (stepper.start)(&input)
+        .map_err(|err| err.locate(&input))
+        .with_context(|| format!("Error in {}", path.display()))?;
 
-    println!("D12 Part 1 = {:?}", d12::part_01());
-    println!("D12 Part 2 = {:?}", d12::part_02());
+    println!(
+        "D{:02} {}, one {} at a time (`help` lists the commands)",
+        args.day,
+        solution.name(),
+        stepper.name
+    );
+    step::repl(
+        session.as_mut(),
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout().lock(),
+    )?;
+    Ok(())
+}
+
+/// Answers the selected day again every time its input or one of its examples changes.
+fn watch(args: &WatchArgs) -> anyhow::Result<()> {
+    let solution =
+        registry::find(args.day).with_context(|| format!("Day {} is not solved", args.day))?;
+
+    let mut paths = vec![];
+    match &args.input {
+        Some(input) => paths.push(input.clone()),
+        None => {
+            paths.push(solution.default_input());
+            let examples = examples::discover(&examples::default_dir())?;
+            paths.extend(
+                examples
+                    .into_iter()
+                    .filter(|example| example.day == args.day)
+                    .map(|example| example.input),
+            );
+        }
+    }
+
+    println!(
+        "Watching D{:02} {} in {} files, Ctrl-C to stop",
+        args.day,
+        solution.name(),
+        paths.len()
+    );
+
+    let mut watcher = Watcher::new(paths);
+    let mut previous = HashMap::new();
+    loop {
+        for path in watcher.poll() {
+            let answers = match std::fs::read_to_string(path) {
+                Ok(input) => watch::answer(solution, &input),
+                Err(err) => {
+                    println!("{}: {}", path.display(), err);
+                    previous.remove(path);
+                    continue;
+                }
+            };
+
+            println!("{}:", path.display());
+            for line in watch::compare(previous.get(path), &answers) {
+                println!("    {}", line);
+            }
+            previous.insert(path.to_owned(), answers);
+        }
+
+        std::thread::sleep(Duration::from_millis(args.interval));
+    }
+}
 
-    println!("D13 Part 1 = {:?}", d13::part_01());
-    println!("D13 Part 1 = {:?}", d13::part_02());
+/// Sweeps day 1 measurements one line at a time, however long the input is.
+fn sweep(args: &SweepArgs) -> anyhow::Result<()> {
+    let (reader, name) = open_depths(&args.input)?;
+    let changes =
+        d01::sweep(reader, args.window).with_context(|| format!("Error in {}", name))?;
 
-    println!("D14 Part 1 = {:?}", d14::part_01());
-    println!("D14 Part 2 = {:?}", d14::part_02());
+    println!("Windows of {} measurements:", args.window);
+    println!("{}", indent(&changes.to_string()));
+    Ok(())
+}
 
-    println!("D15 Part 1 = {:?}", d15::part_01());
-    println!("D15 Part 2 = {:?}", d15::part_02());
+/// Writes the statistics of every window of day 1 measurements as CSV, streaming the input.
+fn stats(args: &StatsArgs) -> anyhow::Result<()> {
+    let (reader, name) = open_depths(&args.input)?;
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path)
+                .with_context(|| format!("Error while creating {}", path.display()))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
 
-    println!("D16 Part 1 = {:?}", d16::part_01());
-    println!("D16 Part 2 = {:?}", d16::part_02());
+    window::stream_csv(&mut out, d01::depths(reader), args.window, args.step)
+        .with_context(|| format!("Error in {}", name))
+}
 
-    println!("D17 Part 1 = {:?}", d17::part_01());
-    println!("D17 Part 2 = {:?}", d17::part_02());
+/// Day 1 measurements of `input`, the default input when there is none and stdin for `-`,
+/// together with the name of where they come from.
+fn open_depths(input: &Option<PathBuf>) -> anyhow::Result<(Box<dyn BufRead>, String)> {
+    match input {
+        Some(path) if path == Path::new("-") => {
+            Ok((Box::new(std::io::stdin().lock()), "stdin".to_owned()))
+        }
+        input => {
+            let path = input.clone().unwrap_or_else(|| d01::Day01.default_input());
+            let file = std::fs::File::open(&path)
+                .with_context(|| format!("Error while reading {}", path.display()))?;
+            Ok((
+                Box::new(std::io::BufReader::new(file)),
+                path.display().to_string(),
+            ))
+        }
+    }
+}
 
-    println!("D18 Part 1 = {:?}", d18::part_01());
-    println!("D18 Part 2 = {:?}", d18::part_02());
+/// Flies a day 2 mission script with the selected steering models.
+fn mission(args: &MissionArgs) -> anyhow::Result<()> {
+    let path = &args.script;
+    let script = std::fs::read_to_string(path)
+        .with_context(|| format!("Error while reading {}", path.display()))?;
+    let mission = Mission::parse(&script)
+        .map_err(|err| err.locate(&script))
+        .with_context(|| format!("Error in {}", path.display()))?;
 
-    println!("D19 Part 1 = {:?}", d19::part_01());
-    println!("D19 Part 2 = {:?}", d19::part_02());
+    if args.steering != Some(cli::Steering::Aim) {
+        let report = mission.fly(d02::Direct).and_then(|flight| flight.report());
+        print_flight("Direct", report)?;
+    }
+    if args.steering != Some(cli::Steering::Direct) {
+        let report = mission.fly(d02::Aim).and_then(|flight| flight.report());
+        print_flight("Aim", report)?;
+    }
+    Ok(())
+}
 
-    println!("D20 Part 1 = {:?}", d20::part_01());
-    println!("D20 Part 2 = {:?}", d20::part_02());
+fn print_flight(name: &str, report: aoc_2021::error::Result<String>) -> anyhow::Result<()> {
+    let report =
+        report.with_context(|| format!("Error with the {} steering", name.to_lowercase()))?;
+    println!("{} steering:", name);
+    println!("{}", indent(&report));
+    Ok(())
+}
 
-    println!("D21 Part 1 = {:?}", d21::part_01());
-    println!("D21 Part 1 = {:?}", d21::part_02());
+/// `err` with its causes, followed by the offending input line when it is a parse error.
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-    println!("D22 Part 1 = {:?}", d22::part_01());
-    println!("D22 Part 1 = {:?}", d22::part_02());
+/// Parses the input at `path`, `-` standing for stdin.
+fn parse_input(solution: &dyn DynSolution, path: &Path) -> anyhow::Result<Box<dyn Parsed>> {
+    if path == Path::new("-") {
+        return solution.parse_reader(&mut std::io::stdin().lock());
+    }
 
-    println!("D23 Part 1 = {:?}", d23::part_01());
-    println!("D23 Part 1 = {:?}", d23::part_02());
+    let input = std::fs::read_to_string(path).context("Error while reading input")?;
+    solution.parse(&input)
 }
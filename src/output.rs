@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use anyhow::bail;
+
+/// How the runner prints its results.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    /// One JSON object per line.
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> anyhow::Result<Format> {
+        Ok(match s {
+            "text" => Format::Text,
+            "json" => Format::Json,
+            "csv" => Format::Csv,
+            _ => bail!("Format must be text, json or csv, got `{}`", s),
+        })
+    }
+
+    /// Line printed before the first result, if any.
+    pub fn header(&self) -> Option<&'static str> {
+        match self {
+            Format::Csv => Some("day,part,answer,elapsed_ms,error"),
+            Format::Text | Format::Json => None,
+        }
+    }
+}
+
+/// Answer of one part together with the time it took.
+pub struct PartResult {
+    pub day: u8,
+    pub part: u8,
+    pub answer: anyhow::Result<String>,
+    pub elapsed: Duration,
+}
+
+impl PartResult {
+    pub fn format(&self, format: Format) -> String {
+        let (day, part) = (self.day, self.part);
+        let elapsed_ms = format!("{:.3}", self.elapsed.as_secs_f64() * 1000.0);
+        let (answer, error) = match &self.answer {
+            Ok(answer) => (Some(answer.as_str()), None),
+            Err(err) => (None, Some(format!("{:#}", err))),
+        };
+
+        match format {
+            Format::Text => match &self.answer {
+                // multi-line answers (like rendered dots) start on their own line
+                Ok(answer) if answer.contains('\n') => {
+                    format!("D{:02} Part {} =\n{}", day, part, answer)
+                }
+                Ok(answer) => format!("D{:02} Part {} = {}", day, part, answer),
+                Err(err) => format!("D{:02} Part {} = Error: {:#}", day, part, err),
+            },
+            Format::Json => format!(
+                r#"{{"day":{},"part":{},"answer":{},"elapsed_ms":{},"error":{}}}"#,
+                day,
+                part,
+                answer.map_or("null".to_owned(), json_string),
+                elapsed_ms,
+                error.as_deref().map_or("null".to_owned(), json_string),
+            ),
+            Format::Csv => format!(
+                "{},{},{},{},{}",
+                day,
+                part,
+                csv_field(answer.unwrap_or_default()),
+                elapsed_ms,
+                csv_field(error.as_deref().unwrap_or_default()),
+            ),
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c.is_control() => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use anyhow::anyhow;
+
+    fn result(answer: anyhow::Result<String>) -> PartResult {
+        PartResult {
+            day: 13,
+            part: 2,
+            answer,
+            elapsed: Duration::from_micros(1500),
+        }
+    }
+
+    #[test]
+    fn test_format_answer() {
+        let res = result(Ok("2876".to_owned()));
+
+        assert_eq!(res.format(Format::Text), "D13 Part 2 = 2876");
+        assert_eq!(
+            res.format(Format::Json),
+            r#"{"day":13,"part":2,"answer":"2876","elapsed_ms":1.500,"error":null}"#
+        );
+        assert_eq!(res.format(Format::Csv), "13,2,2876,1.500,");
+
+        let res = result(Ok("#.#\n.\"#".to_owned()));
+        assert_eq!(res.format(Format::Text), "D13 Part 2 =\n#.#\n.\"#");
+        assert_eq!(
+            res.format(Format::Json),
+            r##"{"day":13,"part":2,"answer":"#.#\n.\"#","elapsed_ms":1.500,"error":null}"##
+        );
+        assert_eq!(res.format(Format::Csv), "13,2,\"#.#\n.\"\"#\",1.500,");
+    }
+
+    #[test]
+    fn test_format_error() {
+        let res = result(Err(anyhow!("No path, found")));
+
+        assert_eq!(
+            res.format(Format::Text),
+            "D13 Part 2 = Error: No path, found"
+        );
+        assert_eq!(
+            res.format(Format::Json),
+            r#"{"day":13,"part":2,"answer":null,"elapsed_ms":1.500,"error":"No path, found"}"#
+        );
+        assert_eq!(res.format(Format::Csv), "13,2,,1.500,\"No path, found\"");
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Runs `f` on every item using `jobs` worker threads and hands the results to `on_result` in
+/// item order, as soon as all earlier results are in.
+pub fn run_ordered<T, R, F, C>(items: &[T], jobs: usize, f: F, mut on_result: C)
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    C: FnMut(R),
+{
+    if jobs <= 1 {
+        items.iter().map(&f).for_each(on_result);
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(items.len()) {
+            let (tx, next, f) = (tx.clone(), &next, &f);
+            scope.spawn(move || loop {
+                let ix = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(ix) else {
+                    break;
+                };
+                if tx.send((ix, f(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut pending = HashMap::new();
+        let mut expected = 0;
+        for (ix, res) in rx {
+            pending.insert(ix, res);
+            while let Some(res) = pending.remove(&expected) {
+                on_result(res);
+                expected += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_run_ordered() {
+        let items = (0..100u64).collect::<Vec<_>>();
+        // earlier items take longer, so results regularly come in out of order
+        let work = |&n: &u64| {
+            thread::sleep(std::time::Duration::from_micros((100 - n) * 20));
+            n * 2
+        };
+
+        for jobs in [1, 4, 200] {
+            let mut results = vec![];
+            run_ordered(&items, jobs, work, |res| results.push(res));
+            assert_eq!(results, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+        }
+
+        let mut results: Vec<u64> = vec![];
+        run_ordered(&[] as &[u64], 4, work, |res| results.push(res));
+        assert!(results.is_empty());
+    }
+}
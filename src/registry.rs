@@ -0,0 +1,111 @@
+use crate::solution::DynSolution;
+use crate::{
+    d01, d02, d03, d04, d05, d06, d07, d08, d09, d10, d11, d12, d13, d14, d15, d16, d17, d18, d19,
+    d20, d21, d22, d23,
+};
+
+/// Every solved day, in calendar order.
+pub static SOLUTIONS: &[&dyn DynSolution] = &[
+    &d01::Day01,
+    &d02::Day02,
+    &d03::Day03,
+    &d04::Day04,
+    &d05::Day05,
+    &d06::Day06,
+    &d07::Day07,
+    &d08::Day08,
+    &d09::Day09,
+    &d10::Day10,
+    &d11::Day11,
+    &d12::Day12,
+    &d13::Day13,
+    &d14::Day14,
+    &d15::Day15,
+    &d16::Day16,
+    &d17::Day17,
+    &d18::Day18,
+    &d19::Day19,
+    &d20::Day20,
+    &d21::Day21,
+    &d22::Day22,
+    &d23::Day23,
+];
+
+pub fn find(day: u8) -> Option<&'static dyn DynSolution> {
+    SOLUTIONS
+        .iter()
+        .copied()
+        .find(|solution| solution.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::error::{Error, Located};
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_days_are_unique_and_ordered() {
+        let days = SOLUTIONS.iter().map(|s| s.day()).collect::<Vec<_>>();
+        assert_eq!(days, (1..=23).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_generated_inputs_are_solvable() {
+        for solution in SOLUTIONS {
+            let seed = solution.day() as u64;
+            let size = solution.input_size() / 4 + 1;
+            let input = solution.generate(&mut Rng::new(seed), size);
+            assert_eq!(input, solution.generate(&mut Rng::new(seed), size));
+
+            let context = format!("day {} seed {}:\n{}", solution.day(), seed, input);
+            let parsed = solution.parse(&input).expect(&context);
+            parsed.part_01().expect(&context);
+            parsed.part_02().expect(&context);
+        }
+    }
+
+    #[test]
+    fn test_malformed_inputs_are_located() {
+        for solution in SOLUTIONS {
+            let input = solution.generate(&mut Rng::new(1), 3);
+            let chars = input.chars().collect::<Vec<_>>();
+            let mut rng = Rng::new(solution.day() as u64);
+
+            for _ in 0..40 {
+                // garble a character, or cut the input short
+                let ix = rng.below(chars.len());
+                let malformed = match rng.below(3) {
+                    0 => chars[..ix].iter().collect::<String>(),
+                    n => {
+                        let mut chars = chars.clone();
+                        chars[ix] = [' ', 'x', '-'][n];
+                        chars.into_iter().collect()
+                    }
+                };
+
+                let Err(err) = solution.parse(&malformed) else {
+                    continue;
+                };
+                let context = format!("day {}: {:#} in\n{}", solution.day(), err, malformed);
+                let located = err.downcast_ref::<Located>().expect(&context);
+                let read = solution.parse_reader(&mut malformed.as_bytes());
+                let read_err = read.err().expect(&context);
+                assert!(read_err.downcast_ref::<Located>().is_some(), "{}", context);
+                let Error::Parse { line, column, .. } = located.error else {
+                    panic!("{}", context);
+                };
+                let text = malformed.lines().nth(line - 1).unwrap_or_default();
+                assert!(line <= malformed.lines().count() + 1, "{}", context);
+                assert!(column <= text.chars().count() + 1, "{}", context);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(15).map(|s| s.name()), Some("Chiton"));
+        assert!(find(25).is_none());
+    }
+}
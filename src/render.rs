@@ -0,0 +1,226 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::error::Result;
+use crate::grid::Grid;
+use crate::{d09, d11, d13, d15, d20};
+
+/// Colour of a pixel, as red, green and blue.
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+pub const RED: Rgb = [220, 40, 40];
+
+/// Image of one cell per pixel, written as a binary PGM when grayscale and a PPM otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Picture {
+    Gray(Grid<u8>),
+    Color(Grid<Rgb>),
+}
+
+impl Picture {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Picture::Gray(_) => "pgm",
+            Picture::Color(_) => "ppm",
+        }
+    }
+
+    /// Writes the picture with every cell drawn as a `scale` by `scale` square.
+    pub fn write<W: Write>(&self, out: &mut W, scale: usize) -> io::Result<()> {
+        match self {
+            Picture::Gray(grid) => write_netpbm(out, "P5", grid, scale, |&shade| vec![shade]),
+            Picture::Color(grid) => write_netpbm(out, "P6", grid, scale, |rgb| rgb.to_vec()),
+        }
+    }
+}
+
+fn write_netpbm<W: Write, T>(
+    out: &mut W,
+    magic: &str,
+    grid: &Grid<T>,
+    scale: usize,
+    bytes: impl Fn(&T) -> Vec<u8>,
+) -> io::Result<()> {
+    let scale = scale.max(1);
+    write!(
+        out,
+        "{}\n{} {}\n255\n",
+        magic,
+        grid.width() * scale,
+        grid.height() * scale
+    )?;
+
+    for row in grid.rows() {
+        let line = row
+            .iter()
+            .flat_map(|cell| bytes(cell).repeat(scale))
+            .collect::<Vec<_>>();
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+    Ok(())
+}
+
+/// One picture of a rendering, animations being numbered frames.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub name: String,
+    pub picture: Picture,
+}
+
+impl Frame {
+    pub fn new(name: impl Into<String>, picture: Picture) -> Frame {
+        Frame {
+            name: name.into(),
+            picture,
+        }
+    }
+}
+
+/// Gray level of `value` between 0 and `max`, from black to white.
+pub fn shade(value: u32, max: u32) -> u8 {
+    (value.min(max) * 255 / max.max(1)) as u8
+}
+
+/// Distinct colour for every `id`, spreading the hues by the golden angle.
+pub fn palette(id: usize) -> Rgb {
+    let hue = (id as f64 * 137.508) % 360.0;
+    let x = 1.0 - ((hue / 60.0) % 2.0 - 1.0).abs();
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let channel = |c: f64| (55.0 + c * 200.0) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+/// Colours the states of a cellular automaton, cells without a colour being black.
+pub fn cells(grid: &Grid<char>, colors: &[(char, Rgb)]) -> Grid<Rgb> {
+    grid.map(|c| {
+        colors
+            .iter()
+            .find(|(state, _)| state == c)
+            .map_or(BLACK, |&(_, rgb)| rgb)
+    })
+}
+
+/// Writes every frame to `dir` as `<name>.pgm` or `<name>.ppm`, returning the written paths.
+pub fn save(frames: &[Frame], dir: &Path, scale: usize) -> anyhow::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Error while creating {}", dir.display()))?;
+
+    let mut paths = vec![];
+    for frame in frames {
+        let path = dir.join(format!("{}.{}", frame.name, frame.picture.extension()));
+        let mut out = io::BufWriter::new(
+            std::fs::File::create(&path)
+                .with_context(|| format!("Error while creating {}", path.display()))?,
+        );
+        frame
+            .picture
+            .write(&mut out, scale)
+            .and_then(|_| out.flush())
+            .with_context(|| format!("Error while writing {}", path.display()))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Day whose puzzle input can be rendered.
+pub struct Renderer {
+    pub day: u8,
+    /// What the frames show.
+    pub name: &'static str,
+    pub render: fn(&str) -> Result<Vec<Frame>>,
+}
+
+/// Every renderable day, in calendar order.
+pub static RENDERERS: &[Renderer] = &[
+    Renderer {
+        day: 9,
+        name: "basins coloured by id",
+        render: |input| Ok(d09::render(&d09::parse(input)?)),
+    },
+    Renderer {
+        day: 11,
+        name: "octopus energy until they all flash",
+        render: |input| Ok(d11::render(&d11::parse(input)?)),
+    },
+    Renderer {
+        day: 13,
+        name: "paper after every fold",
+        render: |input| Ok(d13::render(&d13::parse(input)?)),
+    },
+    Renderer {
+        day: 15,
+        name: "lowest risk paths",
+        render: |input| d15::render(&d15::parse(input)?),
+    },
+    Renderer {
+        day: 20,
+        name: "image after every enhancement",
+        render: |input| Ok(d20::render(&d20::parse(input)?)),
+    },
+];
+
+pub fn find(day: u8) -> Option<&'static Renderer> {
+    RENDERERS.iter().find(|renderer| renderer.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::registry;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_write() {
+        let gray = Picture::Gray(Grid::from_rows(vec![vec![0, 255]]).unwrap());
+        let mut out = vec![];
+        gray.write(&mut out, 2).unwrap();
+        assert_eq!(out, b"P5\n4 2\n255\n\x00\x00\xff\xff\x00\x00\xff\xff");
+
+        let color = Picture::Color(Grid::from_rows(vec![vec![RED], vec![WHITE]]).unwrap());
+        let mut out = vec![];
+        color.write(&mut out, 1).unwrap();
+        assert_eq!(out, b"P6\n1 2\n255\n\xdc\x28\x28\xff\xff\xff");
+        assert_eq!(color.extension(), "ppm");
+    }
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(shade(0, 9), 0);
+        assert_eq!(shade(9, 9), 255);
+        assert_eq!(shade(12, 9), 255);
+
+        let colors = (0..10).map(palette).collect::<Vec<_>>();
+        assert!(colors
+            .iter()
+            .enumerate()
+            .all(|(ix, c)| !colors[..ix].contains(c)));
+    }
+
+    #[test]
+    fn test_renderers() {
+        for renderer in RENDERERS {
+            let solution = registry::find(renderer.day).unwrap();
+            let input = solution.generate(&mut Rng::new(1), 8);
+            let frames = (renderer.render)(&input).unwrap();
+
+            assert!(!frames.is_empty());
+            assert!(frames
+                .iter()
+                .all(|frame| frame.name.starts_with(&format!("{:02}_", renderer.day))));
+        }
+    }
+}
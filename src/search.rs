@@ -0,0 +1,222 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Cost of moving between states, summed up along a path.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+
+impl<T: Copy + Ord + Default + Add<Output = T>> Cost for T {}
+
+/// Graph searched for the cheapest path from a start state to any goal state.
+pub trait Search {
+    type State: Clone + Eq + Hash;
+    type Cost: Cost;
+
+    /// States reachable in one move from `state`, with the cost of getting there.
+    fn neighbours(&self, state: &Self::State) -> Vec<(Self::State, Self::Cost)>;
+
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    /// Lower bound on the cost from `state` to a goal, only used by [`a_star`].
+    fn heuristic(&self, _state: &Self::State) -> Self::Cost {
+        Self::Cost::default()
+    }
+}
+
+/// Cheapest path found by a search, from the start state to the goal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    pub states: Vec<S>,
+}
+
+/// Cost of the cheapest path from `start` to a goal, `None` when there is none.
+pub fn dijkstra<P: Search>(problem: &P, start: P::State) -> Option<P::Cost> {
+    run(problem, start, false).map(|(cost, _)| cost)
+}
+
+/// Same as [`dijkstra`], exploring states with a low heuristic first.
+pub fn a_star<P: Search>(problem: &P, start: P::State) -> Option<P::Cost> {
+    run(problem, start, true).map(|(cost, _)| cost)
+}
+
+pub fn dijkstra_path<P: Search>(problem: &P, start: P::State) -> Option<Path<P::State, P::Cost>> {
+    run(problem, start, false).map(|(cost, states)| Path { cost, states })
+}
+
+pub fn a_star_path<P: Search>(problem: &P, start: P::State) -> Option<Path<P::State, P::Cost>> {
+    run(problem, start, true).map(|(cost, states)| Path { cost, states })
+}
+
+/// Frontier entry, ordered so that the binary heap pops the lowest estimate first.
+struct Node<C> {
+    estimate: C,
+    cost: C,
+    ix: usize,
+}
+
+impl<C: Ord> PartialEq for Node<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: Ord> Eq for Node<C> {}
+
+impl<C: Ord> PartialOrd for Node<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Ord> Ord for Node<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // on equal estimates prefer the node further along
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+            .then_with(|| other.ix.cmp(&self.ix))
+    }
+}
+
+/// Every state seen so far, with its best known cost and the state it was reached from.
+struct Visited<S, C> {
+    states: Vec<(S, C, Option<usize>)>,
+    index: HashMap<S, usize>,
+}
+
+impl<S: Clone + Eq + Hash, C: Ord + Copy> Visited<S, C> {
+    /// Index of `state` when it is new or `cost` improves on the known one.
+    fn improve(&mut self, state: S, cost: C, parent: Option<usize>) -> Option<usize> {
+        match self.index.entry(state) {
+            Entry::Occupied(entry) => {
+                let ix = *entry.get();
+                let known = &mut self.states[ix];
+                if cost >= known.1 {
+                    return None;
+                }
+                known.1 = cost;
+                known.2 = parent;
+                Some(ix)
+            }
+            Entry::Vacant(entry) => {
+                let ix = self.states.len();
+                self.states.push((entry.key().clone(), cost, parent));
+                entry.insert(ix);
+                Some(ix)
+            }
+        }
+    }
+
+    fn path_to(&self, mut ix: usize) -> Vec<S> {
+        let mut path = vec![self.states[ix].0.clone()];
+        while let Some(parent) = self.states[ix].2 {
+            path.push(self.states[parent].0.clone());
+            ix = parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+fn run<P: Search>(
+    problem: &P,
+    start: P::State,
+    use_heuristic: bool,
+) -> Option<(P::Cost, Vec<P::State>)> {
+    let estimate = |state: &P::State, cost: P::Cost| {
+        if use_heuristic {
+            cost + problem.heuristic(state)
+        } else {
+            cost
+        }
+    };
+
+    let mut visited = Visited {
+        states: vec![],
+        index: HashMap::new(),
+    };
+    let mut frontier = BinaryHeap::new();
+
+    let zero = P::Cost::default();
+    let ix = visited.improve(start.clone(), zero, None)?;
+    frontier.push(Node {
+        estimate: estimate(&start, zero),
+        cost: zero,
+        ix,
+    });
+
+    while let Some(Node { cost, ix, .. }) = frontier.pop() {
+        if cost > visited.states[ix].1 {
+            // a cheaper way to this state was found after it got queued
+            continue;
+        }
+        let state = visited.states[ix].0.clone();
+        if problem.is_goal(&state) {
+            return Some((cost, visited.path_to(ix)));
+        }
+
+        for (next, step) in problem.neighbours(&state) {
+            let next_cost = cost + step;
+            let next_estimate = estimate(&next, next_cost);
+            if let Some(next_ix) = visited.improve(next, next_cost, Some(ix)) {
+                frontier.push(Node {
+                    estimate: next_estimate,
+                    cost: next_cost,
+                    ix: next_ix,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Counting up from a number by adding 1 (costing 1) or doubling (costing 3).
+    struct Numbers {
+        goal: u32,
+    }
+
+    impl Search for Numbers {
+        type State = u32;
+        type Cost = u32;
+
+        fn neighbours(&self, &n: &u32) -> Vec<(u32, u32)> {
+            [(n + 1, 1), (n * 2, 3)]
+                .into_iter()
+                .filter(|&(next, _)| next <= self.goal)
+                .collect()
+        }
+
+        fn is_goal(&self, &n: &u32) -> bool {
+            n == self.goal
+        }
+
+        fn heuristic(&self, &n: &u32) -> u32 {
+            u32::from(n != self.goal)
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let problem = Numbers { goal: 20 };
+
+        assert_eq!(dijkstra(&problem, 1), Some(10));
+        assert_eq!(a_star(&problem, 1), Some(10));
+
+        let path = a_star_path(&problem, 1).unwrap();
+        assert_eq!(path.cost, 10);
+        assert_eq!(path.states, [1, 2, 3, 4, 5, 10, 20]);
+        assert_eq!(dijkstra_path(&problem, 20).unwrap().states, [20]);
+
+        assert_eq!(dijkstra(&problem, 21), None);
+    }
+}
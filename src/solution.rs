@@ -0,0 +1,118 @@
+use std::fmt::Display;
+use std::io::Read;
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::rng::Rng;
+
+/// One day of the advent calendar: how its input is read and how both parts are answered.
+pub trait Solution {
+    const DAY: u8;
+    const NAME: &'static str;
+
+    type Input: Send + Sync;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+
+    fn part_01(input: &Self::Input) -> Result<Self::Answer1>;
+
+    fn part_02(input: &Self::Input) -> Result<Self::Answer2>;
+
+    /// Size of the real puzzle input, in the unit [`Solution::generate`] counts in.
+    const INPUT_SIZE: usize;
+
+    /// Random input that parses and has answers to both parts. What `size` counts (lines, fish,
+    /// rows of a grid, ...) depends on the day.
+    fn generate(rng: &mut Rng, size: usize) -> String;
+
+    /// Puzzle input used when no other file is given, found in the repository's `inputs`
+    /// directory whatever the working directory is.
+    fn default_input() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("inputs")
+            .join(format!("{:02}_input.txt", Self::DAY))
+    }
+
+    /// Reads the whole input from `reader` (a file, stdin, ...) and parses it.
+    fn parse_reader<R: Read>(mut reader: R) -> Result<Self::Input> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        Self::parse(&input)
+    }
+}
+
+/// Object safe view of a [`Solution`], so days with different input and answer types
+/// can live side by side in the registry.
+pub trait DynSolution: Sync {
+    fn day(&self) -> u8;
+
+    fn name(&self) -> &'static str;
+
+    fn default_input(&self) -> PathBuf;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Box<dyn Parsed>>;
+
+    fn parse_reader(&self, reader: &mut dyn Read) -> anyhow::Result<Box<dyn Parsed>>;
+
+    fn input_size(&self) -> usize;
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> String;
+}
+
+/// Parsed input of one day, ready to answer both parts.
+pub trait Parsed: Send + Sync {
+    fn part_01(&self) -> anyhow::Result<String>;
+
+    fn part_02(&self) -> anyhow::Result<String>;
+}
+
+struct ParsedInput<S: Solution>(S::Input);
+
+impl<S: Solution> Parsed for ParsedInput<S> {
+    fn part_01(&self) -> anyhow::Result<String> {
+        Ok(S::part_01(&self.0)?.to_string())
+    }
+
+    fn part_02(&self) -> anyhow::Result<String> {
+        Ok(S::part_02(&self.0)?.to_string())
+    }
+}
+
+impl<S> DynSolution for S
+where
+    S: Solution + Sync + 'static,
+{
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn name(&self) -> &'static str {
+        S::NAME
+    }
+
+    fn default_input(&self) -> PathBuf {
+        S::default_input()
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Box<dyn Parsed>> {
+        let parsed = S::parse(input).map_err(|err| err.locate(input))?;
+        Ok(Box::new(ParsedInput::<S>(parsed)))
+    }
+
+    fn parse_reader(&self, reader: &mut dyn Read) -> anyhow::Result<Box<dyn Parsed>> {
+        // read whole, so that parse errors are located like the ones of inputs read from files
+        let mut input = String::new();
+        reader.read_to_string(&mut input).map_err(Error::Io)?;
+        self.parse(&input)
+    }
+
+    fn input_size(&self) -> usize {
+        S::INPUT_SIZE
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> String {
+        S::generate(rng, size)
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+
+use crate::error::Result;
+use crate::{d11, d18, d21, d23};
+
+/// State of a puzzle that can be advanced one step at a time.
+pub trait Simulation: Clone + Display {
+    /// Advances by one step and describes it, `None` once the simulation is over.
+    fn step(&mut self) -> Option<String>;
+}
+
+/// Simulation remembering all its earlier states, so that it can be stepped back.
+pub trait Session {
+    /// Advances by one step and describes it, `None` once the simulation is over.
+    fn step(&mut self) -> Option<String>;
+    /// Goes back one step, `false` when already at the start.
+    fn back(&mut self) -> bool;
+    /// Number of steps taken since the start.
+    fn steps(&self) -> usize;
+    fn show(&self) -> String;
+}
+
+struct History<S> {
+    states: Vec<S>,
+}
+
+impl<S: Simulation> Session for History<S> {
+    fn step(&mut self) -> Option<String> {
+        let mut next = self.states.last()?.clone();
+        let event = next.step()?;
+        self.states.push(next);
+        Some(event)
+    }
+
+    fn back(&mut self) -> bool {
+        if self.states.len() < 2 {
+            return false;
+        }
+        self.states.pop();
+        true
+    }
+
+    fn steps(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    fn show(&self) -> String {
+        self.states
+            .last()
+            .map(|state| state.to_string())
+            .unwrap_or_default()
+    }
+}
+
+pub fn session<S: Simulation + 'static>(start: S) -> Box<dyn Session> {
+    Box::new(History {
+        states: vec![start],
+    })
+}
+
+/// Day whose input can be stepped through.
+pub struct Stepper {
+    pub day: u8,
+    /// What a step is.
+    pub name: &'static str,
+    pub start: fn(&str) -> Result<Box<dyn Session>>,
+}
+
+/// Every day that can be stepped through, in calendar order.
+pub static STEPPERS: &[Stepper] = &[
+    Stepper {
+        day: 11,
+        name: "octopus step",
+        start: |input| Ok(session(d11::Cavern::new(d11::parse(input)?))),
+    },
+    Stepper {
+        day: 18,
+        name: "snailfish addition, explosion or split",
+        start: |input| Ok(session(d18::Homework::new(d18::parse(input)?))),
+    },
+    Stepper {
+        day: 21,
+        name: "turn with the deterministic die",
+        start: |input| Ok(session(d21::Game::new(d21::parse(input)?))),
+    },
+    Stepper {
+        day: 23,
+        name: "amphipod move of the cheapest organization",
+        start: |input| Ok(session(d23::Organization::new(&d23::parse(input)?)?)),
+    },
+];
+
+pub fn find(day: u8) -> Option<&'static Stepper> {
+    STEPPERS.iter().find(|stepper| stepper.day == day)
+}
+
+const HELP: &str = "Commands:
+    step [N]  advance N steps (default 1), also `s` or an empty line
+    run [N]   advance N steps, or until the end
+    back [N]  go back N steps (default 1)
+    show      print the current state
+    help      print this message
+    quit      leave";
+
+/// Upper bound of a `run` without a count, for simulations that don't end by themselves.
+const MAX_RUN: usize = 100_000;
+
+/// Reads commands from `input` until `quit` or its end, printing to `out`.
+pub fn repl(
+    session: &mut dyn Session,
+    input: &mut dyn BufRead,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(out, "{}", session.show())?;
+
+    loop {
+        write!(out, "[{}]> ", session.steps())?;
+        out.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Ok(());
+        }
+
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("step");
+        let count = match words.next().map(str::parse::<usize>) {
+            None => None,
+            Some(Ok(count)) => Some(count),
+            Some(Err(_)) => {
+                writeln!(out, "`{}` needs a number of steps", command)?;
+                continue;
+            }
+        };
+
+        match command {
+            "step" | "s" => advance(session, count.unwrap_or(1), out)?,
+            "run" | "r" => advance(session, count.unwrap_or(MAX_RUN), out)?,
+            "back" | "b" => {
+                for _ in 0..count.unwrap_or(1) {
+                    if !session.back() {
+                        break;
+                    }
+                }
+                writeln!(out, "back to step {}", session.steps())?;
+            }
+            "show" => writeln!(out, "{}", session.show())?,
+            "help" | "h" | "?" => writeln!(out, "{}", HELP)?,
+            "quit" | "q" | "exit" => return Ok(()),
+            _ => writeln!(out, "Unknown command `{}`, try `help`", command)?,
+        }
+    }
+}
+
+/// Takes up to `count` steps, printing the last one.
+fn advance(session: &mut dyn Session, count: usize, out: &mut dyn Write) -> io::Result<()> {
+    let mut last = None;
+    let mut finished = false;
+    for _ in 0..count {
+        match session.step() {
+            Some(event) => last = Some(event),
+            None => {
+                finished = true;
+                break;
+            }
+        }
+    }
+
+    if let Some(event) = last {
+        writeln!(out, "step {}: {}", session.steps(), event)?;
+    }
+    if finished {
+        writeln!(out, "finished after {} steps", session.steps())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Counting down to zero.
+    #[derive(Clone)]
+    struct Countdown(u32);
+
+    impl Display for Countdown {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "at {}", self.0)
+        }
+    }
+
+    impl Simulation for Countdown {
+        fn step(&mut self) -> Option<String> {
+            self.0 = self.0.checked_sub(1)?;
+            Some(format!("down to {}", self.0))
+        }
+    }
+
+    fn run_repl(session: &mut dyn Session, commands: &str) -> String {
+        let mut out = vec![];
+        repl(session, &mut commands.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_repl() {
+        let mut countdown = session(Countdown(5));
+        let out = run_repl(
+            countdown.as_mut(),
+            "\nrun 2\nshow\nback 5\nstep 2\nrun\nrun x\nquit\nshow\n",
+        );
+
+        assert_eq!(
+            out,
+            "at 5
+[0]> step 1: down to 4
+[1]> step 3: down to 2
+[3]> at 2
+[3]> back to step 0
+[0]> step 2: down to 3
+[2]> step 5: down to 0
+finished after 5 steps
+[5]> `run` needs a number of steps
+[5]> "
+        );
+        assert_eq!(countdown.show(), "at 0");
+    }
+
+    #[test]
+    fn test_steppers() {
+        for stepper in STEPPERS {
+            let input = crate::registry::find(stepper.day)
+                .unwrap()
+                .generate(&mut crate::rng::Rng::new(3), 5);
+            let mut session = (stepper.start)(&input).unwrap();
+
+            let start = session.show();
+            assert!(session.step().is_some(), "day {}", stepper.day);
+            assert!(session.back());
+            assert_eq!(session.show(), start);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::error::describe;
+use crate::solution::DynSolution;
+
+/// Answer of a part, or the error it failed with.
+pub type Outcome = std::result::Result<String, String>;
+
+/// Modification time and length of a file, `None` when it doesn't exist.
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Files polled for changes of their modification time or length.
+pub struct Watcher {
+    /// Every file with its stamp at the last poll, `None` before the first one.
+    files: Vec<(PathBuf, Option<Stamp>)>,
+}
+
+impl Watcher {
+    /// Watches `paths`, all of them counting as changed on the first poll.
+    pub fn new(paths: Vec<PathBuf>) -> Watcher {
+        Watcher {
+            files: paths.into_iter().map(|path| (path, None)).collect(),
+        }
+    }
+
+    /// Files modified, created or removed since the last poll.
+    pub fn poll(&mut self) -> Vec<&Path> {
+        let mut changed = vec![];
+        for (path, seen) in &mut self.files {
+            let stamp = std::fs::metadata(&*path)
+                .and_then(|meta| Ok((meta.modified()?, meta.len())))
+                .ok();
+            if *seen != Some(stamp) {
+                *seen = Some(stamp);
+                changed.push(path.as_path());
+            }
+        }
+        changed
+    }
+}
+
+/// Parses `input` and answers both parts, a parse error coming with the line it points at.
+pub fn answer(solution: &dyn DynSolution, input: &str) -> [Outcome; 2] {
+    match solution.parse(input) {
+        Ok(parsed) => {
+            [parsed.part_01(), parsed.part_02()].map(|answer| answer.map_err(|err| describe(&err)))
+        }
+        Err(err) => {
+            let err = describe(&err);
+            [Err(err.clone()), Err(err)]
+        }
+    }
+}
+
+/// One line per part comparing the answers with the previous ones, if any.
+pub fn compare(previous: Option<&[Outcome; 2]>, current: &[Outcome; 2]) -> Vec<String> {
+    let show = |outcome: &Outcome| match outcome {
+        Ok(answer) => answer.clone(),
+        Err(err) => format!("error: {}", err),
+    };
+
+    current
+        .iter()
+        .enumerate()
+        .map(|(ix, now)| {
+            let change = match previous.map(|previous| &previous[ix]) {
+                None => show(now),
+                Some(before) if before == now => format!("{} (unchanged)", show(now)),
+                Some(before) => format!("{} (was {})", show(now), show(before)),
+            };
+            format!("Part {} = {}", ix + 1, change)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn test_poll() {
+        let dir = std::env::temp_dir().join(format!("aoc-2021-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.txt"), dir.join("b.txt"));
+        std::fs::write(&a, "1").unwrap();
+        let _ = std::fs::remove_file(&b);
+
+        let mut watcher = Watcher::new(vec![a.clone(), b.clone()]);
+        assert_eq!(watcher.poll(), [a.as_path(), b.as_path()]);
+        assert!(watcher.poll().is_empty());
+
+        std::fs::write(&b, "22").unwrap();
+        assert_eq!(watcher.poll(), [b.as_path()]);
+        std::fs::write(&a, "333").unwrap();
+        std::fs::remove_file(&b).unwrap();
+        assert_eq!(watcher.poll(), [a.as_path(), b.as_path()]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compare() {
+        let day01 = registry::find(1).unwrap();
+        let first = answer(day01, "199\n200\n208\n210");
+        assert_eq!(first, [Ok("3".to_owned()), Ok("1".to_owned())]);
+        assert_eq!(compare(None, &first), ["Part 1 = 3", "Part 2 = 1"]);
+
+        let second = answer(day01, "199\n200\n208\n210\n211");
+        assert_eq!(
+            compare(Some(&first), &second),
+            ["Part 1 = 4 (was 3)", "Part 2 = 2 (was 1)"]
+        );
+        assert_eq!(
+            compare(Some(&second), &second),
+            ["Part 1 = 4 (unchanged)", "Part 2 = 2 (unchanged)"]
+        );
+
+        let broken = answer(day01, "199\nx");
+        assert!(broken[0].is_err() && broken[1].is_err());
+        assert!(broken[0]
+            .as_ref()
+            .unwrap_err()
+            .ends_with("\n    2 | x\n      | ^"));
+        assert!(compare(Some(&second), &broken)[0].ends_with("(was 4)"));
+    }
+}
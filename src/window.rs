@@ -0,0 +1,331 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::io::{self, Write};
+
+use crate::error::Result;
+
+/// Statistics of one window of depth measurements.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowStats {
+    /// Index of the first measurement of the window.
+    pub start: usize,
+    pub len: usize,
+    pub sum: u64,
+    pub mean: f64,
+    pub min: usize,
+    pub max: usize,
+    /// Middle measurement, or the mean of the two middle ones when the window is even.
+    pub median: f64,
+}
+
+impl WindowStats {
+    pub const CSV_HEADER: &'static str = "start,len,sum,mean,min,max,median";
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.start, self.len, self.sum, self.mean, self.min, self.max, self.median
+        )
+    }
+}
+
+/// Writes the statistics of every window as CSV, header first.
+pub fn write_csv<W: Write>(
+    out: &mut W,
+    windows: impl IntoIterator<Item = WindowStats>,
+) -> io::Result<()> {
+    writeln!(out, "{}", WindowStats::CSV_HEADER)?;
+    for stats in windows {
+        writeln!(out, "{}", stats.to_csv())?;
+    }
+    Ok(())
+}
+
+/// Windows of `size` measurements sliding over measurements fed one at a time, one window
+/// starting every `step` measurements. Only the measurements of the current window are kept.
+pub struct Windows {
+    size: usize,
+    step: usize,
+    /// Number of measurements pushed so far, the index of the next one.
+    pushed: usize,
+    values: VecDeque<usize>,
+    sum: u64,
+    /// Indices and values of the measurements that can still become the window's minimum
+    /// (resp. maximum), that is the ones not followed by a smaller (resp. larger) one.
+    minima: VecDeque<(usize, usize)>,
+    maxima: VecDeque<(usize, usize)>,
+    median: Median,
+}
+
+impl Windows {
+    /// Both `size` and `step` must be at least 1.
+    pub fn new(size: usize, step: usize) -> Windows {
+        assert!(size > 0 && step > 0, "windows and steps are not empty");
+        Windows {
+            size,
+            step,
+            pushed: 0,
+            values: VecDeque::with_capacity(size + 1),
+            sum: 0,
+            minima: VecDeque::new(),
+            maxima: VecDeque::new(),
+            median: Median::default(),
+        }
+    }
+
+    /// Adds the next measurement, returning the statistics of the window it completes if one
+    /// starts at a multiple of the step.
+    pub fn push(&mut self, value: usize) -> Option<WindowStats> {
+        let index = self.pushed;
+        self.pushed += 1;
+
+        self.values.push_back(value);
+        self.sum += value as u64;
+        while self.minima.back().is_some_and(|&(_, min)| min >= value) {
+            self.minima.pop_back();
+        }
+        self.minima.push_back((index, value));
+        while self.maxima.back().is_some_and(|&(_, max)| max <= value) {
+            self.maxima.pop_back();
+        }
+        self.maxima.push_back((index, value));
+        self.median.insert((value, index));
+
+        if self.values.len() > self.size {
+            let dropped = index - self.size;
+            let value = self.values.pop_front().unwrap_or_default();
+            self.sum -= value as u64;
+            if self.minima.front().is_some_and(|&(ix, _)| ix == dropped) {
+                self.minima.pop_front();
+            }
+            if self.maxima.front().is_some_and(|&(ix, _)| ix == dropped) {
+                self.maxima.pop_front();
+            }
+            self.median.remove((value, dropped));
+        }
+
+        let start = self.pushed.checked_sub(self.size)?;
+        if start % self.step != 0 {
+            return None;
+        }
+        Some(WindowStats {
+            start,
+            len: self.size,
+            sum: self.sum,
+            mean: self.sum as f64 / self.size as f64,
+            min: self.minima.front()?.1,
+            max: self.maxima.front()?.1,
+            median: self.median.median()?,
+        })
+    }
+}
+
+/// Statistics of every window of `size` measurements of `depths` starting at a multiple of
+/// `step`, the last measurements being left out when they don't fill a window.
+pub fn windows(depths: &[usize], size: usize, step: usize) -> Vec<WindowStats> {
+    let mut windows = Windows::new(size, step);
+    depths
+        .iter()
+        .filter_map(|&depth| windows.push(depth))
+        .collect()
+}
+
+/// Writes the statistics of every window of `depths` as CSV as soon as it is complete, so that
+/// long inputs are never held in memory. The windows before a bad measurement are still written.
+pub fn stream_csv<W: Write>(
+    out: &mut W,
+    depths: impl IntoIterator<Item = Result<usize>>,
+    size: usize,
+    step: usize,
+) -> Result<()> {
+    let mut windows = Windows::new(size, step);
+    let mut failure = None;
+    let stats = depths
+        .into_iter()
+        .map_while(|depth| depth.map_err(|err| failure = Some(err)).ok())
+        .filter_map(|depth| windows.push(depth));
+    write_csv(out, stats)?;
+    out.flush()?;
+    failure.map_or(Ok(()), Err)
+}
+
+/// Median of a sliding window: its lower half in a max-heap and its upper half in a min-heap.
+/// Measurements leaving the window are only dropped once they reach the top of their heap, or
+/// when too many of them piled up.
+#[derive(Default)]
+struct Median {
+    /// Values and indices of the lower half, one more than the upper half when odd.
+    low: BinaryHeap<(usize, usize)>,
+    high: BinaryHeap<Reverse<(usize, usize)>>,
+    /// Indices of the measurements that left the window but are still in a heap.
+    removed: HashSet<usize>,
+    low_len: usize,
+    high_len: usize,
+}
+
+impl Median {
+    fn insert(&mut self, entry: (usize, usize)) {
+        if self.low.peek().is_none_or(|&top| entry <= top) {
+            self.low.push(entry);
+            self.low_len += 1;
+        } else {
+            self.high.push(Reverse(entry));
+            self.high_len += 1;
+        }
+        self.rebalance();
+    }
+
+    fn remove(&mut self, entry: (usize, usize)) {
+        self.removed.insert(entry.1);
+        // entries are unique and the tops are never removed ones, so this is the entry's heap
+        if self.low.peek().is_some_and(|&top| entry <= top) {
+            self.low_len -= 1;
+        } else {
+            self.high_len -= 1;
+        }
+
+        if self.removed.len() > self.low_len + self.high_len {
+            let removed = std::mem::take(&mut self.removed);
+            self.low.retain(|entry| !removed.contains(&entry.1));
+            self.high
+                .retain(|Reverse(entry)| !removed.contains(&entry.1));
+        }
+        self.rebalance();
+    }
+
+    fn rebalance(&mut self) {
+        self.prune();
+        while self.low_len > self.high_len + 1 {
+            if let Some(top) = self.low.pop() {
+                self.high.push(Reverse(top));
+                self.low_len -= 1;
+                self.high_len += 1;
+            }
+            self.prune();
+        }
+        while self.high_len > self.low_len {
+            if let Some(Reverse(top)) = self.high.pop() {
+                self.low.push(top);
+                self.high_len -= 1;
+                self.low_len += 1;
+            }
+            self.prune();
+        }
+    }
+
+    /// Drops the removed entries at the top of both heaps.
+    fn prune(&mut self) {
+        while let Some(&(_, ix)) = self.low.peek() {
+            if !self.removed.remove(&ix) {
+                break;
+            }
+            self.low.pop();
+        }
+        while let Some(&Reverse((_, ix))) = self.high.peek() {
+            if !self.removed.remove(&ix) {
+                break;
+            }
+            self.high.pop();
+        }
+    }
+
+    fn median(&self) -> Option<f64> {
+        let &(low, _) = self.low.peek()?;
+        if self.low_len > self.high_len {
+            Some(low as f64)
+        } else {
+            let Reverse((high, _)) = self.high.peek()?;
+            Some((low + high) as f64 / 2.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::d01;
+    use crate::error::Error;
+    use crate::rng::Rng;
+
+    fn brute_force(depths: &[usize], size: usize, step: usize) -> Vec<WindowStats> {
+        depths
+            .windows(size)
+            .enumerate()
+            .step_by(step)
+            .map(|(start, window)| {
+                let mut sorted = window.to_vec();
+                sorted.sort_unstable();
+                let sum = window.iter().sum::<usize>() as u64;
+                WindowStats {
+                    start,
+                    len: size,
+                    sum,
+                    mean: sum as f64 / size as f64,
+                    min: sorted[0],
+                    max: sorted[size - 1],
+                    median: (sorted[(size - 1) / 2] + sorted[size / 2]) as f64 / 2.0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_windows() {
+        let depths = d01::parse("199\n200\n208\n210\n200\n207\n240\n269\n260\n263").unwrap();
+        let stats = windows(&depths, 3, 1);
+        let increases = stats.windows(2).filter(|w| w[0].sum < w[1].sum).count();
+        assert_eq!(increases, d01::solve_part2(&depths).unwrap());
+
+        assert_eq!(
+            windows(&depths, 4, 3)[1],
+            WindowStats {
+                start: 3,
+                len: 4,
+                sum: 857,
+                mean: 214.25,
+                min: 200,
+                max: 240,
+                median: 208.5,
+            }
+        );
+
+        let mut rng = Rng::new(7);
+        for _ in 0..50 {
+            // few distinct values, so that there are plenty of ties
+            let depths = (0..rng.between(0, 60))
+                .map(|_| rng.between(0, 8) as usize)
+                .collect::<Vec<_>>();
+            let (size, step) = (rng.between(1, 12) as usize, rng.between(1, 4) as usize);
+            assert_eq!(
+                windows(&depths, size, step),
+                brute_force(&depths, size, step),
+                "{:?} size {} step {}",
+                depths,
+                size,
+                step
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut out = vec![];
+        write_csv(&mut out, windows(&[3, 1, 2], 2, 1)).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "start,len,sum,mean,min,max,median\n0,2,4,2,1,3,2\n1,2,3,1.5,1,2,1.5\n"
+        );
+    }
+
+    #[test]
+    fn test_stream_csv() {
+        let mut out = vec![];
+        let res = stream_csv(&mut out, d01::depths("3\n1\nx\n2".as_bytes()), 2, 1);
+        assert!(matches!(res, Err(Error::Parse { line: 3, .. })));
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "start,len,sum,mean,min,max,median\n0,2,4,2,1,3,2\n"
+        );
+    }
+}